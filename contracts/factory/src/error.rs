@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,18 +6,90 @@ pub enum ContractError {
   #[error("{0}")]
   Std(#[from] StdError),
 
+  #[error("{0}")]
+  OverflowError(#[from] OverflowError),
+
   #[error("Unauthorized")]
   Unauthorized {},
 
   #[error("Pair already exists")]
   PairExists {},
 
+  #[error("Pair assets must be different")]
+  SameAssets {},
+
+  #[error("Invalid asset info: {asset_info}")]
+  InvalidAssetInfo { asset_info: String },
+
+  #[error("Decimals of the native token {denom} are not registered")]
+  NativeTokenDecimalsNotFound { denom: String },
+
+  #[error("Decimals must be less than or equal to 18")]
+  InvalidDecimals {},
+
+  #[error("Stable pair assets must have the same decimals")]
+  StableDecimalsMismatch {},
+
+  #[error("Pair creation fee must be paid exactly")]
+  InvalidPairCreationFee {},
+
+  #[error("Fee collector is not set")]
+  FeeCollectorNotSet {},
+
   #[error("Pair type already exists")]
   PairTypeExists {},
 
   #[error("Invalid tick space")]
   InvalidTickSpace {},
 
+  #[error("Cannot propose the ownership that is already expired")]
+  Expired {},
+
+  #[error("Ownership proposal not found")]
+  OwnershipProposalNotFound {},
+
+  #[error("Ownership proposal expired")]
+  OwnershipProposalExpired {},
+
+  #[error("Action must be queued while the timelock is set")]
+  Timelocked {},
+
+  #[error("Pending action not found")]
+  PendingActionNotFound {},
+
+  #[error("Pending action is executable at {executable_at}")]
+  PendingActionNotReady { executable_at: u64 },
+
   #[error("Invalid fee rate")]
   InvalidFeeRate {},
-}
\ No newline at end of file
+
+  #[error("Invalid amp")]
+  InvalidAmp {},
+
+  #[error("Max swap ticks must be greater than 0")]
+  InvalidMaxSwapTicks {},
+
+  #[error("History sizes must be greater than 0")]
+  InvalidHistorySize {},
+
+  #[error("Max referral commission must be less than or equal to 10000 bps")]
+  InvalidReferralCommission {},
+
+  #[error("Stable pair code id is not set")]
+  StablePairCodeIdNotSet {},
+
+  #[error("Fee discount must be less than or equal to 1")]
+  InvalidFeeDiscount {},
+
+  #[error("Quote asset already exists")]
+  QuoteAssetExists {},
+
+  #[error("Quote asset not found")]
+  QuoteAssetNotFound {},
+
+  #[error("Can't swap")]
+  CanNotSwap {},
+
+  #[error("Minimum receive assertion, minimum: {minimum_receive}, receive: {receive}")]
+  MinimumReceiveAssertion { minimum_receive: Uint128, receive: Uint128 },
+}
@@ -1,15 +1,59 @@
-use cosmwasm_std::{to_binary, Binary, Decimal, DepsMut, Env, MessageInfo, Order, QueryRequest, Reply, ReplyOn, Response,
-  StdError, StdResult, SubMsg, WasmMsg, WasmQuery};
+use cosmwasm_std::{to_binary, from_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+  QueryRequest, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, Uint256, WasmMsg, WasmQuery};
+use cw0::Expiration;
+use cw_storage_plus::U64Key;
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
 
-use wineswap::factory::{Config, InstantiateMsg, ExecuteMsg, PairInfo, PairType};
-use wineswap::pair::{Config as PairConfig, InstantiateMsg as PairInstantiateMsg};
-use wineswap::asset::AssetInfo;
+use wineswap::factory::{Config, Cw20HookMsg, InstantiateMsg, ExecuteMsg, MigrateMsg, PairInfo, PairType, PendingAction,
+  TimelockAction};
+use wineswap::pair::{Config as PairConfig, Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg,
+  ConfigResponse as PairConfigResponse, HistoryConfig, InstantiateMsg as PairInstantiateMsg, PairInfoResponse,
+  QueryMsg as PairQueryMsg, Referral, TickInfoResponse};
+use wineswap::stable_pair::{ExecuteMsg as StablePairExecuteMsg, InstantiateMsg as StablePairInstantiateMsg};
+use wineswap::asset::{Asset, AssetInfo, TokenNumber};
+use wineswap::ownership::OwnershipProposal;
+use wineswap_math::swap::{compute_swap_tick, marginal_return};
+use wineswap_math::stable::MAX_AMP;
+use wineswap_math::price::MAX_DECIMALS;
 use protobuf::Message;
 
-use crate::state::{pair_key, asset_infos_key, FactoryContract, TmpPairInfo};
+use crate::state::{pair_key, FactoryContract, TmpPairInfo};
 use crate::response::MsgInstantiateContractResponse;
 use crate::error::ContractError;
 
+// offer amount is split by at most this number of steps (+ crossed ticks)
+const SPLIT_STEPS: u128 = 50;
+// max referral commission of the pairs
+const MAX_BPS: u16 = 10000;
+
+/// `UpdateConfig` of the execute msg and the timelock action
+pub struct UpdateConfigParams {
+  pub token_code_id: Option<u64>,
+  pub pair_code_id: Option<u64>,
+  pub stable_pair_code_id: Option<u64>,
+  pub max_swap_ticks: Option<u32>,
+  pub max_referral_commission_bps: Option<u16>,
+  pub timelock_delay: Option<u64>,
+  pub pair_creation_fee: Option<Asset>,
+  pub fee_collector: Option<String>,
+}
+
+/// `CreatePair` of the execute msg and the cw20 hook
+pub struct CreatePairParams {
+  pub asset_infos: [AssetInfo; 2],
+  pub pair_type: String,
+  pub initial_price: Decimal,
+}
+
+/// `SplitSwap` of the execute msg and the cw20 hook, with the offer asset and the validated receiver
+pub struct SplitSwapParams {
+  pub offer_asset: Asset,
+  pub ask_asset_info: AssetInfo,
+  pub minimum_receive: Option<Uint128>,
+  pub to: Option<Addr>,
+  pub referral: Option<Referral>,
+}
+
 impl<'a> FactoryContract<'a> {
   pub fn instantiate(
     &self,
@@ -22,8 +66,28 @@ impl<'a> FactoryContract<'a> {
       owner: deps.api.addr_validate(&msg.owner)?,
       pair_code_id: msg.pair_code_id,
       token_code_id: msg.token_code_id,
+      quote_assets: msg.quote_assets,
+      stable_pair_code_id: msg.stable_pair_code_id,
+      max_swap_ticks: msg.max_swap_ticks,
+      max_referral_commission_bps: msg.max_referral_commission_bps.unwrap_or_default(),
+      ownership_proposal: None,
+      timelock_delay: msg.timelock_delay.unwrap_or_default(),
+      pair_creation_fee: msg.pair_creation_fee.filter(|fee| !fee.amount.is_zero()),
+      fee_collector: msg.fee_collector.map(|fee_collector| deps.api.addr_validate(&fee_collector)).transpose()?,
     };
 
+    if config.max_swap_ticks == Some(0) {
+      return Err(StdError::generic_err("Invalid max swap ticks"));
+    }
+
+    if config.max_referral_commission_bps > MAX_BPS {
+      return Err(StdError::generic_err("Invalid max referral commission"));
+    }
+
+    if config.pair_creation_fee.is_some() && config.fee_collector.is_none() {
+      return Err(StdError::generic_err("Fee collector is not set"));
+    }
+
     self.config.save(deps.storage, &config)?;
 
     Ok(Response::new())
@@ -44,14 +108,16 @@ impl<'a> FactoryContract<'a> {
     }))?;
     let liquidity_token = pair_config.liquidity_token;
 
-    self.pairs.save(
+    self.save_pair(
         deps.storage,
         tmp_pair_info.pair_key,
         &PairInfo {
           liquidity_token: liquidity_token.clone(),
           contract_addr: deps.api.addr_validate(pair_contract)?,
           asset_infos: tmp_pair_info.asset_infos,
-          pair_type: tmp_pair_info.pair_type
+          pair_type: tmp_pair_info.pair_type,
+          asset_decimals: Some(tmp_pair_info.asset_decimals),
+          creator: Some(tmp_pair_info.creator),
         }
     )?;
 
@@ -61,6 +127,21 @@ impl<'a> FactoryContract<'a> {
     )
 }
 
+  pub fn migrate(&self, deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // index pairs created before asset index
+    let pairs: Vec<(Vec<u8>, PairInfo)> = self.pairs
+      .range(deps.storage, None, None, Order::Ascending)
+      .collect::<StdResult<_>>()?;
+
+    for (key, pair_info) in pairs.iter() {
+      self.save_pair(deps.storage, key.clone(), pair_info)?;
+    }
+
+    Ok(Response::new()
+      .add_attribute("action", "migrate")
+      .add_attribute("indexed_pairs", pairs.len().to_string()))
+  }
+
   pub fn execute(
     &self, 
     deps: DepsMut,
@@ -70,20 +151,110 @@ impl<'a> FactoryContract<'a> {
   ) -> Result<Response, ContractError> {
     match msg{
       ExecuteMsg::UpdateConfig {
-        owner,
         token_code_id,
         pair_code_id,
-      } => self.update_config(deps, env, info, owner, token_code_id, pair_code_id), 
+        stable_pair_code_id,
+        max_swap_ticks,
+        max_referral_commission_bps,
+        timelock_delay,
+        pair_creation_fee,
+        fee_collector,
+      } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.update_config(deps, env, info, UpdateConfigParams {
+          token_code_id,
+          pair_code_id,
+          stable_pair_code_id,
+          max_swap_ticks,
+          max_referral_commission_bps,
+          timelock_delay,
+          pair_creation_fee,
+          fee_collector,
+        })
+      },
+      ExecuteMsg::ProposeNewOwner { owner, expires } => self.propose_new_owner(deps, env, info, owner, expires),
+      ExecuteMsg::ClaimOwnership {} => self.claim_ownership(deps, env, info),
+      ExecuteMsg::DropOwnershipProposal {} => self.drop_ownership_proposal(deps, env, info),
+      ExecuteMsg::UpdatePairConfig {
+        pair,
+        max_swap_ticks,
+        history_config,
+      } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.update_pair_config(deps, env, info, pair, max_swap_ticks, history_config)
+      },
       ExecuteMsg::CreatePair {
         asset_infos,
         pair_type,
         initial_price,
-      } => self.create_pair(deps, env, info, asset_infos, pair_type, initial_price),
+      } => {
+        let paid_fee = self.sent_pair_creation_fee(deps.as_ref(), &info)?;
+        self.create_pair(deps, env, info.sender, paid_fee, CreatePairParams { asset_infos, pair_type, initial_price })
+      },
+      ExecuteMsg::DeregisterPair {
+        asset_infos,
+        pair_type,
+      } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.deregister_pair(deps, env, info, asset_infos, pair_type)
+      },
       ExecuteMsg::AddPairType {
         type_name,
         tick_space,
         fee_rate,
-      } => self.add_pair_type(deps, env, info, type_name, tick_space, fee_rate),
+        amp,
+      } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.add_pair_type(deps, env, info, PairType { type_name, tick_space, fee_rate, amp })
+      },
+      ExecuteMsg::AddQuoteAsset { asset_info } => self.add_quote_asset(deps, env, info, asset_info),
+      ExecuteMsg::RemoveQuoteAsset { asset_info } => self.remove_quote_asset(deps, env, info, asset_info),
+      ExecuteMsg::AddNativeTokenDecimals { denom, decimals }
+        => self.add_native_token_decimals(deps, env, info, denom, decimals),
+      ExecuteMsg::SetFeeDiscount { address, discount } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.set_fee_discount(deps, env, info, address, discount)
+      },
+      ExecuteMsg::MigrateContract { contract, code_id, msg } => {
+        self.assert_not_timelocked(deps.as_ref())?;
+        self.migrate_contract(deps, info, contract, code_id, msg)
+      },
+      ExecuteMsg::QueueAction { action } => self.queue_action(deps, env, info, action),
+      ExecuteMsg::ExecuteAction { id } => self.execute_action(deps, env, info, id),
+      ExecuteMsg::CancelAction { id } => self.cancel_action(deps, env, info, id),
+      ExecuteMsg::Receive(msg) => self.receive_cw20(deps, env, info, msg),
+      ExecuteMsg::SplitSwap {
+        offer_asset,
+        ask_asset_info,
+        minimum_receive,
+        to,
+        referral,
+      } => {
+        if !offer_asset.is_native_token() {
+          return Err(ContractError::Unauthorized {});
+        }
+        offer_asset.assert_sent_native_token_balance(&info)?;
+
+        let to_addr = if let Some(to_addr) = to {
+          Some(deps.api.addr_validate(&to_addr)?)
+        } else {
+          None
+        };
+
+        self.split_swap(deps, env, info.sender, SplitSwapParams {
+          offer_asset,
+          ask_asset_info,
+          minimum_receive,
+          to: to_addr,
+          referral,
+        })
+      },
+      ExecuteMsg::AssertMinimumReceive {
+        asset_info,
+        prev_balance,
+        minimum_receive,
+        receiver,
+      } => self.assert_minimum_receive(deps.as_ref(), asset_info, prev_balance, minimum_receive, receiver),
     }
   }
 }
@@ -95,9 +266,7 @@ impl<'a> FactoryContract<'a> {
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    owner: Option<String>,
-    token_code_id: Option<u64>,
-    pair_code_id: Option<u64>,
+    params: UpdateConfigParams,
   ) -> Result<Response, ContractError> {
     let mut config: Config = self.config.load(deps.storage)?;
     
@@ -105,9 +274,16 @@ impl<'a> FactoryContract<'a> {
       return Err(ContractError::Unauthorized {})
     }
 
-    if let Some(owner) = owner {
-      config.owner = deps.api.addr_validate(&owner)?;
-    }
+    let UpdateConfigParams {
+      token_code_id,
+      pair_code_id,
+      stable_pair_code_id,
+      max_swap_ticks,
+      max_referral_commission_bps,
+      timelock_delay,
+      pair_creation_fee,
+      fee_collector,
+    } = params;
 
     if let Some(token_code_id) = token_code_id {
       config.token_code_id = token_code_id;
@@ -117,20 +293,176 @@ impl<'a> FactoryContract<'a> {
       config.pair_code_id = pair_code_id;
     }
 
+    if let Some(stable_pair_code_id) = stable_pair_code_id {
+      config.stable_pair_code_id = Some(stable_pair_code_id);
+    }
+
+    if let Some(max_swap_ticks) = max_swap_ticks {
+      if max_swap_ticks == 0 {
+        return Err(ContractError::InvalidMaxSwapTicks {})
+      }
+      config.max_swap_ticks = Some(max_swap_ticks);
+    }
+
+    if let Some(max_referral_commission_bps) = max_referral_commission_bps {
+      if max_referral_commission_bps > MAX_BPS {
+        return Err(ContractError::InvalidReferralCommission {})
+      }
+      config.max_referral_commission_bps = max_referral_commission_bps;
+    }
+
+    if let Some(timelock_delay) = timelock_delay {
+      config.timelock_delay = timelock_delay;
+    }
+
+    if let Some(pair_creation_fee) = pair_creation_fee {
+      config.pair_creation_fee = Some(pair_creation_fee).filter(|fee| !fee.amount.is_zero());
+    }
+
+    if let Some(fee_collector) = fee_collector {
+      config.fee_collector = Some(deps.api.addr_validate(&fee_collector)?);
+    }
+
+    if config.pair_creation_fee.is_some() && config.fee_collector.is_none() {
+      return Err(ContractError::FeeCollectorNotSet {})
+    }
+
     self.config.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
   }
 
-  pub fn create_pair(
+  pub fn propose_new_owner(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires: Expiration,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if expires.is_expired(&env.block) {
+      return Err(ContractError::Expired {})
+    }
+
+    let owner = deps.api.addr_validate(&owner)?;
+    config.ownership_proposal = Some(OwnershipProposal { owner: owner.clone(), expires });
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "propose_new_owner")
+      .add_attribute("owner", owner.to_string())
+      .add_attribute("expires", expires.to_string())
+    )
+  }
+
+  pub fn claim_ownership(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    let proposal = config.ownership_proposal.ok_or(ContractError::OwnershipProposalNotFound {})?;
+
+    if info.sender != proposal.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if proposal.expires.is_expired(&env.block) {
+      return Err(ContractError::OwnershipProposalExpired {})
+    }
+
+    config.owner = proposal.owner;
+    config.ownership_proposal = None;
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "claim_ownership")
+      .add_attribute("owner", config.owner.to_string())
+    )
+  }
+
+  pub fn drop_ownership_proposal(
     &self,
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    asset_infos: [AssetInfo; 2],
-    pair_type: String,
-    initial_price: Decimal,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if config.ownership_proposal.is_none() {
+      return Err(ContractError::OwnershipProposalNotFound {})
+    }
+
+    config.ownership_proposal = None;
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "drop_ownership_proposal"))
+  }
+
+  pub fn update_pair_config(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair: String,
+    max_swap_ticks: Option<u32>,
+    history_config: Option<HistoryConfig>,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if max_swap_ticks == Some(0) {
+      return Err(ContractError::InvalidMaxSwapTicks {})
+    }
+
+    if let Some(history_config) = &history_config {
+      if history_config.hourly_size == 0 || history_config.daily_size == 0 {
+        return Err(ContractError::InvalidHistorySize {})
+      }
+    }
+
+    let pair = deps.api.addr_validate(&pair)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::UpdateConfig { max_swap_ticks, history_config })?,
+        funds: vec![],
+      })
+      .add_attribute("action", "update_pair_config")
+      .add_attribute("pair", pair.to_string())
+    )
+  }
+
+  pub fn create_pair(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    creator: Addr,
+    paid_fee: Option<Asset>,
+    params: CreatePairParams,
   ) -> Result<Response, ContractError> {
+    let CreatePairParams { asset_infos, pair_type, initial_price } = params;
+    if asset_infos[0].equal(&asset_infos[1]) {
+      return Err(ContractError::SameAssets {})
+    }
+
+    let asset_decimals = [
+      self.asset_decimals(deps.as_ref(), &asset_infos[0])?,
+      self.asset_decimals(deps.as_ref(), &asset_infos[1])?,
+    ];
+
     let key = pair_key(&asset_infos, pair_type.clone());
 
     if let Ok(Some(_)) = self.pairs.may_load(deps.storage, key.clone()) {
@@ -139,37 +471,116 @@ impl<'a> FactoryContract<'a> {
 
     let type_data = self.pair_type.load(deps.storage, pair_type.as_bytes().to_vec())?; 
 
+    let config = self.config.load(deps.storage)?;
+    let mut fee_msgs: Vec<CosmosMsg> = vec![];
+    match config.pair_creation_fee {
+      Some(fee) => {
+        let paid_fee = match paid_fee {
+          Some(paid_fee) if paid_fee.info.equal(&fee.info) && paid_fee.amount >= fee.amount => paid_fee,
+          _ => return Err(ContractError::InvalidPairCreationFee {}),
+        };
+        let fee_collector = config.fee_collector.ok_or(ContractError::FeeCollectorNotSet {})?;
+
+        // the excess goes back to the creator
+        let refund_asset = Asset {
+          info: fee.info.clone(),
+          amount: paid_fee.amount.checked_sub(fee.amount)?,
+        };
+        fee_msgs.push(fee.into_msg(&deps.querier, fee_collector)?);
+        if !refund_asset.amount.is_zero() {
+          fee_msgs.push(refund_asset.into_msg(&deps.querier, creator.clone())?);
+        }
+      }
+      None => {
+        // sent cw20 would be stuck without the fee
+        if paid_fee.is_some() {
+          return Err(ContractError::InvalidPairCreationFee {})
+        }
+      }
+    }
+
     self.temp_pair_info.save(
       deps.storage,
       &TmpPairInfo {
         pair_key: key,
         asset_infos: asset_infos.clone(),
-        pair_type: type_data.clone()
+        pair_type: type_data.clone(),
+        asset_decimals,
+        creator,
       }
     )?;
 
-    let config = self.config.load(deps.storage)?;
+    let pair_msg = if let Some(amp) = type_data.amp {
+      // stable pair type, initial_price is not used
+      if asset_decimals[0] != asset_decimals[1] {
+        return Err(ContractError::StableDecimalsMismatch {})
+      }
+      let stable_pair_code_id = config.stable_pair_code_id.ok_or(ContractError::StablePairCodeIdNotSet {})?;
+      WasmMsg::Instantiate {
+        code_id: stable_pair_code_id,
+        funds: vec![],
+        admin: Some(env.contract.address.to_string()),
+        label: "".to_string(),
+        msg: to_binary(&StablePairInstantiateMsg {
+          asset_infos: asset_infos.clone(),
+          token_code_id: config.token_code_id,
+          tick_space: type_data.tick_space,
+          fee_rate: type_data.fee_rate,
+          amp,
+          asset_decimals: Some(asset_decimals),
+        })?
+      }
+    } else {
+      WasmMsg::Instantiate {
+        code_id: config.pair_code_id,
+        funds: vec![],
+        admin: Some(env.contract.address.to_string()),
+        label: "".to_string(),
+        msg: to_binary(&PairInstantiateMsg {
+          asset_infos: asset_infos.clone(),
+          token_code_id: config.token_code_id,
+          initial_price,
+          tick_space: type_data.tick_space,
+          fee_rate: type_data.fee_rate,
+          history_config: None,
+          max_swap_ticks: config.max_swap_ticks,
+          asset_decimals: Some(asset_decimals),
+        })?
+      }
+    };
+
     Ok(Response::new()
       .add_attribute("action", "create_pair")
-      .add_attribute("pair", &format!("{}-{}", asset_infos[0], asset_infos[1]))
+      .add_attribute("pair", format!("{}-{}", asset_infos[0], asset_infos[1]))
       .add_submessage(SubMsg {
         id: 1,
         gas_limit: None,
-        msg: WasmMsg::Instantiate {
-          code_id: config.pair_code_id,
-          funds: vec![],
-          admin: None,
-          label: "".to_string(),
-          msg: to_binary(&PairInstantiateMsg {
-            asset_infos,
-            token_code_id: config.token_code_id,
-            initial_price,
-            tick_space: type_data.tick_space,
-            fee_rate: type_data.fee_rate
-          })?
-        }.into(),
+        msg: pair_msg.into(),
         reply_on: ReplyOn::Success
-      }))
+      })
+      .add_messages(fee_msgs))
+  }
+
+  pub fn deregister_pair(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: String,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let pair_info = self.remove_pair(deps.storage, pair_key(&asset_infos, pair_type.clone()))?;
+
+    Ok(Response::new()
+      .add_attribute("action", "deregister_pair")
+      .add_attribute("pair", pair_info.contract_addr.to_string())
+      .add_attribute("pair_type", pair_type)
+    )
   }
 
   pub fn add_pair_type(
@@ -177,15 +588,15 @@ impl<'a> FactoryContract<'a> {
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    type_name: String,
-    tick_space: u16,
-    fee_rate: Decimal
+    pair_type: PairType,
   ) -> Result<Response, ContractError> {
     let config = self.config.load(deps.storage)?;
     if info.sender != config.owner {
       return Err(ContractError::Unauthorized {})
     }
 
+    let PairType { type_name, tick_space, fee_rate, amp } = pair_type;
+
     if fee_rate < Decimal::zero() || fee_rate > Decimal::one() {
       return Err(ContractError::InvalidFeeRate {})
     }
@@ -194,6 +605,12 @@ impl<'a> FactoryContract<'a> {
       return Err(ContractError::InvalidTickSpace {})
     }
 
+    if let Some(amp) = amp {
+      if amp == 0 || amp > MAX_AMP {
+        return Err(ContractError::InvalidAmp {})
+      }
+    }
+
     let key = type_name.as_bytes().to_vec();
 
     if let Ok(Some(_)) = self.pair_type.may_load(deps.storage, key.clone()) {
@@ -204,7 +621,8 @@ impl<'a> FactoryContract<'a> {
     self.pair_type.save(deps.storage, key, &PairType {
       type_name: type_name.clone(),
       tick_space,
-      fee_rate
+      fee_rate,
+      amp,
     })?;
 
     Ok(Response::new()
@@ -212,26 +630,704 @@ impl<'a> FactoryContract<'a> {
       .add_attribute("type_name", type_name)
       .add_attribute("tick_space", tick_space.to_string())
       .add_attribute("fee_rate", fee_rate.to_string())
+      .add_attribute("amp", amp.map(|amp| amp.to_string()).unwrap_or_default())
+    )
+  }
+
+  pub fn add_quote_asset(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if config.quote_assets.iter().any(|quote_asset| quote_asset.equal(&asset_info)) {
+      return Err(ContractError::QuoteAssetExists {})
+    }
+
+    config.quote_assets.push(asset_info.clone());
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "add_quote_asset")
+      .add_attribute("asset_info", asset_info.to_string())
+    )
+  }
+
+  pub fn add_native_token_decimals(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+    decimals: u8,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if denom.is_empty() {
+      return Err(ContractError::InvalidAssetInfo { asset_info: denom })
+    }
+
+    if decimals > MAX_DECIMALS {
+      return Err(ContractError::InvalidDecimals {})
+    }
+
+    self.native_token_decimals.save(deps.storage, denom.as_bytes(), &decimals)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "add_native_token_decimals")
+      .add_attribute("denom", denom)
+      .add_attribute("decimals", decimals.to_string())
+    )
+  }
+
+  pub fn set_fee_discount(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    discount: Decimal,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if discount > Decimal::one() {
+      return Err(ContractError::InvalidFeeDiscount {})
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    if discount.is_zero() {
+      self.fee_discounts.remove(deps.storage, &address);
+    } else {
+      self.fee_discounts.save(deps.storage, &address, &discount)?;
+    }
+
+    Ok(Response::new()
+      .add_attribute("action", "set_fee_discount")
+      .add_attribute("address", address.to_string())
+      .add_attribute("discount", discount.to_string())
+    )
+  }
+
+  pub fn remove_quote_asset(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let len = config.quote_assets.len();
+    config.quote_assets.retain(|quote_asset| !quote_asset.equal(&asset_info));
+    if config.quote_assets.len() == len {
+      return Err(ContractError::QuoteAssetNotFound {})
+    }
+
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "remove_quote_asset")
+      .add_attribute("asset_info", asset_info.to_string())
     )
   }
 }
 
+/// timelock
 impl<'a> FactoryContract<'a> {
-  pub fn ust_pair_existence(&self, deps: &DepsMut, asset_info: AssetInfo) -> bool {
-    let uusd = AssetInfo::NativeToken{ denom: "uusd".to_string() };
-    let asset_infos_key = asset_infos_key(&[uusd, asset_info]);
-    let pks: Vec<_> = self.pairs
-      .idx
-      .asset_infos
-      .prefix(asset_infos_key)
-      .keys(deps.storage, None, None, Order::Ascending)
-      .take(1)
-      .collect();
-
-    if pks.len() == 0{
-      return false
-    } else {
-      return true
+  pub fn queue_action(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: TimelockAction,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let id = self.next_action_id.may_load(deps.storage)?.unwrap_or_default();
+    let queued_at = env.block.time.seconds();
+    let executable_at = queued_at + config.timelock_delay;
+    self.pending_actions.save(deps.storage, U64Key::new(id), &PendingAction { id, action, executable_at, queued_at })?;
+    self.next_action_id.save(deps.storage, &(id + 1))?;
+
+    Ok(Response::new()
+      .add_attribute("action", "queue_action")
+      .add_attribute("id", id.to_string())
+      .add_attribute("executable_at", executable_at.to_string())
+    )
+  }
+
+  pub fn execute_action(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let pending_action = self.pending_actions.may_load(deps.storage, U64Key::new(id))?
+      .ok_or(ContractError::PendingActionNotFound {})?;
+
+    // the delay may have been raised after the action is queued
+    let executable_at = pending_action.executable_at.max(pending_action.queued_at + config.timelock_delay);
+    if env.block.time.seconds() < executable_at {
+      return Err(ContractError::PendingActionNotReady { executable_at })
+    }
+
+    self.pending_actions.remove(deps.storage, U64Key::new(id));
+
+    // owner checks of the actions pass with the same sender
+    let res = match pending_action.action {
+      TimelockAction::UpdateConfig {
+        token_code_id,
+        pair_code_id,
+        stable_pair_code_id,
+        max_swap_ticks,
+        max_referral_commission_bps,
+        timelock_delay,
+        pair_creation_fee,
+        fee_collector,
+      } => self.update_config(deps, env, info, UpdateConfigParams {
+        token_code_id,
+        pair_code_id,
+        stable_pair_code_id,
+        max_swap_ticks,
+        max_referral_commission_bps,
+        timelock_delay,
+        pair_creation_fee,
+        fee_collector,
+      }),
+      TimelockAction::UpdatePairConfig {
+        pair,
+        max_swap_ticks,
+        history_config,
+      } => self.update_pair_config(deps, env, info, pair, max_swap_ticks, history_config),
+      TimelockAction::AddPairType {
+        type_name,
+        tick_space,
+        fee_rate,
+        amp,
+      } => self.add_pair_type(deps, env, info, PairType { type_name, tick_space, fee_rate, amp }),
+      TimelockAction::SetFeeDiscount { address, discount } => self.set_fee_discount(deps, env, info, address, discount),
+      TimelockAction::DeregisterPair {
+        asset_infos,
+        pair_type,
+      } => self.deregister_pair(deps, env, info, asset_infos, pair_type),
+      TimelockAction::RampAmp {
+        pair,
+        next_amp,
+        next_amp_time,
+      } => self.ramp_amp(deps, info, pair, next_amp, next_amp_time),
+      TimelockAction::MigrateContract {
+        contract,
+        code_id,
+        msg,
+      } => self.migrate_contract(deps, info, contract, code_id, msg),
+      TimelockAction::ProposeNewMinter {
+        pair,
+        minter,
+        expires,
+      } => self.propose_new_minter(deps, info, pair, minter, expires),
+    }?;
+
+    Ok(res.add_attribute("executed_action_id", id.to_string()))
+  }
+
+  pub fn cancel_action(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if self.pending_actions.may_load(deps.storage, U64Key::new(id))?.is_none() {
+      return Err(ContractError::PendingActionNotFound {})
+    }
+
+    self.pending_actions.remove(deps.storage, U64Key::new(id));
+
+    Ok(Response::new()
+      .add_attribute("action", "cancel_action")
+      .add_attribute("id", id.to_string())
+    )
+  }
+
+  /// stable pairs accept `RampAmp` only from the factory while the delay is set
+  fn ramp_amp(
+    &self,
+    deps: DepsMut,
+    info: MessageInfo,
+    pair: String,
+    next_amp: u64,
+    next_amp_time: u64,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let pair = deps.api.addr_validate(&pair)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&StablePairExecuteMsg::RampAmp { next_amp, next_amp_time })?,
+        funds: vec![],
+      })
+      .add_attribute("action", "ramp_amp")
+      .add_attribute("pair", pair.to_string())
+    )
+  }
+
+  /// pairs accept `ProposeNewMinter` only from the factory while the delay is set
+  fn propose_new_minter(
+    &self,
+    deps: DepsMut,
+    info: MessageInfo,
+    pair: String,
+    minter: String,
+    expires: Expiration,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let pair = deps.api.addr_validate(&pair)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::ProposeNewMinter { minter: minter.clone(), expires })?,
+        funds: vec![],
+      })
+      .add_attribute("action", "propose_new_minter")
+      .add_attribute("pair", pair.to_string())
+      .add_attribute("minter", minter)
+    )
+  }
+
+  /// pairs and their lp tokens are instantiated with the factory as the admin
+  fn migrate_contract(
+    &self,
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    code_id: u64,
+    msg: Binary,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender != config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let contract = deps.api.addr_validate(&contract)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Migrate {
+        contract_addr: contract.to_string(),
+        new_code_id: code_id,
+        msg,
+      })
+      .add_attribute("action", "migrate_contract")
+      .add_attribute("contract", contract.to_string())
+      .add_attribute("code_id", code_id.to_string())
+    )
+  }
+
+  /// timelocked actions can't be executed directly while the delay is set
+  fn assert_not_timelocked(&self, deps: Deps) -> Result<(), ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if config.timelock_delay > 0 {
+      return Err(ContractError::Timelocked {})
+    }
+    Ok(())
+  }
+}
+
+/// split swap
+impl<'a> FactoryContract<'a> {
+  pub fn receive_cw20(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+  ) -> Result<Response, ContractError> {
+    let sent_asset = Asset {
+      info: AssetInfo::Token {
+        contract_addr: info.sender.to_string(),
+      },
+      amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg) {
+      Ok(Cw20HookMsg::CreatePair {
+        asset_infos,
+        pair_type,
+        initial_price,
+      }) => self.create_pair(
+        deps,
+        env,
+        Addr::unchecked(cw20_msg.sender),
+        Some(sent_asset),
+        CreatePairParams { asset_infos, pair_type, initial_price },
+      ),
+      Ok(Cw20HookMsg::SplitSwap {
+        ask_asset_info,
+        minimum_receive,
+        to,
+        referral,
+      }) => {
+        let to_addr = if let Some(to_addr) = to {
+          Some(deps.api.addr_validate(&to_addr)?)
+        } else {
+          None
+        };
+
+        self.split_swap(deps, env, Addr::unchecked(cw20_msg.sender), SplitSwapParams {
+          offer_asset: sent_asset,
+          ask_asset_info,
+          minimum_receive,
+          to: to_addr,
+          referral,
+        })
+      },
+      Err(err) => Err(ContractError::Std(err)),
+    }
+  }
+
+  pub fn split_swap(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    params: SplitSwapParams,
+  ) -> Result<Response, ContractError> {
+    let SplitSwapParams { offer_asset, ask_asset_info, minimum_receive, to, referral } = params;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let legs = self.compute_split(deps.as_ref(), &offer_asset.info, &ask_asset_info, offer_asset.amount)?;
+
+    // native token is taxed on every send to the pairs, so the tax (and its cap) is deducted per leg
+    let legs = legs
+      .into_iter()
+      .map(|(pair_contract, amount)| {
+        let amount = if offer_asset.is_native_token() {
+          Asset { info: offer_asset.info.clone(), amount }.deduct_tax(&deps.querier)?.amount
+        } else {
+          amount
+        };
+        Ok((pair_contract, amount))
+      })
+      .collect::<StdResult<Vec<_>>>()?;
+    let offer_amount = legs.iter().fold(Uint128::zero(), |sum, (_, amount)| sum + *amount);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (pair_contract, amount) in legs.iter() {
+      let msg = match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => WasmMsg::Execute {
+          contract_addr: pair_contract.to_string(),
+          msg: to_binary(&PairExecuteMsg::Swap {
+            offer_asset: Asset {
+              info: offer_asset.info.clone(),
+              amount: *amount,
+            },
+            to: Some(receiver.to_string()),
+            belief_price: None,
+            max_slippage: None,
+            max_ticks: None,
+            partial_fill: None,
+            referral: referral.clone(),
+          })?,
+          funds: vec![Coin {
+            denom: denom.to_string(),
+            amount: *amount,
+          }],
+        },
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+          contract_addr: contract_addr.to_string(),
+          msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair_contract.to_string(),
+            amount: *amount,
+            msg: to_binary(&PairCw20HookMsg::Swap {
+              to: Some(receiver.to_string()),
+              belief_price: None,
+              max_slippage: None,
+              max_ticks: None,
+              partial_fill: None,
+              referral: referral.clone(),
+            })?,
+          })?,
+          funds: vec![],
+        },
+      };
+      messages.push(msg.into());
+    }
+
+    // check the sum of every leg at the end
+    if let Some(minimum_receive) = minimum_receive {
+      let prev_balance = ask_asset_info.query_balance(&deps.querier, &receiver)?;
+      messages.push(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+          asset_info: ask_asset_info.clone(),
+          prev_balance,
+          minimum_receive,
+          receiver: receiver.to_string(),
+        })?,
+        funds: vec![],
+      }.into());
     }
+
+    let legs_attr = legs
+      .iter()
+      .map(|(pair_contract, amount)| format!("{}:{}", pair_contract, amount))
+      .collect::<Vec<String>>()
+      .join(", ");
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "split_swap")
+      .add_attribute("sender", sender.to_string())
+      .add_attribute("receiver", receiver.to_string())
+      .add_attribute("offer_asset", offer_asset.info.to_string())
+      .add_attribute("ask_asset", ask_asset_info.to_string())
+      .add_attribute("offer_amount", offer_amount.to_string())
+      .add_attribute("legs", legs_attr)
+    )
   }
-}
\ No newline at end of file
+
+  pub fn assert_minimum_receive(
+    &self,
+    deps: Deps,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    minimum_receive: Uint128,
+    receiver: String,
+  ) -> Result<Response, ContractError> {
+    let receiver = deps.api.addr_validate(&receiver)?;
+    let balance = asset_info.query_balance(&deps.querier, &receiver)?;
+    let receive = balance.checked_sub(prev_balance).unwrap_or_default();
+
+    if receive < minimum_receive {
+      return Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive })
+    }
+
+    Ok(Response::new()
+      .add_attribute("action", "assert_minimum_receive")
+      .add_attribute("receive", receive.to_string())
+    )
+  }
+
+  /// split offer amount greedily, each step goes to the pair with the best marginal price
+  /// return (pair contract, offer amount) of the pairs that get any
+  fn compute_split(
+    &self,
+    deps: Deps,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+    offer_amount: Uint128,
+  ) -> Result<Vec<(Addr, Uint128)>, ContractError> {
+    let pairs = self.asset_infos_pairs(deps.storage, &[offer_asset_info.clone(), ask_asset_info.clone()])?;
+
+    let mut pools: Vec<SplitPool> = vec![];
+    // stable pairs don't have ticks to split over
+    for pair in pairs.into_iter().filter(|pair| pair.pair_type.amp.is_none()) {
+      let pair_info: PairInfoResponse = deps.querier.query_wasm_smart(
+        pair.contract_addr.to_string(),
+        &PairQueryMsg::PairInfo {}
+      )?;
+      let pair_config: PairConfigResponse = deps.querier.query_wasm_smart(
+        pair.contract_addr.to_string(),
+        &PairQueryMsg::Config {}
+      )?;
+
+      let offer_token = if offer_asset_info.equal(&pair_info.asset_infos[0]) {
+        TokenNumber::Token0
+      } else {
+        TokenNumber::Token1
+      };
+
+      pools.push(SplitPool {
+        contract_addr: pair.contract_addr,
+        offer_token,
+        tick_space: pair_info.tick_space,
+        fee_rate: pair_info.fee_rate,
+        tick_index: pair_info.current_tick_index,
+        price_sqrt: pair_info.current_price_sqrt,
+        liquidity: None,
+        offer_amount: Uint128::zero(),
+        ticks: 0,
+        max_swap_ticks: pair_config.max_swap_ticks,
+        exhausted: false,
+      });
+    }
+
+    let step = std::cmp::max(
+      offer_amount.multiply_ratio(1u128, SPLIT_STEPS),
+      Uint128::from(1u128)
+    );
+    let mut remain = offer_amount;
+    while !remain.is_zero() {
+      let pool = match pools
+        .iter_mut()
+        .filter(|pool| !pool.exhausted)
+        .max_by_key(|pool| marginal_return(pool.price_sqrt, &pool.offer_token, pool.fee_rate)) {
+        Some(pool) => pool,
+        None => return Err(ContractError::CanNotSwap {}),
+      };
+
+      let liquidity = match pool.liquidity {
+        Some(liquidity) => liquidity,
+        None => {
+          // the pair fails the swap that walks more ticks
+          if pool.ticks >= pool.max_swap_ticks {
+            pool.exhausted = true;
+            continue;
+          }
+
+          // uninitialized tick can't be passed
+          let liquidity = deps.querier.query_wasm_smart::<TickInfoResponse>(
+            pool.contract_addr.to_string(),
+            &PairQueryMsg::TickInfo { tick_index: pool.tick_index }
+          ).map(|res| res.tick_info.total_liquidity).unwrap_or_default();
+          pool.liquidity = Some(liquidity);
+          pool.ticks += 1;
+          liquidity
+        }
+      };
+
+      if liquidity.is_zero() {
+        pool.exhausted = true;
+        continue;
+      }
+
+      let (offer_amount, _, _, next_price_sqrt, next_tick_index) = compute_swap_tick(
+        pool.tick_index,
+        pool.tick_space,
+        pool.price_sqrt,
+        liquidity,
+        &pool.offer_token,
+        std::cmp::min(remain, step),
+        pool.fee_rate
+      );
+
+      remain = remain.checked_sub(offer_amount)?;
+      pool.offer_amount += offer_amount;
+      pool.price_sqrt = next_price_sqrt;
+      if pool.tick_index != next_tick_index {
+        pool.tick_index = next_tick_index;
+        pool.liquidity = None;
+      }
+    }
+
+    Ok(pools
+      .into_iter()
+      .filter(|pool| !pool.offer_amount.is_zero())
+      .map(|pool| (pool.contract_addr, pool.offer_amount))
+      .collect())
+  }
+}
+
+// swap state of a pair while computing split
+struct SplitPool {
+  contract_addr: Addr,
+  offer_token: TokenNumber,
+  tick_space: u16,
+  fee_rate: Decimal,
+  tick_index: i32,
+  price_sqrt: Uint256,
+  // liquidity of tick_index, None if not loaded
+  liquidity: Option<Uint128>,
+  offer_amount: Uint128,
+  // ticks that offer_amount walks, the step count of the pair swap
+  ticks: u32,
+  max_swap_ticks: u32,
+  exhausted: bool,
+}
+
+impl<'a> FactoryContract<'a> {
+  /// whether the asset has a pair with any of the quote assets
+  pub fn quote_pair_existence(&self, deps: Deps, asset_info: AssetInfo) -> StdResult<bool> {
+    Ok(!self.quote_pairs(deps, asset_info)?.quote_pairs.is_empty())
+  }
+
+  /// native pair creation fee sent with the message, None if the fee is not a native token
+  fn sent_pair_creation_fee(&self, deps: Deps, info: &MessageInfo) -> Result<Option<Asset>, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    let fee_denom = match config.pair_creation_fee {
+      Some(Asset { info: AssetInfo::NativeToken { denom }, .. }) => Some(denom),
+      _ => None,
+    };
+
+    // other coins would be stuck in the factory
+    if info.funds.iter().any(|coin| Some(&coin.denom) != fee_denom.as_ref()) {
+      return Err(ContractError::InvalidPairCreationFee {})
+    }
+
+    Ok(fee_denom.map(|denom| {
+      let amount = info.funds
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_default();
+      Asset { info: AssetInfo::NativeToken { denom }, amount }
+    }))
+  }
+
+  /// decimals of the asset, cw20 token must respond to `TokenInfo` and native token must be registered
+  pub fn asset_decimals(&self, deps: Deps, asset_info: &AssetInfo) -> Result<u8, ContractError> {
+    let invalid_asset_info = || ContractError::InvalidAssetInfo { asset_info: asset_info.to_string() };
+
+    let decimals = match asset_info {
+      AssetInfo::Token { contract_addr } => {
+        let contract_addr = deps.api.addr_validate(contract_addr).map_err(|_| invalid_asset_info())?;
+        let token_info: TokenInfoResponse = deps.querier
+          .query_wasm_smart(contract_addr, &Cw20QueryMsg::TokenInfo {})
+          .map_err(|_| invalid_asset_info())?;
+        token_info.decimals
+      }
+      AssetInfo::NativeToken { denom } => {
+        if denom.is_empty() {
+          return Err(invalid_asset_info())
+        }
+        self.native_token_decimals
+          .may_load(deps.storage, denom.as_bytes())?
+          .ok_or_else(|| ContractError::NativeTokenDecimalsNotFound { denom: denom.clone() })?
+      }
+    };
+
+    // pairs convert prices with 10^decimals
+    if decimals > MAX_DECIMALS {
+      return Err(ContractError::InvalidDecimals {})
+    }
+
+    Ok(decimals)
+  }
+}
@@ -5,12 +5,15 @@ mod query;
 mod state;
 
 pub use crate::error::ContractError;
-pub use wineswap::factory::{InstantiateMsg, ExecuteMsg, QueryMsg};
+pub use wineswap::factory::{InstantiateMsg, ExecuteMsg, MigrateMsg, QueryMsg};
 pub use crate::state::FactoryContract;
 
 #[cfg(test)]
 mod testing;
 
+#[cfg(test)]
+mod mock_querier;
+
 #[cfg(not(feature = "library"))]
 pub mod entry {
   use super::*;
@@ -39,6 +42,12 @@ pub mod entry {
     tract.reply(deps, env, msg)
   }
 
+  #[entry_point]
+  pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let tract = FactoryContract::default();
+    tract.migrate(deps, env, msg)
+  }
+
   #[entry_point]
   pub fn execute(
       deps: DepsMut,
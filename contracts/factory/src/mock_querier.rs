@@ -0,0 +1,275 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery, Uint128
+};
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+use std::collections::HashMap;
+
+use cw20::{BalanceResponse, TokenInfoResponse};
+use wineswap::asset::Asset;
+use wineswap::pair::{
+    ConfigResponse as PairConfigResponse, HistoryConfig, PairInfoResponse, SimulationResponse, TickInfoResponse,
+};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    PairInfo {},
+    Config {},
+    TickInfo { tick_index: i32 },
+    Simulation { offer_asset: Asset },
+    Balance { address: String },
+    TokenInfo {},
+}
+
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<TerraQueryWrapper>,
+    tax_querier: TaxQuerier,
+    pair_querier: PairQuerier,
+    // (token contract, address) => balance, zero if not set
+    token_balances: HashMap<(String, String), Uint128>,
+    // token contract => decimals, contract without it is not a token
+    token_decimals: HashMap<String, u8>,
+}
+
+#[derive(Clone, Default)]
+pub struct PairQuerier {
+    // pair address => simulation result of 1000000 offer, pair without it fails to simulate.
+    // steps of the detail are the ticks it walks
+    simulations: HashMap<String, SimulationResponse>,
+    pair_infos: HashMap<String, PairInfoResponse>,
+    // (pair address, tick index) => tick info
+    tick_infos: HashMap<(String, i32), TickInfoResponse>,
+    // pair address => max swap ticks, 100 if not set
+    max_swap_ticks: HashMap<String, u32>,
+}
+
+impl PairQuerier {
+    pub fn set_simulations(&mut self, simulations: &[(&String, &SimulationResponse)]) {
+        let mut temp: HashMap<String, SimulationResponse> = HashMap::new();
+        for (contract_addr, simulation) in simulations.iter() {
+            temp.insert(contract_addr.to_string(), (*simulation).clone());
+        }
+        self.simulations = temp;
+    }
+
+    pub fn set_pool(&mut self, contract_addr: &str, pair_info: &PairInfoResponse, tick_infos: &[TickInfoResponse]) {
+        self.pair_infos.insert(contract_addr.to_string(), pair_info.clone());
+        for tick_info in tick_infos.iter() {
+            self.tick_infos.insert((contract_addr.to_string(), tick_info.tick_index), tick_info.clone());
+        }
+    }
+
+    pub fn set_max_swap_ticks(&mut self, contract_addr: &str, max_swap_ticks: u32) {
+        self.max_swap_ticks.insert(contract_addr.to_string(), max_swap_ticks);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    // this lets us iterate over all pairs that match the first string
+    caps: HashMap<String, Uint128>,
+}
+
+impl TaxQuerier {
+    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
+        TaxQuerier {
+            rate,
+            caps: caps_to_map(caps),
+        }
+    }
+}
+
+pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
+    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
+    for (denom, cap) in caps.iter() {
+        owner_map.insert(denom.to_string(), **cap);
+    }
+    owner_map
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Treasury {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg) {
+                    Ok(QueryMsg::PairInfo {}) => {
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(self.pair_querier.pair_infos.get(contract_addr).unwrap()).unwrap(),
+                        ))
+                    }
+                    Ok(QueryMsg::Config {}) => {
+                        let pair_info = self.pair_querier.pair_infos.get(contract_addr).unwrap();
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&PairConfigResponse {
+                                asset_infos: pair_info.asset_infos.clone(),
+                                tick_space: pair_info.tick_space,
+                                fee_rate: pair_info.fee_rate,
+                                liquidity_token: pair_info.liquidity_token.clone(),
+                                factory: None,
+                                max_swap_ticks: self.pair_querier.max_swap_ticks.get(contract_addr).copied().unwrap_or(100),
+                                asset_decimals: None,
+                                history_config: HistoryConfig::default(),
+                            }).unwrap(),
+                        ))
+                    }
+                    Ok(QueryMsg::TickInfo { tick_index }) => {
+                        match self.pair_querier.tick_infos.get(&(contract_addr.to_string(), tick_index)) {
+                            Some(tick_info) => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(tick_info).unwrap(),
+                            )),
+                            None => SystemResult::Ok(ContractResult::Err("TickInfo not found".to_string())),
+                        }
+                    }
+                    Ok(QueryMsg::Simulation { offer_asset }) => {
+                        let max_swap_ticks = self.pair_querier.max_swap_ticks.get(contract_addr).copied().unwrap_or(100);
+                        match self.pair_querier.simulations.get(contract_addr) {
+                            Some(SimulationResponse { detail: Some(detail), .. })
+                                if detail.steps.len() as u32 > max_swap_ticks =>
+                            {
+                                SystemResult::Ok(ContractResult::Err("Max ticks reached".to_string()))
+                            }
+                            Some(simulation) => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&SimulationResponse {
+                                    return_amount: simulation.return_amount.multiply_ratio(offer_asset.amount, 1000000u128),
+                                    commission_amount: simulation.commission_amount,
+                                    detail: None,
+                                }).unwrap(),
+                            )),
+                            None => SystemResult::Ok(ContractResult::Err("Can not swap".to_string())),
+                        }
+                    }
+                    Ok(QueryMsg::Balance { address }) => {
+                        let balance = self
+                            .token_balances
+                            .get(&(contract_addr.to_string(), address))
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&BalanceResponse { balance }).unwrap(),
+                        ))
+                    }
+                    Ok(QueryMsg::TokenInfo {}) => {
+                        match self.token_decimals.get(contract_addr) {
+                            Some(decimals) => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&TokenInfoResponse {
+                                    name: contract_addr.to_string(),
+                                    symbol: "TOKEN".to_string(),
+                                    decimals: *decimals,
+                                    total_supply: Uint128::zero(),
+                                }).unwrap(),
+                            )),
+                            None => SystemResult::Ok(ContractResult::Err("Not a token".to_string())),
+                        }
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            tax_querier: TaxQuerier::default(),
+            pair_querier: PairQuerier::default(),
+            token_balances: HashMap::new(),
+            token_decimals: HashMap::new(),
+        }
+    }
+
+    pub fn with_simulations(&mut self, simulations: &[(&String, &SimulationResponse)]) {
+        self.pair_querier.set_simulations(simulations);
+    }
+
+    // pair info and initialized ticks of the pair
+    pub fn with_pool(&mut self, contract_addr: &str, pair_info: &PairInfoResponse, tick_infos: &[TickInfoResponse]) {
+        self.pair_querier.set_pool(contract_addr, pair_info, tick_infos);
+    }
+
+    pub fn with_max_swap_ticks(&mut self, contract_addr: &str, max_swap_ticks: u32) {
+        self.pair_querier.set_max_swap_ticks(contract_addr, max_swap_ticks);
+    }
+
+    pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
+        for (addr, balance) in balances {
+            self.base.update_balance(addr.to_string(), balance.clone());
+        }
+    }
+
+    pub fn with_token_decimals(&mut self, token_decimals: &[(&str, u8)]) {
+        for (contract_addr, decimals) in token_decimals {
+            self.token_decimals.insert(contract_addr.to_string(), *decimals);
+        }
+    }
+
+    // configure the token owner mock querier
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        self.tax_querier = TaxQuerier::new(rate, caps);
+    }
+}
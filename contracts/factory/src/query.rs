@@ -1,7 +1,9 @@
-use cosmwasm_std::{to_binary, Binary, Deps, StdResult, Order};
-use cw_storage_plus::Bound;
-use wineswap::factory::{AssetInfosWithType, Config, PairInfoWithType, PairType, QueryMsg};
-use wineswap::asset::AssetInfo;
+use cosmwasm_std::{to_binary, Binary, Decimal, Deps, StdError, StdResult, Order};
+use cw_storage_plus::{Bound, U64Key};
+use wineswap::factory::{AssetInfosWithType, BestQuoteResponse, Config, FeeDiscountResponse, PairInfoWithType, PairType,
+  NativeTokenDecimalsResponse, PendingAction, QueryMsg, QuotePairInfo, QuotePairsResponse};
+use wineswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use wineswap::asset::{Asset, AssetInfo};
 
 use crate::state::{pair_key, asset_infos_key, FactoryContract};
 
@@ -22,6 +24,8 @@ impl<'a> FactoryContract<'a> {
         contract_addr: pair.contract_addr,
         liquidity_token: pair.liquidity_token,
         pair_type,
+        asset_decimals: pair.asset_decimals,
+        creator: pair.creator,
       }].to_vec())
     } else {
       let asset_infos_key = asset_infos_key(&asset_infos);
@@ -41,6 +45,8 @@ impl<'a> FactoryContract<'a> {
           contract_addr: pair.contract_addr,
           liquidity_token: pair.liquidity_token,
           pair_type: pair.pair_type.type_name,
+          asset_decimals: pair.asset_decimals,
+          creator: pair.creator,
         }
       }).collect();
 
@@ -64,13 +70,120 @@ impl<'a> FactoryContract<'a> {
           asset_infos: v.asset_infos,
           contract_addr: v.contract_addr,
           liquidity_token: v.liquidity_token,
-          pair_type: v.pair_type.type_name
+          pair_type: v.pair_type.type_name,
+          asset_decimals: v.asset_decimals,
+          creator: v.creator,
         }
       })
       .collect();
     
     Ok(pairs)
   }
+  fn pairs_by_asset(
+    &self,
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<AssetInfosWithType>,
+    limit: Option<u32>,
+  ) -> StdResult<Vec<PairInfoWithType>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|start_after| Bound::exclusive(pair_key(&start_after.asset_infos, start_after.pair_type)));
+
+    self.asset_pairs
+      .prefix(asset_info.as_bytes().to_vec())
+      .keys(deps.storage, start, None, Order::Ascending)
+      .take(limit)
+      .map(|key| {
+        let pair = self.pairs.load(deps.storage, key)?;
+        Ok(PairInfoWithType {
+          asset_infos: pair.asset_infos,
+          contract_addr: pair.contract_addr,
+          liquidity_token: pair.liquidity_token,
+          pair_type: pair.pair_type.type_name,
+          asset_decimals: pair.asset_decimals,
+          creator: pair.creator,
+        })
+      })
+      .collect()
+  }
+
+  pub fn quote_pairs(&self, deps: Deps, asset_info: AssetInfo) -> StdResult<QuotePairsResponse> {
+    let config = self.config.load(deps.storage)?;
+
+    let mut quote_pairs: Vec<QuotePairInfo> = vec![];
+    for quote_asset in config.quote_assets.into_iter() {
+      if quote_asset.equal(&asset_info) {
+        continue
+      }
+
+      let pair_types = self.pairs
+        .idx
+        .asset_infos
+        .prefix(asset_infos_key(&[quote_asset.clone(), asset_info.clone()]))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pair)| pair.pair_type.type_name))
+        .collect::<StdResult<Vec<String>>>()?;
+
+      if !pair_types.is_empty() {
+        quote_pairs.push(QuotePairInfo { quote_asset, pair_types });
+      }
+    }
+
+    Ok(QuotePairsResponse { quote_pairs })
+  }
+
+  fn best_quote(&self, deps: Deps, offer_asset: Asset, ask_asset_info: AssetInfo) -> StdResult<BestQuoteResponse> {
+    let pairs = self.asset_infos_pairs(deps.storage, &[offer_asset.info.clone(), ask_asset_info.clone()])?;
+
+    // native offer is taxed on the send to the pair
+    let offer_tax_amount = offer_asset.compute_tax(&deps.querier)?;
+    let pair_offer_asset = Asset {
+      info: offer_asset.info.clone(),
+      amount: offer_asset.amount.checked_sub(offer_tax_amount)?,
+    };
+
+    let mut best_quote: Option<BestQuoteResponse> = None;
+    for pair in pairs {
+      // skip the pair that can't swap (not enough liquidity or more ticks than its max swap ticks)
+      let simulation: SimulationResponse = match deps.querier.query_wasm_smart(
+        pair.contract_addr.to_string(),
+        &PairQueryMsg::Simulation { offer_asset: pair_offer_asset.clone(), detailed: None, trader: None }
+      ) {
+        Ok(simulation) => simulation,
+        Err(_) => continue,
+      };
+
+      let return_tax_amount = Asset {
+        info: ask_asset_info.clone(),
+        amount: simulation.return_amount,
+      }.compute_tax(&deps.querier)?;
+      let return_amount = simulation.return_amount.checked_sub(return_tax_amount)?;
+      let tax_amount = offer_tax_amount + return_tax_amount;
+
+      let is_better = match &best_quote {
+        Some(best_quote) => return_amount > best_quote.return_amount,
+        None => true,
+      };
+      if is_better {
+        best_quote = Some(BestQuoteResponse {
+          pair: PairInfoWithType {
+            asset_infos: pair.asset_infos,
+            contract_addr: pair.contract_addr,
+            liquidity_token: pair.liquidity_token,
+            pair_type: pair.pair_type.type_name,
+            asset_decimals: pair.asset_decimals,
+            creator: pair.creator,
+          },
+          return_amount,
+          commission_amount: simulation.commission_amount,
+          tax_amount,
+        });
+      }
+    }
+
+    best_quote.ok_or_else(|| StdError::generic_err("No pair to swap"))
+  }
+
   fn pair_type(&self, deps: Deps, type_name: String) -> StdResult<PairType> {
     self.pair_type.load(deps.storage, type_name.as_bytes().to_vec())
   }
@@ -85,6 +198,46 @@ impl<'a> FactoryContract<'a> {
       .map(|item| item.map(|(_, v)| v))
       .collect();
   }
+
+  fn fee_discount(&self, deps: Deps, address: String) -> StdResult<FeeDiscountResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let discount = self.fee_discounts.may_load(deps.storage, &addr)?.unwrap_or_else(Decimal::zero);
+
+    Ok(FeeDiscountResponse { address, discount })
+  }
+
+  fn fee_discounts(&self, deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<FeeDiscountResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    self.fee_discounts
+      .range(deps.storage, start, None, Order::Ascending)
+      .take(limit)
+      .map(|item| {
+        let (k, discount) = item?;
+        Ok(FeeDiscountResponse {
+          address: String::from_utf8(k)?,
+          discount,
+        })
+      })
+      .collect()
+  }
+
+  fn native_token_decimals(&self, deps: Deps, denom: String) -> StdResult<NativeTokenDecimalsResponse> {
+    let decimals = self.native_token_decimals.load(deps.storage, denom.as_bytes())?;
+    Ok(NativeTokenDecimalsResponse { denom, decimals })
+  }
+
+  fn pending_actions(&self, deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Vec<PendingAction>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|start_after| Bound::exclusive(U64Key::new(start_after)));
+
+    self.pending_actions
+      .range(deps.storage, start, None, Order::Ascending)
+      .take(limit)
+      .map(|item| item.map(|(_, v)| v))
+      .collect()
+  }
 }
 
 impl<'a> FactoryContract<'a> {
@@ -95,6 +248,15 @@ impl<'a> FactoryContract<'a> {
       QueryMsg::Pairs { start_after, limit } => to_binary(&self.pairs(deps, start_after, limit)?),
       QueryMsg::PairType { type_name } => to_binary(&self.pair_type(deps, type_name)?),
       QueryMsg::PairTypes { start_after, limit } => to_binary(&self.pair_types(deps, start_after, limit)?),
+      QueryMsg::PairsByAsset { asset_info, start_after, limit }
+        => to_binary(&self.pairs_by_asset(deps, asset_info, start_after, limit)?),
+      QueryMsg::QuotePairs { asset_info } => to_binary(&self.quote_pairs(deps, asset_info)?),
+      QueryMsg::BestQuote { offer_asset, ask_asset_info }
+        => to_binary(&self.best_quote(deps, offer_asset, ask_asset_info)?),
+      QueryMsg::FeeDiscount { address } => to_binary(&self.fee_discount(deps, address)?),
+      QueryMsg::FeeDiscounts { start_after, limit } => to_binary(&self.fee_discounts(deps, start_after, limit)?),
+      QueryMsg::NativeTokenDecimals { denom } => to_binary(&self.native_token_decimals(deps, denom)?),
+      QueryMsg::PendingActions { start_after, limit } => to_binary(&self.pending_actions(deps, start_after, limit)?),
     }
   }
 }
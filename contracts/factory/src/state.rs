@@ -1,9 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::{Index, IndexList, IndexedMap, Map, MultiIndex, Item};
+use cosmwasm_std::{Addr, Decimal, Empty, Order, StdResult, Storage};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Map, MultiIndex, Item, U64Key};
 use wineswap::asset::AssetInfo;
-use wineswap::factory::{Config, PairInfo, PairType};
+use wineswap::factory::{Config, PairInfo, PairType, PendingAction};
 
 
 pub struct FactoryContract<'a> {
@@ -11,6 +12,15 @@ pub struct FactoryContract<'a> {
   pub temp_pair_info: Item<'a, TmpPairInfo>,
   pub pairs: IndexedMap<'a, Vec<u8>, PairInfo, PairIndexes<'a>>,
   pub pair_type: Map<'a, Vec<u8>, PairType>,
+  // key: (asset_info, pair_key), pairs can't be indexed by single asset with MultiIndex
+  pub asset_pairs: Map<'a, (Vec<u8>, Vec<u8>), Empty>,
+  // discount rate of the swap fee by trader
+  pub fee_discounts: Map<'a, &'a Addr, Decimal>,
+  // decimals by native denom, cw20 decimals are queried
+  pub native_token_decimals: Map<'a, &'a [u8], u8>,
+  // queued timelocked actions by id
+  pub pending_actions: Map<'a, U64Key, PendingAction>,
+  pub next_action_id: Item<'a, u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,6 +28,8 @@ pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
     pub asset_infos: [AssetInfo; 2],
     pub pair_type: PairType,
+    pub asset_decimals: [u8; 2],
+    pub creator: Addr,
 }
 
 pub fn pair_key(asset_infos: &[AssetInfo; 2], pair_type: String) -> Vec<u8> {
@@ -41,18 +53,30 @@ impl Default for FactoryContract<'static> {
       "temp_pair_info",
       "asset_infos",
       "type_name",
-      "pair_type"
+      "pair_type",
+      "asset_pairs",
+      "fee_discounts",
+      "native_token_decimals",
+      "pending_actions",
+      "next_action_id"
     )
   }
 }
 
 impl<'a> FactoryContract<'a> {
+  // one storage key per item
+  #[allow(clippy::too_many_arguments)]
   fn new(
     config_key: &'a str,
     temp_pair_info_key: &'a str,
     pair_key: &'a str,
     asset_infos_key: &'a str,
     pair_type_key: &'a str,
+    asset_pairs_key: &'a str,
+    fee_discounts_key: &'a str,
+    native_token_decimals_key: &'a str,
+    pending_actions_key: &'a str,
+    next_action_id_key: &'a str,
   ) -> Self {
     let indexes = PairIndexes {
       asset_infos: MultiIndex::new(asset_infos_idx, pair_key, asset_infos_key),
@@ -61,9 +85,44 @@ impl<'a> FactoryContract<'a> {
       config: Item::new(config_key),
       temp_pair_info: Item::new(temp_pair_info_key),
       pairs: IndexedMap::new(pair_key, indexes),
-      pair_type: Map::new(pair_type_key)
+      pair_type: Map::new(pair_type_key),
+      asset_pairs: Map::new(asset_pairs_key),
+      fee_discounts: Map::new(fee_discounts_key),
+      native_token_decimals: Map::new(native_token_decimals_key),
+      pending_actions: Map::new(pending_actions_key),
+      next_action_id: Item::new(next_action_id_key),
     }
   }
+
+  /// save pair and index it by each asset
+  pub fn save_pair(&self, storage: &mut dyn Storage, key: Vec<u8>, pair_info: &PairInfo) -> StdResult<()> {
+    self.pairs.save(storage, key.clone(), pair_info)?;
+    for asset_info in pair_info.asset_infos.iter() {
+      self.asset_pairs.save(storage, (asset_info.as_bytes().to_vec(), key.clone()), &Empty {})?;
+    }
+    Ok(())
+  }
+
+  /// remove pair and its index by each asset
+  pub fn remove_pair(&self, storage: &mut dyn Storage, key: Vec<u8>) -> StdResult<PairInfo> {
+    let pair_info = self.pairs.load(storage, key.clone())?;
+    self.pairs.remove(storage, key.clone())?;
+    for asset_info in pair_info.asset_infos.iter() {
+      self.asset_pairs.remove(storage, (asset_info.as_bytes().to_vec(), key.clone()));
+    }
+    Ok(pair_info)
+  }
+
+  /// every pair (every pair type) of the assets
+  pub fn asset_infos_pairs(&self, storage: &dyn Storage, asset_infos: &[AssetInfo; 2]) -> StdResult<Vec<PairInfo>> {
+    self.pairs
+      .idx
+      .asset_infos
+      .prefix(asset_infos_key(asset_infos))
+      .range(storage, None, None, Order::Ascending)
+      .map(|item| item.map(|(_, v)| v))
+      .collect()
+  }
 }
 
 pub struct PairIndexes<'a> {
@@ -1,30 +1,51 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{attr, from_binary, to_binary, Addr, Decimal, WasmMsg, SubMsg, ReplyOn};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{attr, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, StdError, Uint128, WasmMsg,
+  SubMsg, ReplyOn};
+use cw0::Expiration;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
-use wineswap::factory::{InstantiateMsg, ExecuteMsg, QueryMsg,
-  Config, PairTypeResponse, PairType, PairInfo
+use wineswap::factory::{InstantiateMsg, Cw20HookMsg, ExecuteMsg, MigrateMsg, QueryMsg,
+  AssetInfosWithType, BestQuoteResponse, Config, PairTypeResponse, PairType, PairInfo, PairInfoWithType, QuotePairInfo,
+  QuotePairsResponse, FeeDiscountResponse, NativeTokenDecimalsResponse, PendingAction, TimelockAction
 };
-use wineswap::asset::AssetInfo;
-use wineswap::pair::InstantiateMsg as PairInstantiateMsg;
+use wineswap::asset::{Asset, AssetInfo};
+use wineswap::ownership::OwnershipProposal;
+use wineswap::stable_pair::{ExecuteMsg as StablePairExecuteMsg, InstantiateMsg as StablePairInstantiateMsg};
+use wineswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, HistoryConfig,
+  InstantiateMsg as PairInstantiateMsg, MigrateMsg as PairMigrateMsg, PairInfoResponse, Referral, SimulationDetail, SimulationResponse, SwapStepResponse,
+  TickInfo, TickInfoResponse};
+use wineswap_math::tick::DENOMINATOR;
 use crate::state::{pair_key, FactoryContract, TmpPairInfo};
 use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies as mock_dependencies_with_querier;
 
 #[test]
 fn factory_full_test() {
   let factory = FactoryContract::default();
   // instantiate test
-  let mut deps = mock_dependencies(&[]);
+  let mut deps = mock_dependencies_with_querier(&[]);
+  deps.querier.with_token_decimals(&[("wine", 6), ("soju", 8)]);
 
   let instantiate_msg = InstantiateMsg {
     owner: "owner".to_string(), 
     pair_code_id: 123u64,
     token_code_id: 32u64,
+    quote_assets: vec![AssetInfo::NativeToken { denom: "uusd".to_string() }],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
   };
 
   let info = mock_info("owner", &[]);
 
   let _res = factory.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
 
+  let native_decimals_msg = ExecuteMsg::AddNativeTokenDecimals { denom: "uusd".to_string(), decimals: 6 };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg).unwrap();
+
   let query_res = factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap();
   let config_res: Config = from_binary(&query_res).unwrap(); 
   assert_eq!("owner".to_string(), config_res.owner);
@@ -37,9 +58,14 @@ fn factory_full_test() {
 
   // unauthorized
   let update_msg = ExecuteMsg::UpdateConfig {
-    owner: Some("notowner".to_string()),
-    token_code_id: None,
+    token_code_id: Some(789u64),
     pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
   };
 
   let res = factory.execute(deps.as_mut(), mock_env(), info_with_notowner, update_msg);
@@ -50,25 +76,82 @@ fn factory_full_test() {
   }
 
   // owner change
-  let update_msg = ExecuteMsg::UpdateConfig {
-    owner: Some("next_owner".to_string()),
-    token_code_id: None,
-    pair_code_id: None,
+  let env = mock_env();
+  let propose_msg = ExecuteMsg::ProposeNewOwner {
+    owner: "next_owner".to_string(),
+    expires: Expiration::AtHeight(env.block.height + 100),
   };
 
-  let info = mock_info("owner", &[]);
-  let _res = factory.execute(deps.as_mut(), mock_env(), info, update_msg);
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("notowner", &[]), propose_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ProposeNewOwner {
+    owner: "next_owner".to_string(),
+    expires: Expiration::AtHeight(env.block.height),
+  });
+
+  match res {
+    Err(ContractError::Expired {}) => assert!(true),
+    _ => panic!("Must return expired error"),
+  }
+
+  // drop the proposal
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg.clone()).unwrap();
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::DropOwnershipProposal {}).unwrap();
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("next_owner", &[]), ExecuteMsg::ClaimOwnership {});
+
+  match res {
+    Err(ContractError::OwnershipProposalNotFound {}) => assert!(true),
+    _ => panic!("Must return ownership proposal not found error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg).unwrap();
+  let config_res: Config = from_binary(&factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!("owner".to_string(), config_res.owner);
+  assert_eq!(config_res.ownership_proposal, Some(OwnershipProposal {
+    owner: Addr::unchecked("next_owner"),
+    expires: Expiration::AtHeight(env.block.height + 100),
+  }));
+
+  // only the proposed owner before it expires
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("notowner", &[]), ExecuteMsg::ClaimOwnership {});
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let mut expired_env = env.clone();
+  expired_env.block.height += 100;
+  let res = factory.execute(deps.as_mut(), expired_env, mock_info("next_owner", &[]), ExecuteMsg::ClaimOwnership {});
+
+  match res {
+    Err(ContractError::OwnershipProposalExpired {}) => assert!(true),
+    _ => panic!("Must return ownership proposal expired error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), env, mock_info("next_owner", &[]), ExecuteMsg::ClaimOwnership {}).unwrap();
   let query_res = factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap();
   let config_res: Config = from_binary(&query_res).unwrap(); 
   assert_eq!("next_owner".to_string(), config_res.owner);
+  assert_eq!(None, config_res.ownership_proposal);
   assert_eq!(123u64, config_res.pair_code_id);
   assert_eq!(32u64, config_res.token_code_id);
 
   // other change
   let update_msg = ExecuteMsg::UpdateConfig {
-    owner: None,
     token_code_id: Some(789u64),
     pair_code_id: Some(987u64),
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: Some(50),
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
   };
 
   let info = mock_info("next_owner", &[]);
@@ -78,6 +161,26 @@ fn factory_full_test() {
   assert_eq!("next_owner".to_string(), config_res.owner);
   assert_eq!(987u64, config_res.pair_code_id);
   assert_eq!(789u64, config_res.token_code_id);
+  assert_eq!(50u16, config_res.max_referral_commission_bps);
+
+  // referral commission more than 100%
+  let update_msg = ExecuteMsg::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: Some(10001),
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("next_owner", &[]), update_msg);
+
+  match res {
+    Err(ContractError::InvalidReferralCommission {}) => assert!(true),
+    _ => panic!("Must return invalid referral commission error"),
+  }
 
 
   // add pair type
@@ -86,7 +189,8 @@ fn factory_full_test() {
   let add_pair_type_msg = ExecuteMsg::AddPairType {
     type_name: "type".to_string(),
     tick_space: 150u16,
-    fee_rate: Decimal::from_ratio(3u128, 1000u128)
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
   };
 
   let info = mock_info("next_owner", &[]);
@@ -103,7 +207,8 @@ fn factory_full_test() {
   let add_pair_type_msg = ExecuteMsg::AddPairType {
     type_name: "type".to_string(),
     tick_space: 150u16,
-    fee_rate: Decimal::from_ratio(3u128, 1000u128)
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
   };
 
   let info = mock_info("next_owner", &[]);
@@ -118,7 +223,8 @@ fn factory_full_test() {
   let add_pair_type_msg = ExecuteMsg::AddPairType {
     type_name: "type2".to_string(),
     tick_space: 150u16,
-    fee_rate: Decimal::from_ratio(10u128, 1u128)
+    fee_rate: Decimal::from_ratio(10u128, 1u128),
+    amp: None,
   };
 
   let info = mock_info("next_owner", &[]);
@@ -133,7 +239,8 @@ fn factory_full_test() {
   let add_pair_type_msg = ExecuteMsg::AddPairType {
     type_name: "type3".to_string(),
     tick_space: 150u16,
-    fee_rate: Decimal::from_ratio(3u128, 1000u128)
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
   };
 
   let info = mock_info("not_owner", &[]);
@@ -178,14 +285,17 @@ fn factory_full_test() {
       msg: WasmMsg::Instantiate {
         code_id: 987u64,
         funds: vec![],
-        admin: None,
+        admin: Some(MOCK_CONTRACT_ADDR.to_string()),
         label: "".to_string(),
         msg: to_binary(&PairInstantiateMsg {
           asset_infos: asset_infos.clone(),
           token_code_id: 789u64,
           initial_price: Decimal::one(),
           tick_space: 150u16,
-          fee_rate: Decimal::from_ratio(3u128, 1000u128)
+          fee_rate: Decimal::from_ratio(3u128, 1000u128),
+          history_config: None,
+          max_swap_ticks: None,
+          asset_decimals: Some([6, 6]),
         }).unwrap()
       }.into(),
       reply_on: ReplyOn::Success
@@ -200,8 +310,11 @@ fn factory_full_test() {
       pair_type: PairType {
         type_name: "type".to_string(),
         tick_space: 150u16,
-        fee_rate: Decimal::from_ratio(3u128, 1000u128)
-      }
+        fee_rate: Decimal::from_ratio(3u128, 1000u128),
+        amp: None,
+      },
+      asset_decimals: [6, 6],
+      creator: Addr::unchecked("anyone"),
     }
   );
 
@@ -217,14 +330,17 @@ fn factory_full_test() {
     }
   ];
   let key = pair_key(&asset_infos, "type".to_string());
-  factory.pairs.save(deps.as_mut().storage, key, &PairInfo {
+  factory.save_pair(deps.as_mut().storage, key, &PairInfo {
     asset_infos,
     contract_addr: Addr::unchecked("pair0000"),
     liquidity_token: Addr::unchecked("liquidity0000"),
+    asset_decimals: Some([6, 6]),
+    creator: None,
     pair_type: PairType {
       type_name: "type".to_string(),
       tick_space: 150u16,
-      fee_rate: Decimal::from_ratio(3u128, 1000u128)
+      fee_rate: Decimal::from_ratio(3u128, 1000u128),
+      amp: None,
     }
   }).unwrap();
   let asset_infos = [
@@ -236,14 +352,17 @@ fn factory_full_test() {
     }
   ];
   let key = pair_key(&asset_infos, "type".to_string());
-  factory.pairs.save(deps.as_mut().storage, key, &PairInfo {
+  factory.save_pair(deps.as_mut().storage, key, &PairInfo {
     asset_infos,
     contract_addr: Addr::unchecked("pair0001"),
     liquidity_token: Addr::unchecked("liquidity0001"),
+    asset_decimals: Some([6, 6]),
+    creator: None,
     pair_type: PairType {
       type_name: "type".to_string(),
       tick_space: 150u16,
-      fee_rate: Decimal::from_ratio(3u128, 1000u128)
+      fee_rate: Decimal::from_ratio(3u128, 1000u128),
+      amp: None,
     }
   }).unwrap();
 
@@ -282,14 +401,17 @@ fn factory_full_test() {
       msg: WasmMsg::Instantiate {
         code_id: 987u64,
         funds: vec![],
-        admin: None,
+        admin: Some(MOCK_CONTRACT_ADDR.to_string()),
         label: "".to_string(),
         msg: to_binary(&PairInstantiateMsg {
           asset_infos: asset_infos.clone(),
           token_code_id: 789u64,
           initial_price: Decimal::one(),
           tick_space: 150u16,
-          fee_rate: Decimal::from_ratio(3u128, 1000u128)
+          fee_rate: Decimal::from_ratio(3u128, 1000u128),
+          history_config: None,
+          max_swap_ticks: None,
+          asset_decimals: Some([6, 8]),
         }).unwrap()
       }.into(),
       reply_on: ReplyOn::Success
@@ -304,8 +426,1631 @@ fn factory_full_test() {
       pair_type: PairType {
         type_name: "type".to_string(),
         tick_space: 150u16,
-        fee_rate: Decimal::from_ratio(3u128, 1000u128)
-      }
+        fee_rate: Decimal::from_ratio(3u128, 1000u128),
+        amp: None,
+      },
+      asset_decimals: [6, 8],
+      creator: Addr::unchecked("anyone"),
     }
   );
 }
+
+#[test]
+fn create_pair_validation_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+  deps.querier.with_token_decimals(&[("wine", 18), ("soju", 19)]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let add_pair_type_msg = ExecuteMsg::AddPairType {
+    type_name: "type".to_string(),
+    tick_space: 10u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), add_pair_type_msg).unwrap();
+
+  // native token decimals
+  let native_decimals_msg = |denom: &str| ExecuteMsg::AddNativeTokenDecimals { denom: denom.to_string(), decimals: 6 };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), native_decimals_msg("uusd"));
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg(""));
+  match res {
+    Err(ContractError::InvalidAssetInfo { .. }) => assert!(true),
+    _ => panic!("Must return invalid asset info error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::AddNativeTokenDecimals {
+    denom: "uusd".to_string(),
+    decimals: 19,
+  });
+  match res {
+    Err(ContractError::InvalidDecimals {}) => assert!(true),
+    _ => panic!("Must return invalid decimals error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg("uusd")).unwrap();
+  let res: NativeTokenDecimalsResponse = from_binary(&factory.query(deps.as_ref(), QueryMsg::NativeTokenDecimals {
+    denom: "uusd".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(res, NativeTokenDecimalsResponse { denom: "uusd".to_string(), decimals: 6 });
+
+  let create_pair_msg = |asset_infos: [AssetInfo; 2]| ExecuteMsg::CreatePair {
+    asset_infos,
+    initial_price: Decimal::one(),
+    pair_type: "type".to_string(),
+  };
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+
+  // same assets
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([wine.clone(), wine.clone()]));
+  match res {
+    Err(ContractError::SameAssets {}) => assert!(true),
+    _ => panic!("Must return same assets error"),
+  }
+
+  // not a cw20 token
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([
+    AssetInfo::Token { contract_addr: "makgeolli".to_string() },
+    uusd.clone(),
+  ]));
+  match res {
+    Err(ContractError::InvalidAssetInfo { asset_info }) => assert_eq!(asset_info, "makgeolli"),
+    _ => panic!("Must return invalid asset info error"),
+  }
+
+  // cw20 token with too many decimals
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([
+    AssetInfo::Token { contract_addr: "soju".to_string() },
+    uusd.clone(),
+  ]));
+  match res {
+    Err(ContractError::InvalidDecimals {}) => assert!(true),
+    _ => panic!("Must return invalid decimals error"),
+  }
+
+  // invalid address
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([
+    AssetInfo::Token { contract_addr: "a".to_string() },
+    uusd.clone(),
+  ]));
+  match res {
+    Err(ContractError::InvalidAssetInfo { asset_info }) => assert_eq!(asset_info, "a"),
+    _ => panic!("Must return invalid asset info error"),
+  }
+
+  // empty denom
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([
+    wine.clone(),
+    AssetInfo::NativeToken { denom: "".to_string() },
+  ]));
+  match res {
+    Err(ContractError::InvalidAssetInfo { .. }) => assert!(true),
+    _ => panic!("Must return invalid asset info error"),
+  }
+
+  // native token without decimals
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([
+    wine.clone(),
+    AssetInfo::NativeToken { denom: "uluna".to_string() },
+  ]));
+  match res {
+    Err(ContractError::NativeTokenDecimalsNotFound { denom }) => assert_eq!(denom, "uluna"),
+    _ => panic!("Must return native token decimals not found error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg([wine.clone(), uusd.clone()])).unwrap();
+  assert_eq!(factory.temp_pair_info.load(&deps.storage).unwrap().asset_decimals, [18, 6]);
+}
+
+#[test]
+fn pair_creation_fee_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+  deps.querier.with_token_decimals(&[("wine", 6)]);
+
+  let uusd_fee = Asset {
+    info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+    amount: Uint128::from(100000000u128),
+  };
+  let instantiate_msg = |fee_collector: Option<String>| InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: Some(uusd_fee.clone()),
+    fee_collector,
+  };
+
+  // fee without collector
+  let res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg(None));
+  match res {
+    Err(StdError::GenericErr { .. }) => assert!(true),
+    _ => panic!("Must return generic error"),
+  }
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg(Some("collector".to_string()))).unwrap();
+
+  let add_pair_type_msg = ExecuteMsg::AddPairType {
+    type_name: "type".to_string(),
+    tick_space: 10u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), add_pair_type_msg).unwrap();
+  let native_decimals_msg = ExecuteMsg::AddNativeTokenDecimals { denom: "uusd".to_string(), decimals: 6 };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg).unwrap();
+
+  let asset_infos = [
+    AssetInfo::Token { contract_addr: "wine".to_string() },
+    AssetInfo::NativeToken { denom: "uusd".to_string() },
+  ];
+  let create_pair_msg = ExecuteMsg::CreatePair {
+    asset_infos: asset_infos.clone(),
+    initial_price: Decimal::one(),
+    pair_type: "type".to_string(),
+  };
+
+  // native fee
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), create_pair_msg.clone());
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  let info = mock_info("creator", &[Coin { denom: "uusd".to_string(), amount: Uint128::from(1000000u128) }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, create_pair_msg.clone());
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  let info = mock_info("creator", &[Coin { denom: "uusd".to_string(), amount: Uint128::from(100000000u128) }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, create_pair_msg.clone()).unwrap();
+  assert_eq!(res.messages.len(), 2);
+  assert_eq!(
+    res.messages[1].msg,
+    CosmosMsg::Bank(BankMsg::Send {
+      to_address: "collector".to_string(),
+      amount: vec![Coin { denom: "uusd".to_string(), amount: Uint128::from(100000000u128) }],
+    })
+  );
+  assert_eq!(factory.temp_pair_info.load(&deps.storage).unwrap().creator, Addr::unchecked("creator"));
+
+  // the excess is refunded
+  let info = mock_info("creator", &[Coin { denom: "uusd".to_string(), amount: Uint128::from(150000000u128) }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, create_pair_msg.clone()).unwrap();
+  assert_eq!(res.messages.len(), 3);
+  assert_eq!(
+    res.messages[2].msg,
+    CosmosMsg::Bank(BankMsg::Send {
+      to_address: "creator".to_string(),
+      amount: vec![Coin { denom: "uusd".to_string(), amount: Uint128::from(50000000u128) }],
+    })
+  );
+
+  // other coins are rejected
+  let info = mock_info("creator", &[
+    Coin { denom: "uusd".to_string(), amount: Uint128::from(100000000u128) },
+    Coin { denom: "uluna".to_string(), amount: Uint128::from(1u128) },
+  ]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, create_pair_msg.clone());
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  // cw20 fee
+  let update_msg = |pair_creation_fee: Asset, fee_collector: Option<String>| ExecuteMsg::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: Some(pair_creation_fee),
+    fee_collector,
+  };
+  let wine_fee = Asset {
+    info: AssetInfo::Token { contract_addr: "wine".to_string() },
+    amount: Uint128::from(1000000u128),
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg(wine_fee.clone(), Some("treasury".to_string()))).unwrap();
+  let config: Config = from_binary(&factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.pair_creation_fee, Some(wine_fee));
+  assert_eq!(config.fee_collector, Some(Addr::unchecked("treasury")));
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), create_pair_msg.clone());
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  let receive_msg = |amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+    sender: "creator".to_string(),
+    amount: Uint128::from(amount),
+    msg: to_binary(&Cw20HookMsg::CreatePair {
+      asset_infos: asset_infos.clone(),
+      initial_price: Decimal::one(),
+      pair_type: "type".to_string(),
+    }).unwrap(),
+  });
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("soju", &[]), receive_msg(1000000));
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("wine", &[]), receive_msg(999999));
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("wine", &[]), receive_msg(1500000)).unwrap();
+  assert_eq!(
+    res.messages[1..].iter().map(|msg| msg.msg.clone()).collect::<Vec<CosmosMsg>>(),
+    vec![
+      CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "wine".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+          recipient: "treasury".to_string(),
+          amount: Uint128::from(1000000u128),
+        }).unwrap(),
+        funds: vec![],
+      }),
+      CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "wine".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+          recipient: "creator".to_string(),
+          amount: Uint128::from(500000u128),
+        }).unwrap(),
+        funds: vec![],
+      }),
+    ]
+  );
+
+  // native coins can't be sent with the cw20 fee
+  let info = mock_info("creator", &[Coin { denom: "uusd".to_string(), amount: Uint128::from(1000000u128) }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, create_pair_msg.clone());
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+
+  // zero fee removes it, cw20 can't be sent without the fee
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg(Asset {
+    info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+    amount: Uint128::zero(),
+  }, None)).unwrap();
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("wine", &[]), receive_msg(1000000));
+  match res {
+    Err(ContractError::InvalidPairCreationFee {}) => assert!(true),
+    _ => panic!("Must return invalid pair creation fee error"),
+  }
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), create_pair_msg).unwrap();
+  assert_eq!(res.messages.len(), 1);
+
+  // deregister
+  factory.save_pair(deps.as_mut().storage, pair_key(&asset_infos, "type".to_string()), &PairInfo {
+    asset_infos: asset_infos.clone(),
+    contract_addr: Addr::unchecked("pair0000"),
+    liquidity_token: Addr::unchecked("liquidity0000"),
+    pair_type: PairType {
+      type_name: "type".to_string(),
+      tick_space: 10u16,
+      fee_rate: Decimal::from_ratio(3u128, 1000u128),
+      amp: None,
+    },
+    asset_decimals: Some([6, 6]),
+    creator: Some(Addr::unchecked("creator")),
+  }).unwrap();
+
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), QueryMsg::Pair {
+    asset_infos: asset_infos.clone(),
+    pair_type: Some("type".to_string()),
+  }).unwrap()).unwrap();
+  assert_eq!(res[0].creator, Some(Addr::unchecked("creator")));
+
+  let deregister_msg = ExecuteMsg::DeregisterPair {
+    asset_infos: asset_infos.clone(),
+    pair_type: "type".to_string(),
+  };
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), deregister_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), deregister_msg.clone()).unwrap();
+  assert_eq!(res.attributes, vec![
+    attr("action", "deregister_pair"),
+    attr("pair", "pair0000"),
+    attr("pair_type", "type"),
+  ]);
+
+  let res = factory.query(deps.as_ref(), QueryMsg::Pair {
+    asset_infos: asset_infos.clone(),
+    pair_type: Some("type".to_string()),
+  });
+  assert!(res.is_err());
+
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), QueryMsg::PairsByAsset {
+    asset_info: asset_infos[1].clone(),
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, vec![]);
+
+  // not registered
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), deregister_msg);
+  match res {
+    Err(ContractError::Std(StdError::NotFound { .. })) => assert!(true),
+    _ => panic!("Must return not found error"),
+  }
+}
+
+#[test]
+fn pairs_by_asset_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let pair_type = PairType {
+    type_name: "type".to_string(),
+    tick_space: 150u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  };
+
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+  let soju = AssetInfo::Token { contract_addr: "soju".to_string() };
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+
+  let pairs = [
+    ([wine.clone(), uusd.clone()], "pair0000"),
+    ([soju.clone(), uusd.clone()], "pair0001"),
+    ([wine.clone(), soju.clone()], "pair0002"),
+  ];
+
+  // pairs saved before the asset index, not indexed yet
+  for (asset_infos, contract_addr) in pairs.iter() {
+    factory.pairs.save(deps.as_mut().storage, pair_key(asset_infos, "type".to_string()), &PairInfo {
+      asset_infos: asset_infos.clone(),
+      contract_addr: Addr::unchecked(*contract_addr),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type: pair_type.clone(),
+    }).unwrap();
+  }
+
+  let query_msg = QueryMsg::PairsByAsset {
+    asset_info: uusd.clone(),
+    start_after: None,
+    limit: None,
+  };
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), query_msg.clone()).unwrap()).unwrap();
+  assert_eq!(res, vec![]);
+
+  // migrate index exist pairs
+  let res = factory.migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+  assert_eq!(res.attributes, vec![attr("action", "migrate"), attr("indexed_pairs", "3")]);
+
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+  assert_eq!(
+    res.iter().map(|pair| pair.contract_addr.to_string()).collect::<Vec<String>>(),
+    vec!["pair0001", "pair0000"]
+  );
+
+  // pagination
+  let query_msg = QueryMsg::PairsByAsset {
+    asset_info: wine.clone(),
+    start_after: None,
+    limit: Some(1),
+  };
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+  assert_eq!(res.len(), 1);
+  assert_eq!(res[0].contract_addr, Addr::unchecked("pair0002"));
+  assert_eq!(res[0].pair_type, "type".to_string());
+
+  let query_msg = QueryMsg::PairsByAsset {
+    asset_info: wine,
+    start_after: Some(AssetInfosWithType {
+      asset_infos: res[0].asset_infos.clone(),
+      pair_type: res[0].pair_type.clone(),
+    }),
+    limit: None,
+  };
+  let res: Vec<PairInfoWithType> = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+  assert_eq!(res.len(), 1);
+  assert_eq!(res[0].contract_addr, Addr::unchecked("pair0000"));
+}
+
+#[test]
+fn quote_asset_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+  let ukrw = AssetInfo::NativeToken { denom: "ukrw".to_string() };
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![uusd.clone()],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let info = mock_info("owner", &[]);
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+  // only owner can add
+  let add_msg = ExecuteMsg::AddQuoteAsset { asset_info: ukrw.clone() };
+  let info = mock_info("not_owner", &[]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, add_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let info = mock_info("owner", &[]);
+  let _res = factory.execute(deps.as_mut(), mock_env(), info.clone(), add_msg.clone()).unwrap();
+
+  let res = factory.execute(deps.as_mut(), mock_env(), info, add_msg);
+
+  match res {
+    Err(ContractError::QuoteAssetExists {}) => assert!(true),
+    _ => panic!("Must return quote asset exists error"),
+  }
+
+  let config: Config = from_binary(&factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.quote_assets, vec![uusd.clone(), ukrw.clone()]);
+
+  // wine-uusd with two pair types, wine-ukrw doesn't exist
+  for type_name in ["0.3%", "1%"] {
+    let asset_infos = [wine.clone(), uusd.clone()];
+    factory.save_pair(deps.as_mut().storage, pair_key(&asset_infos, type_name.to_string()), &PairInfo {
+      asset_infos,
+      contract_addr: Addr::unchecked("pair"),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type: PairType {
+        type_name: type_name.to_string(),
+        tick_space: 150u16,
+        fee_rate: Decimal::from_ratio(3u128, 1000u128),
+        amp: None,
+      }
+    }).unwrap();
+  }
+
+  let res: QuotePairsResponse = from_binary(
+    &factory.query(deps.as_ref(), QueryMsg::QuotePairs { asset_info: wine.clone() }).unwrap()
+  ).unwrap();
+  assert_eq!(
+    res.quote_pairs,
+    vec![QuotePairInfo {
+      quote_asset: uusd.clone(),
+      pair_types: vec!["0.3%".to_string(), "1%".to_string()],
+    }]
+  );
+  assert!(factory.quote_pair_existence(deps.as_ref(), wine.clone()).unwrap());
+  assert!(!factory.quote_pair_existence(deps.as_ref(), ukrw.clone()).unwrap());
+
+  // remove
+  let remove_msg = ExecuteMsg::RemoveQuoteAsset { asset_info: uusd };
+  let info = mock_info("owner", &[]);
+  let _res = factory.execute(deps.as_mut(), mock_env(), info.clone(), remove_msg.clone()).unwrap();
+
+  let res = factory.execute(deps.as_mut(), mock_env(), info, remove_msg);
+
+  match res {
+    Err(ContractError::QuoteAssetNotFound {}) => assert!(true),
+    _ => panic!("Must return quote asset not found error"),
+  }
+
+  assert!(!factory.quote_pair_existence(deps.as_ref(), wine).unwrap());
+}
+
+#[test]
+fn best_quote_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+
+  // wine-uusd with three fee tiers
+  let pairs = [("0.3%", "pair0000"), ("1%", "pair0001"), ("2%", "pair0002")];
+  for (type_name, contract_addr) in pairs.iter() {
+    let asset_infos = [wine.clone(), uusd.clone()];
+    factory.save_pair(deps.as_mut().storage, pair_key(&asset_infos, type_name.to_string()), &PairInfo {
+      asset_infos,
+      contract_addr: Addr::unchecked(*contract_addr),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type: PairType {
+        type_name: type_name.to_string(),
+        tick_space: 150u16,
+        fee_rate: Decimal::from_ratio(3u128, 1000u128),
+        amp: None,
+      }
+    }).unwrap();
+  }
+
+  // pair0002 doesn't have enough liquidity
+  deps.querier.with_tax(
+    Decimal::percent(1),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+  deps.querier.with_simulations(&[
+    (&"pair0000".to_string(), &SimulationResponse {
+      return_amount: Uint128::from(1005000u128),
+      commission_amount: Uint128::from(3000u128),
+      detail: None,
+    }),
+    (&"pair0001".to_string(), &SimulationResponse {
+      return_amount: Uint128::from(1010000u128),
+      commission_amount: Uint128::from(10000u128),
+      detail: None,
+    }),
+  ]);
+
+  let query_msg = QueryMsg::BestQuote {
+    offer_asset: Asset { info: wine.clone(), amount: Uint128::from(1000000u128) },
+    ask_asset_info: uusd.clone(),
+  };
+  let res: BestQuoteResponse = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+
+  // 1010000 - 1010000 / 1.01 on terra
+  let tax_amount = Asset { info: uusd.clone(), amount: Uint128::from(1010000u128) }
+    .compute_tax(&deps.as_ref().querier)
+    .unwrap();
+  #[cfg(feature = "terra")]
+  assert_eq!(tax_amount, Uint128::from(10000u128));
+
+  assert_eq!(res, BestQuoteResponse {
+    pair: PairInfoWithType {
+      asset_infos: [wine.clone(), uusd.clone()],
+      contract_addr: Addr::unchecked("pair0001"),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type: "1%".to_string(),
+    },
+    return_amount: Uint128::from(1010000u128) - tax_amount,
+    commission_amount: Uint128::from(10000u128),
+    tax_amount,
+  });
+
+  // native offer is simulated after its tax, no tax on cw20 return
+  let query_msg = QueryMsg::BestQuote {
+    offer_asset: Asset { info: uusd.clone(), amount: Uint128::from(1000000u128) },
+    ask_asset_info: wine.clone(),
+  };
+  let res: BestQuoteResponse = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+
+  let offer_tax_amount = Asset { info: uusd.clone(), amount: Uint128::from(1000000u128) }
+    .compute_tax(&deps.as_ref().querier)
+    .unwrap();
+  #[cfg(feature = "terra")]
+  assert_eq!(offer_tax_amount, Uint128::from(9901u128));
+
+  assert_eq!(res.pair.contract_addr, Addr::unchecked("pair0001"));
+  assert_eq!(
+    res.return_amount,
+    Uint128::from(1010000u128).multiply_ratio(Uint128::from(1000000u128) - offer_tax_amount, 1000000u128),
+  );
+  assert_eq!(res.tax_amount, offer_tax_amount);
+
+  // pair0001 walks more ticks than its max swap ticks
+  let step = SwapStepResponse {
+    tick_index: 0,
+    liquidity: Uint128::from(1000000000u128),
+    offer_amount: Uint128::from(500000u128),
+    return_amount: Uint128::from(505000u128),
+    commission_amount: Uint128::from(5000u128),
+    price_sqrt_before: DENOMINATOR,
+    price_sqrt_after: DENOMINATOR,
+  };
+  deps.querier.with_simulations(&[
+    (&"pair0000".to_string(), &SimulationResponse {
+      return_amount: Uint128::from(1005000u128),
+      commission_amount: Uint128::from(3000u128),
+      detail: None,
+    }),
+    (&"pair0001".to_string(), &SimulationResponse {
+      return_amount: Uint128::from(1010000u128),
+      commission_amount: Uint128::from(10000u128),
+      detail: Some(SimulationDetail {
+        steps: vec![step.clone(), SwapStepResponse { tick_index: 150, ..step }],
+        current_tick_index: 150,
+        current_price_sqrt: DENOMINATOR,
+        price: Decimal256::one(),
+      }),
+    }),
+  ]);
+  deps.querier.with_max_swap_ticks("pair0001", 1);
+  let query_msg = QueryMsg::BestQuote {
+    offer_asset: Asset { info: wine.clone(), amount: Uint128::from(1000000u128) },
+    ask_asset_info: uusd.clone(),
+  };
+  let res: BestQuoteResponse = from_binary(&factory.query(deps.as_ref(), query_msg).unwrap()).unwrap();
+  assert_eq!(res.pair.contract_addr, Addr::unchecked("pair0000"));
+
+  // no pair can swap
+  deps.querier.with_simulations(&[]);
+  let query_msg = QueryMsg::BestQuote {
+    offer_asset: Asset { info: wine, amount: Uint128::from(1000000u128) },
+    ask_asset_info: uusd,
+  };
+  let res = factory.query(deps.as_ref(), query_msg);
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, "No pair to swap"),
+    _ => panic!("Must return generic error"),
+  }
+}
+
+#[test]
+fn split_swap_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+
+  // same liquidity at price 1, only the fee rate is different
+  let pairs = [("0.3%", "pair0000", Decimal::permille(3)), ("1%", "pair0001", Decimal::percent(1))];
+  for (type_name, contract_addr, fee_rate) in pairs.iter() {
+    let asset_infos = [wine.clone(), uusd.clone()];
+    let pair_type = PairType {
+      type_name: type_name.to_string(),
+      tick_space: 10u16,
+      fee_rate: *fee_rate,
+      amp: None,
+    };
+    factory.save_pair(deps.as_mut().storage, pair_key(&asset_infos, type_name.to_string()), &PairInfo {
+      asset_infos: asset_infos.clone(),
+      contract_addr: Addr::unchecked(*contract_addr),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type,
+    }).unwrap();
+
+    let tick_infos: Vec<TickInfoResponse> = (-20..20).map(|tick_index| TickInfoResponse {
+      tick_index,
+      tick_info: TickInfo {
+        total_liquidity: Uint128::from(1000000000000u128),
+        ..TickInfo::default()
+      },
+    }).collect();
+    deps.querier.with_pool(contract_addr, &PairInfoResponse {
+      liquidity_token: "liquidity".to_string(),
+      asset_infos,
+      tick_space: 10u16,
+      fee_rate: *fee_rate,
+      price: Decimal256::one(),
+      current_price_sqrt: DENOMINATOR,
+      current_tick_index: 0,
+    }, &tick_infos);
+  }
+
+  // only native token
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: wine.clone(), amount: Uint128::from(100000000u128) },
+    ask_asset_info: uusd.clone(),
+    minimum_receive: None,
+    to: None,
+    referral: None,
+  };
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // small order goes to the cheapest pair only
+  let offer_amount = Uint128::from(100000000u128);
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: wine.clone(),
+    minimum_receive: None,
+    to: None,
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "pair0000".to_string(),
+    msg: to_binary(&PairExecuteMsg::Swap {
+      offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+      to: Some("addr0000".to_string()),
+      belief_price: None,
+      max_slippage: None,
+      max_ticks: None,
+      partial_fill: None,
+      referral: None,
+    }).unwrap(),
+    funds: vec![Coin { denom: "uusd".to_string(), amount: offer_amount }],
+  })]);
+
+  // large order moves the price of the cheap pair until the other one is as good
+  let offer_amount = Uint128::from(10000000000u128);
+  deps.querier.with_balance(&[(&"addr0001".to_string(), vec![Coin {
+    denom: "uusd".to_string(),
+    amount: Uint128::from(123u128),
+  }])]);
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: uusd.clone(),
+    minimum_receive: Some(Uint128::from(9000000000u128)),
+    to: Some("addr0001".to_string()),
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg);
+
+  // ask asset is same as offer asset, no pair
+  match res {
+    Err(ContractError::CanNotSwap {}) => assert!(true),
+    _ => panic!("Must return can not swap error"),
+  }
+
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: wine.clone(),
+    minimum_receive: Some(Uint128::from(9000000000u128)),
+    to: Some("addr0001".to_string()),
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+  assert_eq!(res.messages.len(), 3);
+  let mut leg_amounts: Vec<Uint128> = vec![];
+  for (i, contract_addr) in ["pair0000", "pair0001"].iter().enumerate() {
+    match &res.messages[i].msg {
+      CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: pair_contract, msg, funds }) => {
+        assert_eq!(pair_contract, contract_addr);
+        match from_binary(msg).unwrap() {
+          PairExecuteMsg::Swap { offer_asset, to, .. } => {
+            assert_eq!(funds, &vec![Coin { denom: "uusd".to_string(), amount: offer_asset.amount }]);
+            assert_eq!(to, Some("addr0001".to_string()));
+            leg_amounts.push(offer_asset.amount);
+          },
+          _ => panic!("Must be swap msg"),
+        }
+      },
+      _ => panic!("Must be wasm execute msg"),
+    }
+  }
+  assert_eq!(leg_amounts[0] + leg_amounts[1], offer_amount);
+  assert!(leg_amounts[0] > leg_amounts[1]);
+
+  // minimum receive is checked after every leg, with the balance before
+  assert_eq!(res.messages[2], SubMsg::new(WasmMsg::Execute {
+    contract_addr: "cosmos2contract".to_string(),
+    msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+      asset_info: wine.clone(),
+      prev_balance: Uint128::zero(),
+      minimum_receive: Uint128::from(9000000000u128),
+      receiver: "addr0001".to_string(),
+    }).unwrap(),
+    funds: vec![],
+  }));
+
+  // more than the liquidity of every pair
+  let offer_amount = Uint128::from(100000000000000u128);
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: wine.clone(),
+    minimum_receive: None,
+    to: None,
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg);
+
+  match res {
+    Err(ContractError::CanNotSwap {}) => assert!(true),
+    _ => panic!("Must return can not swap error"),
+  }
+
+  // the cheap pair stops at its max swap ticks, the rest goes to the other one
+  deps.querier.with_max_swap_ticks("pair0000", 1);
+  let offer_amount = Uint128::from(10000000000u128);
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: wine.clone(),
+    minimum_receive: None,
+    to: None,
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+  assert_eq!(res.messages.len(), 2);
+  let mut capped_leg_amounts: Vec<Uint128> = vec![];
+  for (i, contract_addr) in ["pair0000", "pair0001"].iter().enumerate() {
+    match &res.messages[i].msg {
+      CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: pair_contract, msg, .. }) => {
+        assert_eq!(pair_contract, contract_addr);
+        match from_binary(msg).unwrap() {
+          PairExecuteMsg::Swap { offer_asset, .. } => capped_leg_amounts.push(offer_asset.amount),
+          _ => panic!("Must be swap msg"),
+        }
+      },
+      _ => panic!("Must be wasm execute msg"),
+    }
+  }
+  assert_eq!(capped_leg_amounts[0] + capped_leg_amounts[1], offer_amount);
+  assert!(capped_leg_amounts[0] < leg_amounts[0]);
+  assert!(capped_leg_amounts[0] < capped_leg_amounts[1]);
+  deps.querier.with_max_swap_ticks("pair0000", 100);
+
+  // cw20 offer via receive hook, referral is passed to every leg
+  let referral = Referral { address: "referrer".to_string(), commission_bps: 30 };
+  let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+    sender: "addr0000".to_string(),
+    amount: Uint128::from(100000000u128),
+    msg: to_binary(&Cw20HookMsg::SplitSwap {
+      ask_asset_info: uusd.clone(),
+      minimum_receive: None,
+      to: None,
+      referral: Some(referral.clone()),
+    }).unwrap(),
+  });
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("wine", &[]), msg).unwrap();
+
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "wine".to_string(),
+    msg: to_binary(&Cw20ExecuteMsg::Send {
+      contract: "pair0000".to_string(),
+      amount: Uint128::from(100000000u128),
+      msg: to_binary(&PairCw20HookMsg::Swap {
+        to: Some("addr0000".to_string()),
+        belief_price: None,
+        max_slippage: None,
+        max_ticks: None,
+        partial_fill: None,
+        referral: Some(referral),
+      }).unwrap(),
+    }).unwrap(),
+    funds: vec![],
+  })]);
+
+  // assert minimum receive
+  let msg = ExecuteMsg::AssertMinimumReceive {
+    asset_info: uusd.clone(),
+    prev_balance: Uint128::from(100u128),
+    minimum_receive: Uint128::from(24u128),
+    receiver: "addr0001".to_string(),
+  };
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+
+  match res {
+    Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive }) => {
+      assert_eq!(minimum_receive, Uint128::from(24u128));
+      assert_eq!(receive, Uint128::from(23u128));
+    },
+    _ => panic!("Must return minimum receive assertion error"),
+  }
+
+  let msg = ExecuteMsg::AssertMinimumReceive {
+    asset_info: uusd,
+    prev_balance: Uint128::from(100u128),
+    minimum_receive: Uint128::from(23u128),
+    receiver: "addr0001".to_string(),
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+}
+
+#[test]
+#[cfg(feature = "terra")]
+fn split_swap_tax_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+
+  let wine = AssetInfo::Token { contract_addr: "wine".to_string() };
+  let uusd = AssetInfo::NativeToken { denom: "uusd".to_string() };
+
+  let pairs = [("0.3%", "pair0000", Decimal::permille(3)), ("1%", "pair0001", Decimal::percent(1))];
+  for (type_name, contract_addr, fee_rate) in pairs.iter() {
+    let asset_infos = [wine.clone(), uusd.clone()];
+    factory.save_pair(deps.as_mut().storage, pair_key(&asset_infos, type_name.to_string()), &PairInfo {
+      asset_infos: asset_infos.clone(),
+      contract_addr: Addr::unchecked(*contract_addr),
+      liquidity_token: Addr::unchecked("liquidity"),
+      asset_decimals: Some([6, 6]),
+      creator: None,
+      pair_type: PairType {
+        type_name: type_name.to_string(),
+        tick_space: 10u16,
+        fee_rate: *fee_rate,
+        amp: None,
+      },
+    }).unwrap();
+
+    let tick_infos: Vec<TickInfoResponse> = (-20..20).map(|tick_index| TickInfoResponse {
+      tick_index,
+      tick_info: TickInfo {
+        total_liquidity: Uint128::from(1000000000000u128),
+        ..TickInfo::default()
+      },
+    }).collect();
+    deps.querier.with_pool(contract_addr, &PairInfoResponse {
+      liquidity_token: "liquidity".to_string(),
+      asset_infos,
+      tick_space: 10u16,
+      fee_rate: *fee_rate,
+      price: Decimal256::one(),
+      current_price_sqrt: DENOMINATOR,
+      current_tick_index: 0,
+    }, &tick_infos);
+  }
+
+  // 1% tax capped at 1 uusd per send
+  let tax_cap = Uint128::from(1000000u128);
+  deps.querier.with_tax(Decimal::percent(1), &[(&"uusd".to_string(), &tax_cap)]);
+
+  let offer_amount = Uint128::from(10000000000u128);
+  let msg = ExecuteMsg::SplitSwap {
+    offer_asset: Asset { info: uusd.clone(), amount: offer_amount },
+    ask_asset_info: wine.clone(),
+    minimum_receive: None,
+    to: None,
+    referral: None,
+  };
+  let info = mock_info("addr0000", &[Coin { denom: "uusd".to_string(), amount: offer_amount }]);
+  let res = factory.execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+  // every leg pays the capped tax
+  assert_eq!(res.messages.len(), 2);
+  let mut sent_amount = Uint128::zero();
+  for msg in res.messages.iter() {
+    match &msg.msg {
+      CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => match from_binary(msg).unwrap() {
+        PairExecuteMsg::Swap { offer_asset, .. } => {
+          assert_eq!(funds, &vec![Coin { denom: "uusd".to_string(), amount: offer_asset.amount }]);
+          sent_amount += offer_asset.amount;
+        },
+        _ => panic!("Must be swap msg"),
+      },
+      _ => panic!("Must be wasm execute msg"),
+    }
+  }
+  assert_eq!(sent_amount, offer_amount - tax_cap - tax_cap);
+  assert!(res.attributes.contains(&attr("offer_amount", sent_amount.to_string())));
+}
+#[test]
+fn stable_pair_type_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+  deps.querier.with_token_decimals(&[("ust", 6), ("wust", 8)]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let info = mock_info("owner", &[]);
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+  let native_decimals_msg = ExecuteMsg::AddNativeTokenDecimals { denom: "uusd".to_string(), decimals: 6 };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg).unwrap();
+
+  // invalid amp
+  let add_pair_type_msg = ExecuteMsg::AddPairType {
+    type_name: "stable".to_string(),
+    tick_space: 1u16,
+    fee_rate: Decimal::from_ratio(4u128, 10000u128),
+    amp: Some(0),
+  };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), add_pair_type_msg);
+
+  match res {
+    Err(ContractError::InvalidAmp {}) => assert!(true),
+    _ => panic!("Must return invalid amp error"),
+  }
+
+  let add_pair_type_msg = ExecuteMsg::AddPairType {
+    type_name: "stable".to_string(),
+    tick_space: 1u16,
+    fee_rate: Decimal::from_ratio(4u128, 10000u128),
+    amp: Some(100),
+  };
+
+  factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), add_pair_type_msg).unwrap();
+
+  let pair_type_res: PairTypeResponse = from_binary(
+    &factory.query(deps.as_ref(), QueryMsg::PairType { type_name: "stable".to_string() }).unwrap()
+  ).unwrap();
+  assert_eq!(pair_type_res.amp, Some(100));
+
+  let asset_infos = [
+    AssetInfo::NativeToken { denom: "uusd".to_string() },
+    AssetInfo::Token { contract_addr: "ust".to_string() },
+  ];
+
+  let create_pair_msg = ExecuteMsg::CreatePair {
+    asset_infos: asset_infos.clone(),
+    initial_price: Decimal::one(),
+    pair_type: "stable".to_string()
+  };
+
+  // stable pair code id is not set
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg.clone());
+
+  match res {
+    Err(ContractError::StablePairCodeIdNotSet {}) => assert!(true),
+    _ => panic!("Must return stable pair code id not set error"),
+  }
+
+  let update_msg = ExecuteMsg::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: Some(456u64),
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg).unwrap();
+
+  // stable pair assets must have the same decimals
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::CreatePair {
+    asset_infos: [
+      AssetInfo::NativeToken { denom: "uusd".to_string() },
+      AssetInfo::Token { contract_addr: "wust".to_string() },
+    ],
+    initial_price: Decimal::one(),
+    pair_type: "stable".to_string()
+  });
+
+  match res {
+    Err(ContractError::StableDecimalsMismatch {}) => assert!(true),
+    _ => panic!("Must return stable decimals mismatch error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg).unwrap();
+  assert_eq!(
+    res.messages,
+    vec![SubMsg {
+      id: 1,
+      gas_limit: None,
+      msg: WasmMsg::Instantiate {
+        code_id: 456u64,
+        funds: vec![],
+        admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+        label: "".to_string(),
+        msg: to_binary(&StablePairInstantiateMsg {
+          asset_infos,
+          token_code_id: 32u64,
+          tick_space: 1u16,
+          fee_rate: Decimal::from_ratio(4u128, 10000u128),
+          amp: 100,
+          asset_decimals: Some([6, 6]),
+        }).unwrap()
+      }.into(),
+      reply_on: ReplyOn::Success
+    }]
+  );
+}
+
+#[test]
+fn max_swap_ticks_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies_with_querier(&[]);
+  deps.querier.with_token_decimals(&[("wine", 6)]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let native_decimals_msg = ExecuteMsg::AddNativeTokenDecimals { denom: "uusd".to_string(), decimals: 6 };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), native_decimals_msg).unwrap();
+
+  let update_msg = |max_swap_ticks: u32| ExecuteMsg::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: Some(max_swap_ticks),
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg(0));
+  match res {
+    Err(ContractError::InvalidMaxSwapTicks {}) => assert!(true),
+    _ => panic!("Must return invalid max swap ticks error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg(30)).unwrap();
+  let config: Config = from_binary(&factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.max_swap_ticks, Some(30));
+
+  // new pairs get the max swap ticks
+  let add_pair_type_msg = ExecuteMsg::AddPairType {
+    type_name: "type".to_string(),
+    tick_space: 10u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), add_pair_type_msg).unwrap();
+
+  let asset_infos = [
+    AssetInfo::Token { contract_addr: "wine".to_string() },
+    AssetInfo::NativeToken { denom: "uusd".to_string() },
+  ];
+  let create_pair_msg = ExecuteMsg::CreatePair {
+    asset_infos: asset_infos.clone(),
+    initial_price: Decimal::one(),
+    pair_type: "type".to_string()
+  };
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), create_pair_msg).unwrap();
+  assert_eq!(
+    res.messages[0].msg,
+    WasmMsg::Instantiate {
+      code_id: 123u64,
+      funds: vec![],
+      admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+      label: "".to_string(),
+      msg: to_binary(&PairInstantiateMsg {
+        asset_infos,
+        token_code_id: 32u64,
+        initial_price: Decimal::one(),
+        tick_space: 10u16,
+        fee_rate: Decimal::from_ratio(3u128, 1000u128),
+        history_config: None,
+        max_swap_ticks: Some(30),
+        asset_decimals: Some([6, 6]),
+      }).unwrap()
+    }.into()
+  );
+
+  // update the pair
+  let update_pair_msg = ExecuteMsg::UpdatePairConfig {
+    pair: "pair0000".to_string(),
+    max_swap_ticks: Some(20),
+    history_config: Some(HistoryConfig { hourly_size: 24, daily_size: 30 }),
+  };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), update_pair_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::UpdatePairConfig {
+    pair: "pair0000".to_string(),
+    max_swap_ticks: None,
+    history_config: Some(HistoryConfig { hourly_size: 24, daily_size: 0 }),
+  });
+  match res {
+    Err(ContractError::InvalidHistorySize {}) => assert!(true),
+    _ => panic!("Must return invalid history size error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_pair_msg).unwrap();
+  assert_eq!(
+    res.messages,
+    vec![SubMsg::new(WasmMsg::Execute {
+      contract_addr: "pair0000".to_string(),
+      msg: to_binary(&PairExecuteMsg::UpdateConfig {
+        max_swap_ticks: Some(20),
+        history_config: Some(HistoryConfig { hourly_size: 24, daily_size: 30 }),
+      }).unwrap(),
+      funds: vec![],
+    })]
+  );
+}
+
+#[test]
+fn fee_discount_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let set_msg = |address: &str, discount: Decimal| ExecuteMsg::SetFeeDiscount {
+    address: address.to_string(),
+    discount,
+  };
+
+  // only owner
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), set_msg("market_maker", Decimal::percent(50)));
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // more than 100%
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), set_msg("market_maker", Decimal::percent(101)));
+  match res {
+    Err(ContractError::InvalidFeeDiscount {}) => assert!(true),
+    _ => panic!("Must return invalid fee discount error"),
+  }
+
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), set_msg("market_maker", Decimal::percent(50))).unwrap();
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), set_msg("partner", Decimal::percent(20))).unwrap();
+
+  let res: FeeDiscountResponse = from_binary(&factory.query(deps.as_ref(), QueryMsg::FeeDiscount {
+    address: "market_maker".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(res.discount, Decimal::percent(50));
+
+  // not registered
+  let res: FeeDiscountResponse = from_binary(&factory.query(deps.as_ref(), QueryMsg::FeeDiscount {
+    address: "trader".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(res.discount, Decimal::zero());
+
+  let res: Vec<FeeDiscountResponse> = from_binary(&factory.query(deps.as_ref(), QueryMsg::FeeDiscounts {
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, vec![
+    FeeDiscountResponse { address: "market_maker".to_string(), discount: Decimal::percent(50) },
+    FeeDiscountResponse { address: "partner".to_string(), discount: Decimal::percent(20) },
+  ]);
+
+  // zero discount removes it
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), set_msg("market_maker", Decimal::zero())).unwrap();
+  let res: Vec<FeeDiscountResponse> = from_binary(&factory.query(deps.as_ref(), QueryMsg::FeeDiscounts {
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, vec![FeeDiscountResponse { address: "partner".to_string(), discount: Decimal::percent(20) }]);
+}
+
+#[test]
+fn timelock_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: Some(86400),
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let add_pair_type_action = TimelockAction::AddPairType {
+    type_name: "type".to_string(),
+    tick_space: 10u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  };
+
+  // can't execute directly
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::AddPairType {
+    type_name: "type".to_string(),
+    tick_space: 10u16,
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    amp: None,
+  });
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::DeregisterPair {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() },
+    ],
+    pair_type: "type".to_string(),
+  });
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  // only owner can queue
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::QueueAction {
+    action: add_pair_type_action.clone(),
+  });
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: add_pair_type_action.clone(),
+  }).unwrap();
+  let executable_at = mock_env().block.time.seconds() + 86400;
+  assert_eq!(res.attributes, vec![
+    attr("action", "queue_action"),
+    attr("id", "0"),
+    attr("executable_at", executable_at.to_string()),
+  ]);
+
+  let fee_discount_action = TimelockAction::SetFeeDiscount {
+    address: "market_maker".to_string(),
+    discount: Decimal::percent(50),
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: fee_discount_action.clone(),
+  }).unwrap();
+
+  let res: Vec<PendingAction> = from_binary(&factory.query(deps.as_ref(), QueryMsg::PendingActions {
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, vec![
+    PendingAction { id: 0, action: add_pair_type_action, executable_at, queued_at: mock_env().block.time.seconds() },
+    PendingAction { id: 1, action: fee_discount_action, executable_at, queued_at: mock_env().block.time.seconds() },
+  ]);
+
+  // before the delay
+  let mut env = mock_env();
+  env.block.time = env.block.time.plus_seconds(86399);
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 0 });
+  match res {
+    Err(ContractError::PendingActionNotReady { executable_at: at }) => assert_eq!(at, executable_at),
+    _ => panic!("Must return pending action not ready error"),
+  }
+
+  // cancel
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::CancelAction { id: 1 });
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::CancelAction { id: 1 }).unwrap();
+
+  env.block.time = env.block.time.plus_seconds(1);
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 1 });
+  match res {
+    Err(ContractError::PendingActionNotFound {}) => assert!(true),
+    _ => panic!("Must return pending action not found error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::ExecuteAction { id: 0 });
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 0 }).unwrap();
+  assert_eq!(res.attributes[0], attr("action", "add_pair_type"));
+  assert_eq!(res.attributes.last().unwrap(), &attr("executed_action_id", "0"));
+
+  let pair_type: PairType = from_binary(&factory.query(deps.as_ref(), QueryMsg::PairType {
+    type_name: "type".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(pair_type.tick_space, 10u16);
+
+  let res: Vec<PendingAction> = from_binary(&factory.query(deps.as_ref(), QueryMsg::PendingActions {
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, vec![]);
+
+  // raising the delay applies to the queued actions too
+  let update_delay_action = |timelock_delay: u64| TimelockAction::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: Some(timelock_delay),
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+  let queued_at = env.block.time.seconds();
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: TimelockAction::RampAmp {
+      pair: "stable_pair".to_string(),
+      next_amp: 200,
+      next_amp_time: queued_at + 3 * 86400,
+    },
+  }).unwrap();
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: update_delay_action(2 * 86400),
+  }).unwrap();
+
+  env.block.time = env.block.time.plus_seconds(86400);
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 3 }).unwrap();
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 2 });
+  match res {
+    Err(ContractError::PendingActionNotReady { executable_at: at }) => assert_eq!(at, queued_at + 2 * 86400),
+    _ => panic!("Must return pending action not ready error"),
+  }
+
+  // amp ramp is sent to the stable pair by the factory
+  env.block.time = env.block.time.plus_seconds(86400);
+  let res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 2 }).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "stable_pair".to_string(),
+    msg: to_binary(&StablePairExecuteMsg::RampAmp {
+      next_amp: 200,
+      next_amp_time: queued_at + 3 * 86400,
+    }).unwrap(),
+    funds: vec![],
+  })]);
+
+  // disable the timelock through the timelock
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: update_delay_action(0),
+  }).unwrap();
+  env.block.time = env.block.time.plus_seconds(2 * 86400);
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 4 }).unwrap();
+
+  let config: Config = from_binary(&factory.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.timelock_delay, 0);
+
+  let _res = factory.execute(deps.as_mut(), env, mock_info("owner", &[]), ExecuteMsg::SetFeeDiscount {
+    address: "market_maker".to_string(),
+    discount: Decimal::percent(50),
+  }).unwrap();
+}
+
+#[test]
+fn migrate_contract_test() {
+  let factory = FactoryContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    owner: "owner".to_string(),
+    pair_code_id: 123u64,
+    token_code_id: 32u64,
+    quote_assets: vec![],
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: None,
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+
+  let _res = factory.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let migrate_msg = ExecuteMsg::MigrateContract {
+    contract: "pair0000".to_string(),
+    code_id: 124u64,
+    msg: to_binary(&PairMigrateMsg { factory: None }).unwrap(),
+  };
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), migrate_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // the factory is the admin of the pair
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), migrate_msg.clone()).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Migrate {
+    contract_addr: "pair0000".to_string(),
+    new_code_id: 124u64,
+    msg: to_binary(&PairMigrateMsg { factory: None }).unwrap(),
+  })]);
+  assert_eq!(res.attributes, vec![
+    attr("action", "migrate_contract"),
+    attr("contract", "pair0000"),
+    attr("code_id", "124"),
+  ]);
+
+  // timelocked while the delay is set
+  let update_msg = ExecuteMsg::UpdateConfig {
+    token_code_id: None,
+    pair_code_id: None,
+    stable_pair_code_id: None,
+    max_swap_ticks: None,
+    max_referral_commission_bps: None,
+    timelock_delay: Some(86400),
+    pair_creation_fee: None,
+    fee_collector: None,
+  };
+  let _res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update_msg).unwrap();
+
+  let res = factory.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), migrate_msg);
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  // lp token minter handoff is sent to the pair by the factory
+  let mut env = mock_env();
+  let _res = factory.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::QueueAction {
+    action: TimelockAction::ProposeNewMinter {
+      pair: "pair0000".to_string(),
+      minter: "pair0001".to_string(),
+      expires: Expiration::Never {},
+    },
+  }).unwrap();
+
+  env.block.time = env.block.time.plus_seconds(86400);
+  let res = factory.execute(deps.as_mut(), env, mock_info("owner", &[]), ExecuteMsg::ExecuteAction { id: 0 }).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "pair0000".to_string(),
+    msg: to_binary(&PairExecuteMsg::ProposeNewMinter {
+      minter: "pair0001".to_string(),
+      expires: Expiration::Never {},
+    }).unwrap(),
+    funds: vec![],
+  })]);
+}
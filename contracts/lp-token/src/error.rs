@@ -17,4 +17,16 @@ pub enum ContractError {
 
   #[error("Cannot set approval that is already expired")]
   Expired {},
+
+  #[error("Invalid split amount")]
+  InvalidSplitAmount {},
+
+  #[error("Tokens must have the same range and owner")]
+  MergeMismatch {},
+
+  #[error("Minter proposal not found")]
+  MinterProposalNotFound {},
+
+  #[error("Minter proposal expired")]
+  MinterProposalExpired {},
 }
\ No newline at end of file
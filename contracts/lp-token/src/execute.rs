@@ -1,10 +1,19 @@
 
-use cosmwasm_std::{to_binary, Binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, WasmMsg};
+use cosmwasm_std::{to_binary, Binary, CosmosMsg, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+  Uint128, WasmMsg};
 use cw0::Expiration;
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Map;
 use crate::error::ContractError;
-use wineswap::lp_token::{LpReceiveMsg, ConfigResponse, InstantiateMsg, ExecuteMsg};
+use wineswap::lp_token::{LpReceiveMsg, ConfigResponse, InstantiateMsg, ExecuteMsg, MigrateMsg};
 use wineswap::pair::ExecuteMsg as PairExecuteMsg;
-use crate::state::{LiquidityInfo, LpContract, FeeInfo, Approval};
+use wineswap_math::fee::decimal_to_fee_growth;
+use wineswap::asset::Asset;
+use wineswap::ownership::OwnershipProposal;
+use crate::state::{LiquidityInfo, LegacyLiquidityInfo, LpContract, FeeInfo, Approval};
+
+const CONTRACT_NAME: &str = "crates.io:wine-lp-token";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 impl<'a> LpContract<'a> {
   pub fn instantiate(
@@ -14,16 +23,59 @@ impl<'a> LpContract<'a> {
     _info: MessageInfo,
     msg: InstantiateMsg,
   ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     let config = ConfigResponse{
       name: msg.name,
       symbol: msg.symbol,
       minter: deps.api.addr_validate(&msg.minter)?,
+      minter_proposal: None,
     };
   
     self.config.save(deps.storage, &config)?;
     Ok(Response::default())
   }
 
+  pub fn migrate(&self, deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // contracts without version store fee growth as Decimal
+    if get_contract_version(deps.storage).is_ok() {
+      // fee growth is already Q128.128, only bump the version
+      set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+      return Ok(Response::new().add_attribute("action", "migrate"));
+    }
+
+    // read tokens as legacy format from the same namespace
+    let legacy_tokens: Map<&str, LegacyLiquidityInfo> = Map::new("tokens");
+    let tokens = legacy_tokens
+      .range(deps.storage, None, None, Order::Ascending)
+      .collect::<StdResult<Vec<_>>>()?;
+
+    for (k, v) in tokens.iter() {
+      let token_id = String::from_utf8_lossy(k).to_string();
+      let token = LiquidityInfo {
+        owner: v.owner.clone(),
+        liquidity: v.liquidity,
+        upper_tick_index: v.upper_tick_index,
+        lower_tick_index: v.lower_tick_index,
+        last_updated_fee_infos: v.last_updated_fee_infos.iter().map(|info| FeeInfo {
+          tick_index: info.tick_index,
+          last_fee_growth_0: decimal_to_fee_growth(info.last_fee_growth_0),
+          last_fee_growth_1: decimal_to_fee_growth(info.last_fee_growth_1),
+        }).collect(),
+        approvals: v.approvals.clone(),
+      };
+      // owner doesn't change, so use new data as old data for the index
+      self.tokens.replace(deps.storage, &token_id, Some(&token), Some(&token))?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "migrate")
+      .add_attribute("migrated_tokens", tokens.len().to_string())
+    )
+  }
+
   pub fn execute(
     &self,
     deps: DepsMut,
@@ -65,8 +117,16 @@ impl<'a> LpContract<'a> {
         token_id,
         amount,
         add,
-      } => self.update_liquidity(deps, env, info, token_id, amount, add)
-    } 
+      } => self.update_liquidity(deps, env, info, token_id, amount, add),
+      ExecuteMsg::Split {
+        token_id,
+        liquidity,
+      } => self.split(deps, env, info, token_id, liquidity),
+      ExecuteMsg::Merge { token_ids } => self.merge(deps, env, info, token_ids),
+      ExecuteMsg::ProposeNewMinter { minter, expires } => self.propose_new_minter(deps, env, info, minter, expires),
+      ExecuteMsg::ClaimMinter {} => self.claim_minter(deps, env, info),
+      ExecuteMsg::DropMinterProposal {} => self.drop_minter_proposal(deps, env, info),
+    }
   }
 }
 
@@ -343,6 +403,200 @@ impl<'a> LpContract<'a> {
 
     Ok(Response::new())
   }
+
+  pub fn split(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    liquidity: Uint128,
+  ) -> Result<Response, ContractError> {
+    let mut token = self.tokens.load(deps.storage, &token_id)?;
+
+    // only owner can split
+    if token.owner != info.sender {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    if liquidity.is_zero() || liquidity >= token.liquidity {
+      return Err(ContractError::InvalidSplitAmount {});
+    }
+
+    token.liquidity = token.liquidity.checked_sub(liquidity)?;
+    self.tokens.save(deps.storage, &token_id, &token)?;
+
+    // fee infos are carried forward, so unclaimed reward is split by liquidity
+    let new_token = LiquidityInfo {
+      owner: token.owner.clone(),
+      liquidity,
+      upper_tick_index: token.upper_tick_index,
+      lower_tick_index: token.lower_tick_index,
+      last_updated_fee_infos: token.last_updated_fee_infos.clone(),
+      approvals: vec![],
+    };
+
+    let token_count = self.token_count(deps.storage)?;
+    self.tokens
+      .update(deps.storage, &token_count.to_string(), |old| match old {
+        Some(_) => Err(ContractError::Claimed {}),
+        None => Ok(new_token),
+      })?;
+
+    self.increment_tokens(deps.storage)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "split")
+      .add_attribute("owner", token.owner)
+      .add_attribute("token_id", token_id)
+      .add_attribute("new_token_id", token_count.to_string())
+      .add_attribute("liquidity", liquidity.to_string())
+    )
+  }
+
+  pub fn merge(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+  ) -> Result<Response, ContractError> {
+    let mut sorted_ids = token_ids.clone();
+    sorted_ids.sort();
+    sorted_ids.dedup();
+    if token_ids.len() < 2 || sorted_ids.len() != token_ids.len() {
+      return Err(ContractError::MergeMismatch {});
+    }
+
+    let config = self.config.load(deps.storage)?;
+    let mut token = self.tokens.load(deps.storage, &token_ids[0])?;
+
+    // only owner can merge
+    if token.owner != info.sender {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    // fee infos differ by token, so unclaimed rewards of every token are claimed together
+    let mut rewards: [Asset; 2] = self.reward(deps.as_ref(), token_ids[0].clone())?.rewards;
+    for token_id in token_ids.iter().skip(1) {
+      let other = self.tokens.load(deps.storage, token_id)?;
+      if other.owner != token.owner
+        || other.upper_tick_index != token.upper_tick_index
+        || other.lower_tick_index != token.lower_tick_index {
+        return Err(ContractError::MergeMismatch {});
+      }
+
+      let reward = self.reward(deps.as_ref(), token_id.clone())?;
+      rewards[0].amount = rewards[0].amount.checked_add(reward.rewards[0].amount)?;
+      rewards[1].amount = rewards[1].amount.checked_add(reward.rewards[1].amount)?;
+      token.liquidity = token.liquidity.checked_add(other.liquidity)?;
+    }
+
+    for token_id in token_ids.iter().skip(1) {
+      self.tokens.remove(deps.storage, token_id)?;
+    }
+
+    token.last_updated_fee_infos = self.get_fee_infos(
+      deps.querier,
+      config.minter.to_string(),
+      token.upper_tick_index,
+      token.lower_tick_index
+    )?;
+    self.tokens.save(deps.storage, &token_ids[0], &token)?;
+
+    let claim_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+      contract_addr: config.minter.to_string(),
+      msg: to_binary(&PairExecuteMsg::ClaimReward {
+        token_id: token_ids[0].clone(),
+        rewards,
+      })?,
+      funds: vec![],
+    });
+
+    Ok(Response::new().add_message(claim_msg)
+      .add_attribute("action", "merge")
+      .add_attribute("owner", token.owner)
+      .add_attribute("token_id", token_ids[0].clone())
+      .add_attribute("merged_token_ids", token_ids[1..].join(", "))
+      .add_attribute("liquidity", token.liquidity.to_string())
+    )
+  }
+
+  pub fn propose_new_minter(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter: String,
+    expires: Expiration,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.minter {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    if expires.is_expired(&env.block) {
+      return Err(ContractError::MinterProposalExpired {});
+    }
+
+    let minter = deps.api.addr_validate(&minter)?;
+    config.minter_proposal = Some(OwnershipProposal { owner: minter.clone(), expires });
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "propose_new_minter")
+      .add_attribute("minter", minter.to_string())
+      .add_attribute("expires", expires.to_string())
+    )
+  }
+
+  pub fn claim_minter(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    let proposal = config.minter_proposal.ok_or(ContractError::MinterProposalNotFound {})?;
+
+    if info.sender != proposal.owner {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    if proposal.expires.is_expired(&env.block) {
+      return Err(ContractError::MinterProposalExpired {});
+    }
+
+    config.minter = proposal.owner;
+    config.minter_proposal = None;
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "claim_minter")
+      .add_attribute("minter", config.minter.to_string())
+    )
+  }
+
+  pub fn drop_minter_proposal(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    let mut config = self.config.load(deps.storage)?;
+    if info.sender != config.minter {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    if config.minter_proposal.is_none() {
+      return Err(ContractError::MinterProposalNotFound {});
+    }
+
+    config.minter_proposal = None;
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "drop_minter_proposal"))
+  }
 }
 
 /// help function
@@ -355,15 +609,15 @@ impl<'a> LpContract<'a> {
     recipient: &str, 
     token_id: &str
   ) -> Result<LiquidityInfo, ContractError> {
-    let mut token = self.tokens.load(deps.storage, &token_id)?;
+    let mut token = self.tokens.load(deps.storage, token_id)?;
 
-    self.check_can_send(&env, &info, &token)?;
+    self.check_can_send(env, info, &token)?;
 
     token.owner = deps.api.addr_validate(recipient)?;
 
     // reset approvals
     token.approvals = vec![];
-    self.tokens.save(deps.storage, &token_id, &token)?;
+    self.tokens.save(deps.storage, token_id, &token)?;
     Ok(token)
   }
 
@@ -4,7 +4,7 @@ mod query;
 mod state;
 
 pub use crate::error::ContractError;
-pub use wineswap::lp_token::{InstantiateMsg, ExecuteMsg, QueryMsg};
+pub use wineswap::lp_token::{InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg};
 pub use crate::state::LpContract;
 
 #[cfg(test)]
@@ -42,6 +42,12 @@ pub mod entry {
       tract.execute(deps, env, info, msg)
   }
 
+  #[entry_point]
+  pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let tract = LpContract::default();
+    tract.migrate(deps, env, msg)
+  }
+
   #[entry_point]
   pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let tract = LpContract::default();
@@ -9,6 +9,7 @@ use std::collections::HashMap;
 
 use wineswap::pair::{TickInfosResponse, PairInfoResponse, TickInfoResponse, TickInfo};
 use wineswap::asset::{AssetInfo};
+use wineswap_math::tick::DENOMINATOR;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -147,6 +148,7 @@ impl WasmMockQuerier {
                                     tick_space: 20,
                                     fee_rate: Decimal::zero(),
                                     price: Decimal256::one(),
+                                    current_price_sqrt: DENOMINATOR,
                                     current_tick_index: 0
                                 })
                                 .unwrap(),
@@ -1,12 +1,14 @@
-use cosmwasm_std::{to_binary, Decimal, WasmQuery, QueryRequest, Binary, Deps, QuerierWrapper, Order, StdError, StdResult};
+use cosmwasm_std::{to_binary, Uint256, WasmQuery, QueryRequest, Binary, Deps, QuerierWrapper, Order, StdError, StdResult};
 
 use cw_storage_plus::Bound;
 
 pub use wineswap::lp_token::{
-  QueryMsg, OwnerOfResponse, ConfigResponse, LiquidityInfoResponse, TokensResponse, MinterResponse, RewardResponse
+  QueryMsg, OwnerOfResponse, ConfigResponse, LiquidityInfoResponse, TokensResponse, MinterResponse, NumTokensResponse,
+  RewardResponse
 };
 pub use wineswap::pair::{TickInfosResponse, PairInfoResponse, QueryMsg as PairQueryMsg};
 pub use wineswap::asset::Asset;
+use wineswap_math::fee::{compute_reward, fee_growth_add, fee_growth_sub};
 use crate::state::{LpContract, FeeInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -87,6 +89,12 @@ impl<'a> LpContract<'a> {
     })
   }
 
+  fn num_tokens(&self, deps: Deps) -> StdResult<NumTokensResponse> {
+    Ok(NumTokensResponse {
+      count: self.token_count(deps.storage)?,
+    })
+  }
+
   pub fn reward(&self, deps: Deps, token_id: String) -> StdResult<RewardResponse> {
     let token = self.tokens.load(deps.storage, &token_id)?;
     let config = self.config.load(deps.storage)?;
@@ -97,23 +105,30 @@ impl<'a> LpContract<'a> {
     let asset_infos = pair_config.asset_infos;
     let new_infos: Vec<FeeInfo> = self.get_fee_infos(deps.querier, config.minter.to_string(), token.upper_tick_index, token.lower_tick_index)?;
     
-    let mut reward_per_liquidity_0 = Decimal::zero();
-    let mut reward_per_liquidity_1 = Decimal::zero();
+    let mut reward_per_liquidity_0 = Uint256::zero();
+    let mut reward_per_liquidity_1 = Uint256::zero();
     
     // sum (global state - lp token state) of each growth
+    // growth can wrap around, so use wrapping difference
     for i in 0..new_infos.len() {
-      reward_per_liquidity_0 = reward_per_liquidity_0 + new_infos[i].last_fee_growth_0 - token.last_updated_fee_infos[i].last_fee_growth_0;
-      reward_per_liquidity_1 = reward_per_liquidity_1 + new_infos[i].last_fee_growth_1 - token.last_updated_fee_infos[i].last_fee_growth_1;
+      reward_per_liquidity_0 = fee_growth_add(
+        reward_per_liquidity_0,
+        fee_growth_sub(new_infos[i].last_fee_growth_0, token.last_updated_fee_infos[i].last_fee_growth_0)
+      );
+      reward_per_liquidity_1 = fee_growth_add(
+        reward_per_liquidity_1,
+        fee_growth_sub(new_infos[i].last_fee_growth_1, token.last_updated_fee_infos[i].last_fee_growth_1)
+      );
     }
 
     let rewards = [
       Asset{
         info: asset_infos[0].clone(),
-        amount: reward_per_liquidity_0 * token.liquidity
+        amount: compute_reward(reward_per_liquidity_0, token.liquidity)?
       },
       Asset{
         info: asset_infos[1].clone(),
-        amount: reward_per_liquidity_1 * token.liquidity
+        amount: compute_reward(reward_per_liquidity_1, token.liquidity)?
       },
     ];
     
@@ -176,6 +191,7 @@ impl<'a> LpContract<'a> {
         limit
       } => to_binary(&self.all_tokens(deps, start_after, limit)?),
       QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
+      QueryMsg::NumTokens {} => to_binary(&self.num_tokens(deps)?),
       QueryMsg::Reward { token_id } => to_binary(&self.reward(deps, token_id)?),
     }
   }
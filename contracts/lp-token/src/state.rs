@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, StdResult, Storage, Decimal, Uint128};
+use cosmwasm_std::{Addr, StdResult, Storage, Decimal, Uint128, Uint256};
 
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
 
@@ -56,6 +56,15 @@ impl<'a> LpContract<'a> {
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct FeeInfo {
+  pub tick_index: i32,
+  // Q128.128
+  pub last_fee_growth_0: Uint256,
+  pub last_fee_growth_1: Uint256,
+}
+
+/// fee growth format before Q128.128, only used for migration
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct LegacyFeeInfo {
   pub tick_index: i32,
   pub last_fee_growth_0: Decimal,
   pub last_fee_growth_1: Decimal,
@@ -71,6 +80,16 @@ pub struct LiquidityInfo {
   pub approvals: Vec<Approval>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyLiquidityInfo {
+  pub owner: Addr,
+  pub liquidity: Uint128,
+  pub upper_tick_index: i32,
+  pub lower_tick_index: i32,
+  pub last_updated_fee_infos: Vec<LegacyFeeInfo>,
+  pub approvals: Vec<Approval>,
+}
+
 pub struct LiquidityIndexes<'a> {
   pub owner: MultiIndex<'a, (Addr, Vec<u8>), LiquidityInfo>,
 }
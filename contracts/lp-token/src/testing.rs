@@ -1,13 +1,17 @@
-use cosmwasm_std::{from_binary, to_binary, Addr, Decimal, Uint128, SubMsg, StdError, CosmosMsg, WasmMsg, Timestamp};
+use cosmwasm_std::{attr, from_binary, to_binary, Addr, Decimal, Uint128, Uint256, SubMsg, StdError, CosmosMsg, WasmMsg, Timestamp};
 use cosmwasm_std::testing::{mock_env, mock_info};
 
-use wineswap::lp_token::{InstantiateMsg, QueryMsg, ExecuteMsg, ConfigResponse, Approval, LpReceiveMsg};
+use wineswap::lp_token::{InstantiateMsg, MigrateMsg, QueryMsg, ExecuteMsg, ConfigResponse, Approval, LpReceiveMsg,
+  NumTokensResponse, TokensResponse};
 use wineswap::pair::{TickInfo, ExecuteMsg as PairExecuteMsg};
 use wineswap::asset::{AssetInfo, Asset};
+use wineswap::ownership::OwnershipProposal;
 use cw0::Expiration;
+use cw_storage_plus::Map;
+use wineswap_math::tick::DENOMINATOR;
 
 
-use crate::state::{LpContract, LiquidityInfo, FeeInfo};
+use crate::state::{LpContract, LiquidityInfo, FeeInfo, LegacyLiquidityInfo, LegacyFeeInfo};
 use crate::mock_querier::mock_dependencies;
 use crate::error::ContractError;
 
@@ -53,53 +57,53 @@ fn mint_burn_test() {
 
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -121,18 +125,23 @@ fn mint_burn_test() {
       upper_tick_index: 7,
       lower_tick_index: 2,
       last_updated_fee_infos: vec![
-        FeeInfo{ tick_index: 2, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
-        FeeInfo{ tick_index: 3, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
-        FeeInfo{ tick_index: 4, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
-        FeeInfo{ tick_index: 5, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
-        FeeInfo{ tick_index: 6, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
-        FeeInfo{ tick_index: 7, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::one() },
+        FeeInfo{ tick_index: 2, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
+        FeeInfo{ tick_index: 3, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
+        FeeInfo{ tick_index: 4, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
+        FeeInfo{ tick_index: 5, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
+        FeeInfo{ tick_index: 6, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
+        FeeInfo{ tick_index: 7, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: DENOMINATOR },
       ],
       approvals: vec![]
     },
     token
   );
 
+  // next token id
+  let query_res = lp_token.query(deps.as_ref(), QueryMsg::NumTokens {}).unwrap();
+  let num_tokens: NumTokensResponse = from_binary(&query_res).unwrap();
+  assert_eq!(num_tokens.count, 1);
+
   // try to mint who is not pair
   let info_not_pair = mock_info("not_pair", &[]);
   let res = lp_token.execute(deps.as_mut(), env.clone(), info_not_pair.clone(), mint_mgs);
@@ -160,6 +169,11 @@ fn mint_burn_test() {
     Err(StdError::NotFound { kind: _ }) => assert!(true),
     _ => panic!("Must return error"),
   }
+
+  // burned token id is not reused
+  let query_res = lp_token.query(deps.as_ref(), QueryMsg::NumTokens {}).unwrap();
+  let num_tokens: NumTokensResponse = from_binary(&query_res).unwrap();
+  assert_eq!(num_tokens.count, 1);
 }
 
 #[test]
@@ -181,53 +195,53 @@ fn transfer_send_test() {
 
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -310,53 +324,53 @@ fn approve_revoke_test() {
 
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -497,53 +511,53 @@ fn claim_reward_test() {
 
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -561,53 +575,53 @@ fn claim_reward_test() {
   // fee_growth changed
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::from_ratio(2u128, 1u128),
-      last_fee_growth_1: Decimal::from_ratio(2u128, 1u128),
+      last_fee_growth_0: DENOMINATOR * Uint256::from(2u128),
+      last_fee_growth_1: DENOMINATOR * Uint256::from(2u128),
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -670,12 +684,12 @@ fn claim_reward_test() {
       upper_tick_index: 7,
       lower_tick_index: 2,
       last_updated_fee_infos: vec![
-        FeeInfo{ tick_index: 2, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
-        FeeInfo{ tick_index: 3, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
-        FeeInfo{ tick_index: 4, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
-        FeeInfo{ tick_index: 5, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
-        FeeInfo{ tick_index: 6, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
-        FeeInfo{ tick_index: 7, last_fee_growth_0: Decimal::from_ratio(2u128, 1u128), last_fee_growth_1: Decimal::from_ratio(2u128, 1u128) },
+        FeeInfo{ tick_index: 2, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
+        FeeInfo{ tick_index: 3, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
+        FeeInfo{ tick_index: 4, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
+        FeeInfo{ tick_index: 5, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
+        FeeInfo{ tick_index: 6, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
+        FeeInfo{ tick_index: 7, last_fee_growth_0: DENOMINATOR * Uint256::from(2u128), last_fee_growth_1: DENOMINATOR * Uint256::from(2u128) },
       ],
       approvals: vec![]
     },
@@ -707,53 +721,53 @@ fn update_liquidity_test() {
 
   deps.querier.with_fee_infos(&[
     (&1, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&2, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&3, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&4, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&5, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&6, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&7, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&8, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&9, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
     (&10, &TickInfo {
-      last_fee_growth_0: Decimal::one(),
-      last_fee_growth_1: Decimal::one(),
+      last_fee_growth_0: DENOMINATOR,
+      last_fee_growth_1: DENOMINATOR,
       total_liquidity: Uint128::from(10u128),
     }),
   ]);
@@ -813,4 +827,273 @@ fn update_liquidity_test() {
   let token = lp_token.tokens.load(&deps.storage, "0").unwrap();
 
   assert_eq!(Uint128::from(10u128), token.liquidity)
-}
\ No newline at end of file
+}
+#[test]
+fn migrate_test() {
+  let lp_token = LpContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  // token saved before fee growth was Q128.128
+  let legacy_tokens: Map<&str, LegacyLiquidityInfo> = Map::new("tokens");
+  legacy_tokens.save(deps.as_mut().storage, "0", &LegacyLiquidityInfo {
+    owner: Addr::unchecked("owner"),
+    liquidity: Uint128::from(10u128),
+    upper_tick_index: 3,
+    lower_tick_index: 2,
+    last_updated_fee_infos: vec![
+      LegacyFeeInfo{ tick_index: 2, last_fee_growth_0: Decimal::one(), last_fee_growth_1: Decimal::zero() },
+      LegacyFeeInfo{ tick_index: 3, last_fee_growth_0: Decimal::from_ratio(3u128, 2u128), last_fee_growth_1: Decimal::one() },
+    ],
+    approvals: vec![]
+  }).unwrap();
+
+  let _res = lp_token.migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+  let token = lp_token.tokens.load(&deps.storage, "0").unwrap();
+  assert_eq!(
+    token.last_updated_fee_infos,
+    vec![
+      FeeInfo{ tick_index: 2, last_fee_growth_0: DENOMINATOR, last_fee_growth_1: Uint256::zero() },
+      FeeInfo{ tick_index: 3, last_fee_growth_0: DENOMINATOR * Uint256::from(3u128) / Uint256::from(2u128), last_fee_growth_1: DENOMINATOR },
+    ]
+  );
+
+  // owner index is kept
+  let query_res = lp_token.query(deps.as_ref(), QueryMsg::Tokens { owner: "owner".to_string(), start_after: None, limit: None }).unwrap();
+  let tokens_res: TokensResponse = from_binary(&query_res).unwrap();
+  assert_eq!(tokens_res.tokens, vec!["0".to_string()]);
+
+  // migrating a versioned contract keeps the data
+  let res = lp_token.migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+  assert_eq!(res.attributes, vec![attr("action", "migrate")]);
+  let query_res = lp_token.query(deps.as_ref(), QueryMsg::Tokens { owner: "owner".to_string(), start_after: None, limit: None }).unwrap();
+  let tokens_res: TokensResponse = from_binary(&query_res).unwrap();
+  assert_eq!(tokens_res.tokens, vec!["0".to_string()]);
+}
+
+#[test]
+fn split_merge_test() {
+  // instantiate
+  let lp_token = LpContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    name: "wine_lp".to_string(),
+    symbol: "WINELP".to_string(),
+    minter: "pair".to_string(),
+  };
+
+  let _res = lp_token.instantiate(deps.as_mut(), mock_env(), mock_info("pair", &[]), instantiate_msg).unwrap();
+
+  let fee_info = |growth: u128| TickInfo {
+    last_fee_growth_0: DENOMINATOR * Uint256::from(growth),
+    last_fee_growth_1: DENOMINATOR * Uint256::from(growth),
+    total_liquidity: Uint128::from(10u128),
+  };
+
+  deps.querier.with_fee_infos(&[(&1, &fee_info(1)), (&2, &fee_info(1))]);
+  deps.querier.with_pair_info(&[
+    (&"pair".to_string(), &[AssetInfo::Token {contract_addr: "wine".to_string()}, AssetInfo::NativeToken {denom: "uusd".to_string()}])
+  ]);
+
+  let mint_msg = ExecuteMsg::Mint {
+    liquidity: Uint128::from(10u128),
+    upper_tick_index: 2,
+    lower_tick_index: 1,
+    owner: "owner".to_string()
+  };
+  let _res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("pair", &[]), mint_msg).unwrap();
+
+  // split by who is not owner
+  let split_msg = ExecuteMsg::Split {
+    token_id: "0".to_string(),
+    liquidity: Uint128::from(4u128),
+  };
+
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("not_owner", &[]), split_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // split whole liquidity
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Split {
+    token_id: "0".to_string(),
+    liquidity: Uint128::from(10u128),
+  });
+
+  match res {
+    Err(ContractError::InvalidSplitAmount {}) => assert!(true),
+    _ => panic!("Must return invalid split amount error"),
+  }
+
+  let _res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), split_msg).unwrap();
+
+  let token = lp_token.tokens.load(&deps.storage, "0").unwrap();
+  let new_token = lp_token.tokens.load(&deps.storage, "1").unwrap();
+  assert_eq!(token.liquidity, Uint128::from(6u128));
+  assert_eq!(new_token.liquidity, Uint128::from(4u128));
+  assert_eq!(new_token.owner, Addr::unchecked("owner"));
+  // fee infos are carried forward
+  assert_eq!(new_token.last_updated_fee_infos, token.last_updated_fee_infos);
+
+  // fee_growth changed, mint another one
+  deps.querier.with_fee_infos(&[(&1, &fee_info(2)), (&2, &fee_info(2))]);
+
+  let mint_msg = ExecuteMsg::Mint {
+    liquidity: Uint128::from(5u128),
+    upper_tick_index: 2,
+    lower_tick_index: 1,
+    owner: "owner".to_string()
+  };
+  let _res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("pair", &[]), mint_msg).unwrap();
+
+  // different range
+  let mint_msg = ExecuteMsg::Mint {
+    liquidity: Uint128::from(5u128),
+    upper_tick_index: 1,
+    lower_tick_index: 1,
+    owner: "owner".to_string()
+  };
+  let _res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("pair", &[]), mint_msg).unwrap();
+
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Merge {
+    token_ids: vec!["0".to_string(), "3".to_string()],
+  });
+
+  match res {
+    Err(ContractError::MergeMismatch {}) => assert!(true),
+    _ => panic!("Must return merge mismatch error"),
+  }
+
+  // duplicated token id
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Merge {
+    token_ids: vec!["0".to_string(), "0".to_string()],
+  });
+
+  match res {
+    Err(ContractError::MergeMismatch {}) => assert!(true),
+    _ => panic!("Must return merge mismatch error"),
+  }
+
+  deps.querier.with_fee_infos(&[(&1, &fee_info(3)), (&2, &fee_info(3))]);
+
+  let merge_msg = ExecuteMsg::Merge {
+    token_ids: vec!["0".to_string(), "1".to_string(), "2".to_string()],
+  };
+
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("not_owner", &[]), merge_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = lp_token.execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), merge_msg).unwrap();
+
+  // token0: 6 * 2 ticks * 2, token1: 4 * 2 ticks * 2, token2: 5 * 2 ticks * 1
+  let rewards = [
+    Asset {
+      info: AssetInfo::Token {contract_addr: "wine".to_string()},
+      amount: Uint128::from(50u128)
+    },
+    Asset {
+      info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+      amount: Uint128::from(50u128)
+    }
+  ];
+
+  assert_eq!(
+    res.messages,
+    vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+      contract_addr: "pair".to_string(),
+      msg: to_binary(&PairExecuteMsg::ClaimReward {
+        token_id: "0".to_string(),
+        rewards,
+      }).unwrap(),
+      funds: vec![],
+    }))]
+  );
+
+  let token = lp_token.tokens.load(&deps.storage, "0").unwrap();
+  assert_eq!(token.liquidity, Uint128::from(15u128));
+  assert_eq!(
+    token.last_updated_fee_infos,
+    vec![
+      FeeInfo{ tick_index: 1, last_fee_growth_0: DENOMINATOR * Uint256::from(3u128), last_fee_growth_1: DENOMINATOR * Uint256::from(3u128) },
+      FeeInfo{ tick_index: 2, last_fee_growth_0: DENOMINATOR * Uint256::from(3u128), last_fee_growth_1: DENOMINATOR * Uint256::from(3u128) },
+    ]
+  );
+  assert_eq!(lp_token.tokens.may_load(&deps.storage, "1").unwrap(), None);
+  assert_eq!(lp_token.tokens.may_load(&deps.storage, "2").unwrap(), None);
+}
+
+#[test]
+fn minter_proposal_test() {
+  let lp_token = LpContract::default();
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+
+  let instantiate_msg = InstantiateMsg {
+    name: "wine_lp".to_string(),
+    symbol: "WINELP".to_string(),
+    minter: "pair".to_string(),
+  };
+
+  let _res = lp_token.instantiate(deps.as_mut(), env.clone(), mock_info("pair", &[]), instantiate_msg).unwrap();
+
+  let propose_msg = ExecuteMsg::ProposeNewMinter {
+    minter: "new_pair".to_string(),
+    expires: Expiration::AtHeight(env.block.height + 100),
+  };
+
+  // only minter
+  let res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), propose_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("new_pair", &[]), ExecuteMsg::ClaimMinter {});
+  match res {
+    Err(ContractError::MinterProposalNotFound {}) => assert!(true),
+    _ => panic!("Must return minter proposal not found error"),
+  }
+
+  let _res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("pair", &[]), propose_msg).unwrap();
+  let config: ConfigResponse = from_binary(&lp_token.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.minter, Addr::unchecked("pair"));
+  assert_eq!(config.minter_proposal, Some(OwnershipProposal {
+    owner: Addr::unchecked("new_pair"),
+    expires: Expiration::AtHeight(env.block.height + 100),
+  }));
+
+  let mut expired_env = env.clone();
+  expired_env.block.height += 100;
+  let res = lp_token.execute(deps.as_mut(), expired_env, mock_info("new_pair", &[]), ExecuteMsg::ClaimMinter {});
+  match res {
+    Err(ContractError::MinterProposalExpired {}) => assert!(true),
+    _ => panic!("Must return minter proposal expired error"),
+  }
+
+  let _res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("new_pair", &[]), ExecuteMsg::ClaimMinter {}).unwrap();
+  let config: ConfigResponse = from_binary(&lp_token.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(config.minter, Addr::unchecked("new_pair"));
+  assert_eq!(config.minter_proposal, None);
+
+  // drop
+  let propose_msg = ExecuteMsg::ProposeNewMinter {
+    minter: "pair".to_string(),
+    expires: Expiration::Never {},
+  };
+  let _res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("new_pair", &[]), propose_msg).unwrap();
+  let _res = lp_token.execute(deps.as_mut(), env.clone(), mock_info("new_pair", &[]), ExecuteMsg::DropMinterProposal {}).unwrap();
+  let res = lp_token.execute(deps.as_mut(), env, mock_info("pair", &[]), ExecuteMsg::ClaimMinter {});
+  match res {
+    Err(ContractError::MinterProposalNotFound {}) => assert!(true),
+    _ => panic!("Must return minter proposal not found error"),
+  }
+}
@@ -0,0 +1,20 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+  #[error("{0}")]
+  Std(#[from] StdError),
+
+  #[error("{0}")]
+  OverflowError(#[from] OverflowError),
+
+  #[error("Unauthorized")]
+  Unauthorized {},
+
+  #[error("Nothing to migrate")]
+  NothingToMigrate {},
+
+  #[error("Pair not found")]
+  PairNotFound {},
+}
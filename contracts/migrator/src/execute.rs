@@ -0,0 +1,297 @@
+use cosmwasm_std::{to_binary, from_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest,
+  Response, StdResult, Uint128, WasmMsg, WasmQuery};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+use wineswap::asset::Asset;
+use wineswap::factory::{PairInfoWithType, QueryMsg as FactoryQueryMsg};
+use wineswap::lp_token::{ExecuteMsg as LpExecuteMsg, QueryMsg as LpQueryMsg, NumTokensResponse};
+use wineswap::migrator::{classic, Config, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg};
+use wineswap::pair::{ExecuteMsg as PairExecuteMsg, TickIndexes};
+
+use crate::state::{MigratorContract, TmpMigration};
+use crate::error::ContractError;
+
+/// `Migrate` of the cw20 hook, with the validated addresses
+pub struct MigrateParams {
+  pub old_pair: Addr,
+  pub pair_type: String,
+  pub tick_indexes: TickIndexes,
+  pub receiver: Addr,
+}
+
+impl<'a> MigratorContract<'a> {
+  pub fn instantiate(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg
+  ) -> StdResult<Response> {
+    let config = Config {
+      factory: deps.api.addr_validate(&msg.factory)?,
+    };
+
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new())
+  }
+
+  pub fn migrate(&self, _deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::new())
+  }
+
+  pub fn execute(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg
+  ) -> Result<Response, ContractError> {
+    match msg {
+      ExecuteMsg::Receive(msg) => self.receive_cw20(deps, env, info, msg),
+      ExecuteMsg::ProvideLiquidity {} => self.provide_liquidity(deps.as_ref(), env, info),
+      ExecuteMsg::Refund {} => self.refund(deps, env, info),
+    }
+  }
+}
+
+/// execute function
+impl<'a> MigratorContract<'a> {
+  pub fn receive_cw20(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+  ) -> Result<Response, ContractError> {
+    match from_binary(&cw20_msg.msg) {
+      Ok(Cw20HookMsg::Migrate {
+        old_pair,
+        pair_type,
+        tick_indexes,
+        receiver,
+      }) => {
+        let receiver = if let Some(receiver) = receiver {
+          deps.api.addr_validate(&receiver)?
+        } else {
+          deps.api.addr_validate(&cw20_msg.sender)?
+        };
+        let old_pair = deps.api.addr_validate(&old_pair)?;
+
+        self.migrate_liquidity(deps, env, info.sender, cw20_msg.amount, MigrateParams {
+          old_pair,
+          pair_type,
+          tick_indexes,
+          receiver,
+        })
+      },
+      Err(err) => Err(ContractError::Std(err)),
+    }
+  }
+
+  /// withdraw from the old pair, then provide and refund with self calls
+  pub fn migrate_liquidity(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    old_liquidity_token: Addr,
+    amount: Uint128,
+    params: MigrateParams,
+  ) -> Result<Response, ContractError> {
+    let MigrateParams { old_pair, pair_type, tick_indexes, receiver } = params;
+
+    if amount.is_zero() {
+      return Err(ContractError::NothingToMigrate {})
+    }
+
+    let old_pair_info: classic::PairInfo = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: old_pair.to_string(),
+      msg: to_binary(&classic::QueryMsg::Pair {})?,
+    }))?;
+
+    // only the lp token of the old pair
+    if old_pair_info.liquidity_token != old_liquidity_token.as_str() {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let config = self.config.load(deps.storage)?;
+    // the factory query fails when the pair doesn't exist
+    let pairs: StdResult<Vec<PairInfoWithType>> = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: config.factory.to_string(),
+      msg: to_binary(&FactoryQueryMsg::Pair {
+        asset_infos: old_pair_info.asset_infos,
+        pair_type: Some(pair_type.clone()),
+      })?,
+    }));
+    let pair_info = pairs.ok()
+      .and_then(|pairs| pairs.into_iter().next())
+      .ok_or(ContractError::PairNotFound {})?;
+
+    // the provide mints the next token id
+    let num_tokens: NumTokensResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: pair_info.liquidity_token.to_string(),
+      msg: to_binary(&LpQueryMsg::NumTokens {})?,
+    }))?;
+
+    let prev_balances = [
+      pair_info.asset_infos[0].query_balance(&deps.querier, &env.contract.address)?,
+      pair_info.asset_infos[1].query_balance(&deps.querier, &env.contract.address)?,
+    ];
+
+    self.temp_migration.save(deps.storage, &TmpMigration {
+      receiver: receiver.clone(),
+      pair: pair_info.contract_addr.clone(),
+      liquidity_token: pair_info.liquidity_token,
+      tick_indexes,
+      asset_infos: pair_info.asset_infos,
+      prev_balances,
+      token_id: num_tokens.count.to_string(),
+    })?;
+
+    let messages: Vec<CosmosMsg> = vec![
+      // withdrawn assets are sent to this contract
+      WasmMsg::Execute {
+        contract_addr: old_liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+          contract: old_pair.to_string(),
+          amount,
+          msg: to_binary(&classic::Cw20HookMsg::WithdrawLiquidity {})?,
+        })?,
+        funds: vec![],
+      }.into(),
+      WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::ProvideLiquidity {})?,
+        funds: vec![],
+      }.into(),
+      WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::Refund {})?,
+        funds: vec![],
+      }.into(),
+    ];
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "migrate")
+      .add_attribute("receiver", receiver.to_string())
+      .add_attribute("old_pair", old_pair.to_string())
+      .add_attribute("pair", pair_info.contract_addr.to_string())
+      .add_attribute("pair_type", pair_type)
+      .add_attribute("amount", amount.to_string())
+    )
+  }
+
+  pub fn provide_liquidity(
+    &self,
+    deps: Deps,
+    env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let migration = self.temp_migration.load(deps.storage)?;
+
+    let mut assets: Vec<Asset> = vec![];
+    for (asset_info, prev_balance) in migration.asset_infos.iter().zip(migration.prev_balances.iter()) {
+      let balance = asset_info.query_balance(&deps.querier, &env.contract.address)?;
+      assets.push(Asset {
+        info: asset_info.clone(),
+        amount: balance.checked_sub(*prev_balance)?,
+      });
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    for asset in assets.iter_mut() {
+      if asset.amount.is_zero() {
+        continue;
+      }
+
+      if asset.is_native_token() {
+        // sending to the pair is taxed
+        let coin = asset.deduct_tax(&deps.querier)?;
+        asset.amount = coin.amount;
+        funds.push(coin);
+      } else {
+        messages.push(WasmMsg::Execute {
+          contract_addr: asset.info.to_string(),
+          msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+            spender: migration.pair.to_string(),
+            amount: asset.amount,
+            expires: None,
+          })?,
+          funds: vec![],
+        }.into());
+      }
+    }
+
+    if assets.iter().all(|asset| asset.amount.is_zero()) {
+      return Err(ContractError::NothingToMigrate {})
+    }
+
+    messages.push(WasmMsg::Execute {
+      contract_addr: migration.pair.to_string(),
+      msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+        assets: [assets[0].clone(), assets[1].clone()],
+        token_id: None,
+        tick_indexes: Some(migration.tick_indexes),
+      })?,
+      funds,
+    }.into());
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "provide_liquidity")
+      .add_attribute("pair", migration.pair.to_string())
+      .add_attribute("provide_assets", format!("{}, {}", assets[0], assets[1]))
+    )
+  }
+
+  pub fn refund(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let migration = self.temp_migration.load(deps.storage)?;
+    self.temp_migration.remove(deps.storage);
+
+    // new position is minted to this contract, other positions it holds are not of this migration
+    let mut messages: Vec<CosmosMsg> = vec![
+      WasmMsg::Execute {
+        contract_addr: migration.liquidity_token.to_string(),
+        msg: to_binary(&LpExecuteMsg::Transfer {
+          recipient: migration.receiver.to_string(),
+          token_id: migration.token_id.clone(),
+        })?,
+        funds: vec![],
+      }.into(),
+    ];
+
+    // unused assets are refunded from the pair
+    let mut refund_assets: Vec<Asset> = vec![];
+    for (asset_info, prev_balance) in migration.asset_infos.iter().zip(migration.prev_balances.iter()) {
+      let balance = asset_info.query_balance(&deps.querier, &env.contract.address)?;
+      let refund_asset = Asset {
+        info: asset_info.clone(),
+        amount: balance.checked_sub(*prev_balance)?,
+      };
+      if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.clone().into_msg(&deps.querier, migration.receiver.clone())?);
+      }
+      refund_assets.push(refund_asset);
+    }
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "refund")
+      .add_attribute("receiver", migration.receiver.to_string())
+      .add_attribute("token_id", migration.token_id)
+      .add_attribute("refund_assets", format!("{}, {}", refund_assets[0], refund_assets[1]))
+    )
+  }
+}
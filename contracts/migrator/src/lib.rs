@@ -0,0 +1,56 @@
+mod error;
+mod execute;
+mod query;
+mod state;
+
+pub use crate::error::ContractError;
+pub use wineswap::migrator::{InstantiateMsg, ExecuteMsg, MigrateMsg, QueryMsg};
+pub use crate::state::MigratorContract;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(test)]
+mod mock_querier;
+
+#[cfg(not(feature = "library"))]
+pub mod entry {
+  use super::*;
+
+  use cosmwasm_std::entry_point;
+  use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+
+  #[entry_point]
+  pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+  ) -> StdResult<Response> {
+    let tract = MigratorContract::default();
+    tract.instantiate(deps, env, info, msg)
+  }
+
+  #[entry_point]
+  pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let tract = MigratorContract::default();
+    tract.migrate(deps, env, msg)
+  }
+
+  #[entry_point]
+  pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+  ) -> Result<Response, ContractError> {
+    let tract = MigratorContract::default();
+    tract.execute(deps, env, info, msg)
+  }
+
+  #[entry_point]
+  pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let tract = MigratorContract::default();
+    tract.query(deps, msg)
+  }
+}
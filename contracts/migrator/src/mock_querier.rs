@@ -0,0 +1,213 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery, Uint128
+};
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+use std::collections::HashMap;
+
+use cw20::BalanceResponse;
+use wineswap::asset::AssetInfo;
+use wineswap::factory::PairInfoWithType;
+use wineswap::lp_token::NumTokensResponse;
+use wineswap::migrator::classic;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    // old pair's Pair {} and factory's Pair { asset_infos, pair_type }
+    Pair {
+        asset_infos: Option<[AssetInfo; 2]>,
+        pair_type: Option<String>,
+    },
+    Balance { address: String },
+    NumTokens {},
+}
+
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<TerraQueryWrapper>,
+    tax_querier: TaxQuerier,
+    // old pair address => pair info
+    old_pairs: HashMap<String, classic::PairInfo>,
+    // pair type => pair info, any asset infos of the factory
+    pairs: HashMap<String, PairInfoWithType>,
+    // (token contract, address) => balance, zero if not set
+    token_balances: HashMap<(String, String), Uint128>,
+    // lp token contract => minted token count, zero if not set
+    num_tokens: HashMap<String, u64>,
+}
+
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    // this lets us iterate over all pairs that match the first string
+    caps: HashMap<String, Uint128>,
+}
+
+impl TaxQuerier {
+    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
+        TaxQuerier {
+            rate,
+            caps: caps_to_map(caps),
+        }
+    }
+}
+
+pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
+    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
+    for (denom, cap) in caps.iter() {
+        owner_map.insert(denom.to_string(), **cap);
+    }
+    owner_map
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Treasury {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg) {
+                    Ok(QueryMsg::Pair { pair_type: None, .. }) => {
+                        match self.old_pairs.get(contract_addr) {
+                            Some(pair_info) => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(pair_info).unwrap(),
+                            )),
+                            None => SystemResult::Ok(ContractResult::Err("Pair not found".to_string())),
+                        }
+                    }
+                    Ok(QueryMsg::Pair { pair_type: Some(pair_type), .. }) => {
+                        match self.pairs.get(&pair_type) {
+                            Some(pair_info) => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&vec![pair_info.clone()]).unwrap(),
+                            )),
+                            None => SystemResult::Ok(ContractResult::Err("PairInfo not found".to_string())),
+                        }
+                    }
+                    Ok(QueryMsg::Balance { address }) => {
+                        let balance = self
+                            .token_balances
+                            .get(&(contract_addr.to_string(), address))
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&BalanceResponse { balance }).unwrap(),
+                        ))
+                    }
+                    Ok(QueryMsg::NumTokens {}) => {
+                        let count = self
+                            .num_tokens
+                            .get(contract_addr)
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&NumTokensResponse { count }).unwrap(),
+                        ))
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            tax_querier: TaxQuerier::default(),
+            old_pairs: HashMap::new(),
+            pairs: HashMap::new(),
+            token_balances: HashMap::new(),
+            num_tokens: HashMap::new(),
+        }
+    }
+
+    // the constant product pair to migrate from
+    pub fn with_old_pair(&mut self, contract_addr: &str, pair_info: &classic::PairInfo) {
+        self.old_pairs.insert(contract_addr.to_string(), pair_info.clone());
+    }
+
+    // factory's pair of the pair type
+    pub fn with_pair(&mut self, pair_info: &PairInfoWithType) {
+        self.pairs.insert(pair_info.pair_type.clone(), pair_info.clone());
+    }
+
+    pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
+        for (addr, balance) in balances {
+            self.base.update_balance(addr.to_string(), balance.clone());
+        }
+    }
+
+    pub fn with_token_balance(&mut self, contract_addr: &str, address: &str, balance: Uint128) {
+        self.token_balances.insert((contract_addr.to_string(), address.to_string()), balance);
+    }
+
+    pub fn with_num_tokens(&mut self, contract_addr: &str, count: u64) {
+        self.num_tokens.insert(contract_addr.to_string(), count);
+    }
+
+    // configure the token owner mock querier
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        self.tax_querier = TaxQuerier::new(rate, caps);
+    }
+}
@@ -0,0 +1,18 @@
+use cosmwasm_std::{to_binary, Binary, Deps, StdResult};
+use wineswap::migrator::{Config, QueryMsg};
+
+use crate::state::MigratorContract;
+
+impl<'a> MigratorContract<'a> {
+  fn config(&self, deps: Deps) -> StdResult<Config> {
+    self.config.load(deps.storage)
+  }
+}
+
+impl<'a> MigratorContract<'a> {
+  pub fn query(&self, deps: Deps, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+      QueryMsg::Config {} => to_binary(&self.config(deps)?),
+    }
+  }
+}
@@ -0,0 +1,50 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::Item;
+use wineswap::asset::AssetInfo;
+use wineswap::migrator::Config;
+use wineswap::pair::TickIndexes;
+
+
+pub struct MigratorContract<'a> {
+  pub config: Item<'a, Config>,
+  pub temp_migration: Item<'a, TmpMigration>,
+}
+
+/// migration in progress, removed at the end of the transaction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TmpMigration {
+  pub receiver: Addr,
+  pub pair: Addr,
+  pub liquidity_token: Addr,
+  pub tick_indexes: TickIndexes,
+  /// same order with the pair
+  pub asset_infos: [AssetInfo; 2],
+  /// migrator's balances before the migration
+  pub prev_balances: [Uint128; 2],
+  /// id of the position minted by the provide
+  pub token_id: String,
+}
+
+impl Default for MigratorContract<'static> {
+  fn default() -> Self {
+    Self::new(
+      "config",
+      "temp_migration",
+    )
+  }
+}
+
+impl<'a> MigratorContract<'a> {
+  fn new(
+    config_key: &'a str,
+    temp_migration_key: &'a str,
+  ) -> Self {
+    Self {
+      config: Item::new(config_key),
+      temp_migration: Item::new(temp_migration_key),
+    }
+  }
+}
@@ -0,0 +1,222 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{from_binary, to_binary, Addr, Coin, Decimal, Uint128, WasmMsg, SubMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+use wineswap::asset::{Asset, AssetInfo};
+use wineswap::factory::PairInfoWithType;
+use wineswap::lp_token::ExecuteMsg as LpExecuteMsg;
+use wineswap::migrator::{classic, Config, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use wineswap::pair::{ExecuteMsg as PairExecuteMsg, TickIndexes};
+use crate::state::{MigratorContract, TmpMigration};
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+
+#[test]
+fn migrate_test() {
+  let migrator = MigratorContract::default();
+  let mut deps = mock_dependencies(&[Coin { denom: "uusd".to_string(), amount: Uint128::from(100u128) }]);
+
+  let instantiate_msg = InstantiateMsg {
+    factory: "factory".to_string(),
+  };
+  let _res = migrator.instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_msg).unwrap();
+
+  let query_res = migrator.query(deps.as_ref(), QueryMsg::Config {}).unwrap();
+  let config: Config = from_binary(&query_res).unwrap();
+  assert_eq!(config.factory, Addr::unchecked("factory"));
+
+  // old pair has the assets in the other order
+  deps.querier.with_old_pair("old_pair", &classic::PairInfo {
+    asset_infos: [
+      AssetInfo::NativeToken { denom: "uusd".to_string() },
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+    ],
+    contract_addr: "old_pair".to_string(),
+    liquidity_token: "old_lp".to_string(),
+  });
+  deps.querier.with_pair(&PairInfoWithType {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() },
+    ],
+    contract_addr: Addr::unchecked("pair"),
+    liquidity_token: Addr::unchecked("liquidity"),
+    pair_type: "0.3".to_string(),
+    asset_decimals: Some([6, 6]),
+    creator: None,
+  });
+  deps.querier.with_tax(
+    Decimal::percent(1),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+  deps.querier.with_num_tokens("liquidity", 7);
+
+  let tick_indexes = TickIndexes { upper_tick_index: 10, lower_tick_index: -10 };
+  let migrate_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+    sender: "user".to_string(),
+    amount: Uint128::from(1000u128),
+    msg: to_binary(&Cw20HookMsg::Migrate {
+      old_pair: "old_pair".to_string(),
+      pair_type: "0.3".to_string(),
+      tick_indexes: tick_indexes.clone(),
+      receiver: None,
+    }).unwrap(),
+  });
+
+  // not the lp token of the old pair
+  let res = migrator.execute(deps.as_mut(), mock_env(), mock_info("fake_lp", &[]), migrate_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // no pair of the pair type
+  let res = migrator.execute(deps.as_mut(), mock_env(), mock_info("old_lp", &[]), ExecuteMsg::Receive(Cw20ReceiveMsg {
+    sender: "user".to_string(),
+    amount: Uint128::from(1000u128),
+    msg: to_binary(&Cw20HookMsg::Migrate {
+      old_pair: "old_pair".to_string(),
+      pair_type: "1".to_string(),
+      tick_indexes: tick_indexes.clone(),
+      receiver: None,
+    }).unwrap(),
+  }));
+
+  match res {
+    Err(ContractError::PairNotFound {}) => assert!(true),
+    _ => panic!("Must return pair not found error"),
+  }
+
+  let res = migrator.execute(deps.as_mut(), mock_env(), mock_info("old_lp", &[]), migrate_msg).unwrap();
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: "old_lp".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+          contract: "old_pair".to_string(),
+          amount: Uint128::from(1000u128),
+          msg: to_binary(&classic::Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        }).unwrap(),
+        funds: vec![],
+      }),
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        msg: to_binary(&ExecuteMsg::ProvideLiquidity {}).unwrap(),
+        funds: vec![],
+      }),
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        msg: to_binary(&ExecuteMsg::Refund {}).unwrap(),
+        funds: vec![],
+      }),
+    ]
+  );
+  assert_eq!(
+    migrator.temp_migration.load(&deps.storage).unwrap(),
+    TmpMigration {
+      receiver: Addr::unchecked("user"),
+      pair: Addr::unchecked("pair"),
+      liquidity_token: Addr::unchecked("liquidity"),
+      tick_indexes: tick_indexes.clone(),
+      asset_infos: [
+        AssetInfo::Token { contract_addr: "wine".to_string() },
+        AssetInfo::NativeToken { denom: "uusd".to_string() },
+      ],
+      prev_balances: [Uint128::zero(), Uint128::from(100u128)],
+      token_id: "7".to_string(),
+    }
+  );
+
+  // the old pair sent the withdrawn assets
+  deps.querier.with_balance(&[(
+    &MOCK_CONTRACT_ADDR.to_string(),
+    vec![Coin { denom: "uusd".to_string(), amount: Uint128::from(1000100u128) }],
+  )]);
+  deps.querier.with_token_balance("wine", MOCK_CONTRACT_ADDR, Uint128::from(2000000u128));
+
+  // only self call
+  let res = migrator.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), ExecuteMsg::ProvideLiquidity {});
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+  let res = migrator.execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ProvideLiquidity {}).unwrap();
+
+  // sending uusd to the pair is taxed
+  let uusd = Asset {
+    info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+    amount: Uint128::from(1000000u128),
+  }.deduct_tax(&deps.as_ref().querier).unwrap();
+  #[cfg(feature = "terra")]
+  assert!(uusd.amount < Uint128::from(1000000u128));
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: "wine".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+          spender: "pair".to_string(),
+          amount: Uint128::from(2000000u128),
+          expires: None,
+        }).unwrap(),
+        funds: vec![],
+      }),
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: "pair".to_string(),
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+          assets: [
+            Asset {
+              info: AssetInfo::Token { contract_addr: "wine".to_string() },
+              amount: Uint128::from(2000000u128),
+            },
+            Asset {
+              info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+              amount: uusd.amount,
+            },
+          ],
+          token_id: None,
+          tick_indexes: Some(tick_indexes),
+        }).unwrap(),
+        funds: vec![uusd],
+      }),
+    ]
+  );
+
+  // the pair minted the position and refunded unused uusd
+  deps.querier.with_balance(&[(
+    &MOCK_CONTRACT_ADDR.to_string(),
+    vec![Coin { denom: "uusd".to_string(), amount: Uint128::from(5100u128) }],
+  )]);
+  deps.querier.with_token_balance("wine", MOCK_CONTRACT_ADDR, Uint128::zero());
+  // token 7 is minted, other positions of the migrator are not sent
+  deps.querier.with_num_tokens("liquidity", 8);
+
+  let res = migrator.execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Refund {}).unwrap();
+
+  let refund_uusd = Asset {
+    info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+    amount: Uint128::from(5000u128),
+  };
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&LpExecuteMsg::Transfer {
+          recipient: "user".to_string(),
+          token_id: "7".to_string(),
+        }).unwrap(),
+        funds: vec![],
+      }),
+      SubMsg::new(refund_uusd.into_msg(&deps.as_ref().querier, Addr::unchecked("user")).unwrap()),
+    ]
+  );
+  assert_eq!(migrator.temp_migration.may_load(&deps.storage).unwrap(), None);
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::{StdError, OverflowError};
+use cosmwasm_std::{StdError, OverflowError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -35,4 +35,25 @@ pub enum ContractError {
 
   #[error("You must put token_id or tick_indexes")]
   ProvideOptionError {},
-}
\ No newline at end of file
+
+  #[error("Min liquidity assertion, min: {min_liquidity}, liquidity: {liquidity}")]
+  MinLiquidityAssertion { min_liquidity: Uint128, liquidity: Uint128 },
+
+  #[error("Minimum receive assertion, minimum: {minimum_receive}, receive: {receive}")]
+  MinimumReceiveAssertion { minimum_receive: Uint128, receive: Uint128 },
+
+  #[error("Max ticks reached, max_ticks: {max_ticks}, filled offer: {offer_amount}, return: {return_amount}")]
+  MaxTicksReached { max_ticks: u32, offer_amount: Uint128, return_amount: Uint128 },
+
+  #[error("Max swap ticks must be greater than 0")]
+  InvalidMaxSwapTicks {},
+
+  #[error("History sizes must be greater than 0")]
+  InvalidHistorySize {},
+
+  #[error("Referral needs the factory, migrate the pair with the factory")]
+  ReferralNotSupported {},
+
+  #[error("Minter proposal must be queued in the factory timelock")]
+  Timelocked {},
+}
@@ -1,27 +1,83 @@
 
-use cosmwasm_std::{to_binary, from_binary, Addr, CosmosMsg, Decimal, Decimal256, DepsMut, Env, MessageInfo,
-  Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, Uint256, WasmMsg};
+use cosmwasm_std::{to_binary, from_binary, Addr, CosmosMsg, Decimal, Decimal256, Deps, DepsMut, Env, MessageInfo, Order,
+  Event, QuerierWrapper, QueryRequest, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128, Uint256,
+  WasmMsg, WasmQuery};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Bound, Map, U64Key};
+use cw0::Expiration;
 use wineswap::lp_token::{InstantiateMsg as TokenInstantiateMsg, ExecuteMsg as TokenExecuteMsg};
 use wineswap::new_int_key::NewInt32Key;
 use protobuf::Message;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use wineswap::{
-  pair::{InstantiateMsg, ExecuteMsg, TickInfo, TickIndexes, Cw20HookMsg},
+  pair::{InstantiateMsg, ExecuteMsg, MigrateMsg, TickInfo, TickIndexes, Cw20HookMsg, HistoryConfig, HistoryInfo, HistoryPeriod, Referral},
+  factory::{Config as FactoryConfig, QueryMsg as FactoryQueryMsg},
   asset::{Asset, AssetInfo, TokenNumber},
+  event::{TickLiquidityEvent, TickSwapEvent},
+  tax::ChainTax,
 };
 use wineswap_math::{
   tick::{get_tick_from_price_sqrt, get_tick_price_sqrt, tick_to_tick_index, tick_index_to_tick, DENOMINATOR, MAX_TICK, MIN_TICK},
-  liquidity::{compute_liquidity, get_token_amount_from_liquidity},
-  swap::{compute_swap_tick}
+  liquidity::{compute_liquidity, compute_liquidity_of_tokens, get_token_amount_from_liquidity},
+  swap::{compute_swap_tick},
+  price::price_sqrt_to_price,
+  fee::{compute_fee_growth, decimal_to_fee_growth, discount_fee_rate, fee_growth_add},
 };
 
 use crate::response::MsgInstantiateContractResponse;
 use crate::error::ContractError;
-use crate::state::{Config, PairContract};
+use crate::state::{Config, LegacyTickInfo, PairContract, DEFAULT_MAX_SWAP_TICKS};
 
+const CONTRACT_NAME: &str = "crates.io:wine-pair";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const TICK_RANGE_LIMIT: i32 = 500;
+// every step of the zap in search walks the ticks of the swap, so the steps are capped
+const ZAP_SEARCH_ITERATIONS: u32 = 24;
+// the search stops when the swap amount is known within 1 / ZAP_SEARCH_TOLERANCE of the offer
+const ZAP_SEARCH_TOLERANCE: u128 = 1_000_000;
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
+/// `ProvideLiquidity` of the execute msg and the cw20 hook, with the sent cw20 as one of the assets
+pub struct ProvideParams {
+  pub assets: [Asset; 2],
+  pub token_id: Option<String>,
+  pub tick_indexes: Option<TickIndexes>,
+}
+
+/// `WithdrawLiquidity` of the execute msg
+pub struct WithdrawParams {
+  pub token_id: String,
+  pub amount: Option<Uint128>,
+  pub receive_asset: Option<AssetInfo>,
+  pub minimum_receive: Option<Uint128>,
+}
+
+/// `Swap` of the execute msg and the cw20 hook, with the offer asset and the validated receiver
+pub struct SwapParams {
+  pub offer_asset: Asset,
+  pub to: Option<Addr>,
+  pub belief_price: Option<Decimal>,
+  pub max_slippage: Option<Decimal>,
+  pub max_ticks: Option<u32>,
+  pub partial_fill: bool,
+  pub referral: Option<Referral>,
+}
+
+/// `ZapOut` of the execute msg, the swap of the zap out withdraw
+pub struct ZapOutParams {
+  pub owner: String,
+  pub offer_asset: Asset,
+  pub receive_amount: Uint128,
+  pub minimum_receive: Option<Uint128>,
+}
+
+/// `ZapIn` of the execute msg and the cw20 hook, with the offer asset
+pub struct ZapInParams {
+  pub offer_asset: Asset,
+  pub tick_indexes: TickIndexes,
+  pub min_liquidity: Option<Uint128>,
+}
+
 impl<'a> PairContract<'a> {
   pub fn instantiate(
     &self,
@@ -30,6 +86,8 @@ impl<'a> PairContract<'a> {
     info: MessageInfo,
     msg: InstantiateMsg,
   ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     if msg.tick_space == 0u16 {
       return Err(StdError::generic_err("Invalid tick space"));
     }
@@ -37,6 +95,16 @@ impl<'a> PairContract<'a> {
     if msg.fee_rate >= Decimal::one() {
       return Err(StdError::generic_err("Invalid fee rate"));
     }
+
+    let history_config = msg.history_config.unwrap_or_default();
+    if history_config.hourly_size == 0 || history_config.daily_size == 0 {
+      return Err(StdError::generic_err("Invalid history size"));
+    }
+
+    let max_swap_ticks = msg.max_swap_ticks.unwrap_or(DEFAULT_MAX_SWAP_TICKS);
+    if max_swap_ticks == 0 {
+      return Err(StdError::generic_err("Invalid max swap ticks"));
+    }
     
     // save config
     let config = Config{
@@ -44,10 +112,13 @@ impl<'a> PairContract<'a> {
       tick_space: msg.tick_space,
       fee_rate: msg.fee_rate,
       // temp addr
-      liquidity_token: info.sender,
+      liquidity_token: info.sender.clone(),
+      asset_decimals: msg.asset_decimals,
     };
   
     self.config.save(deps.storage, &config)?;
+    self.factory.save(deps.storage, &info.sender)?;
+    self.max_swap_ticks.save(deps.storage, &max_swap_ticks)?;
 
     let price_sqrt = msg.initial_price.clone().sqrt();
 
@@ -72,10 +143,12 @@ impl<'a> PairContract<'a> {
 
     // set initial data
     self.cumulative_volume.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    self.history_config.save(deps.storage, &history_config)?;
 
     Ok(Response::new().add_submessage(SubMsg {
       msg: WasmMsg::Instantiate {
-        admin: None,
+        // the factory migrates the lp token with `MigrateContract`
+        admin: Some(info.sender.to_string()),
         code_id: msg.token_code_id,
         label: "".to_string(),
         funds: vec![],
@@ -109,6 +182,41 @@ impl<'a> PairContract<'a> {
     Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
   }
 
+  pub fn migrate(&self, deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // pairs instantiated before the max swap ticks don't have the factory
+    if let Some(factory) = msg.factory {
+      self.factory.save(deps.storage, &deps.api.addr_validate(&factory)?)?;
+    }
+
+    // contracts without version store fee growth as Decimal
+    if get_contract_version(deps.storage).is_ok() {
+      // fee growth is already Q128.128, only bump the version
+      set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+      return Ok(Response::new().add_attribute("action", "migrate"));
+    }
+
+    // read tick data as legacy format from the same namespace
+    let legacy_tick_data: Map<NewInt32Key, LegacyTickInfo> = Map::new("tick_data");
+    let legacy_ticks = legacy_tick_data
+      .range(deps.storage, None, None, Order::Ascending)
+      .collect::<StdResult<Vec<_>>>()?;
+
+    for (k, v) in legacy_ticks.iter() {
+      self.tick_data.save(deps.storage, NewInt32Key::from(k.clone()), &TickInfo {
+        last_fee_growth_0: decimal_to_fee_growth(v.last_fee_growth_0),
+        last_fee_growth_1: decimal_to_fee_growth(v.last_fee_growth_1),
+        total_liquidity: v.total_liquidity,
+      })?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+      .add_attribute("action", "migrate")
+      .add_attribute("migrated_ticks", legacy_ticks.len().to_string())
+    )
+  }
+
   pub fn execute(
     &self,
     deps: DepsMut,
@@ -122,16 +230,24 @@ impl<'a> PairContract<'a> {
         assets,
         token_id,
         tick_indexes
-      } => self.provide(deps, env, info, assets, token_id, tick_indexes),
+      } => {
+        let sender = info.sender.clone();
+        self.provide(deps, env, info, sender, ProvideParams { assets, token_id, tick_indexes })
+      },
       ExecuteMsg::WithdrawLiquidity {
         token_id,
         amount,
-      } => self.withdraw(deps, env, info, token_id, amount),
+        receive_asset,
+        minimum_receive,
+      } => self.withdraw(deps, env, info, WithdrawParams { token_id, amount, receive_asset, minimum_receive }),
       ExecuteMsg::Swap {
         offer_asset,
         to,
         belief_price,
-        max_slippage
+        max_slippage,
+        max_ticks,
+        partial_fill,
+        referral,
       } => {
         if !offer_asset.is_native_token() {
           return Err(ContractError::Unauthorized {});
@@ -143,12 +259,42 @@ impl<'a> PairContract<'a> {
             None
         };
 
-        self.swap(deps, env, info.clone(), info.sender, offer_asset, to_addr, belief_price, max_slippage)
+        self.swap(deps, env, info.clone(), info.sender, SwapParams {
+          offer_asset,
+          to: to_addr,
+          belief_price,
+          max_slippage,
+          max_ticks,
+          partial_fill: partial_fill.unwrap_or(false),
+          referral,
+        })
       },
       ExecuteMsg::ClaimReward { 
         token_id,
         rewards,
       } => self.claim(deps, env, info, token_id, rewards),
+      ExecuteMsg::ZapIn {
+        offer_asset,
+        tick_indexes,
+        min_liquidity,
+      } => {
+        if !offer_asset.is_native_token() {
+          return Err(ContractError::Unauthorized {});
+        }
+
+        let sender = info.sender.clone();
+        self.zap_in(deps, env, info, sender, ZapInParams { offer_asset, tick_indexes, min_liquidity })
+      },
+      ExecuteMsg::ZapOut {
+        owner,
+        offer_asset,
+        receive_amount,
+        minimum_receive,
+      } => self.zap_out(deps, env, info, ZapOutParams { owner, offer_asset, receive_amount, minimum_receive }),
+      ExecuteMsg::UpdateConfig { max_swap_ticks, history_config }
+        => self.update_config(deps, env, info, max_swap_ticks, history_config),
+      ExecuteMsg::ProposeNewMinter { minter, expires } => self.propose_new_minter(deps, env, info, minter, expires),
+      ExecuteMsg::DropMinterProposal {} => self.drop_minter_proposal(deps, env, info),
     }
   }
 }
@@ -163,50 +309,75 @@ impl<'a> PairContract<'a> {
     cw20_msg: Cw20ReceiveMsg,
   ) -> Result<Response, ContractError> {
     let contract_addr = info.sender.clone();
+
+    // only asset contract can execute this message
+    let mut authorized: bool = false;
+    let config = self.config.load(deps.storage)?;
+
+    for asset_info in config.asset_infos.iter() {
+      if let AssetInfo::Token { contract_addr } = asset_info {
+        if contract_addr == &info.sender.to_string() {
+          authorized = true;
+        }
+      }
+    }
+
+    if !authorized {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    let sent_asset = Asset {
+      info: AssetInfo::Token {
+        contract_addr: contract_addr.to_string(),
+      },
+      amount: cw20_msg.amount,
+    };
   
     match from_binary(&cw20_msg.msg) {
       Ok(Cw20HookMsg::Swap {
         to,
         belief_price,
         max_slippage,
+        max_ticks,
+        partial_fill,
+        referral,
       }) => {
-        // only asset contract can execute this message
-        let mut authorized: bool = false;
-        let config = self.config.load(deps.storage)?;
-        
-        for asset_info in config.asset_infos.iter() {
-          if let AssetInfo::Token { contract_addr } = asset_info {
-            if contract_addr == &info.sender.to_string() {
-              authorized = true;
-            }
-          }
-        }
-  
-        if !authorized {
-          return Err(ContractError::Unauthorized {});
-        }
-  
         let to_addr = if let Some(to_addr) = to {
           Some(deps.api.addr_validate(to_addr.as_str())?)
         } else {
           None
         };
   
-        self.swap(
-          deps,
-          env,
-          info,
-          Addr::unchecked(cw20_msg.sender),
-          Asset {
-            info: AssetInfo::Token {
-              contract_addr: contract_addr.to_string(),
-            },
-            amount: cw20_msg.amount,
-          },
-          to_addr,
+        self.swap(deps, env, info, Addr::unchecked(cw20_msg.sender), SwapParams {
+          offer_asset: sent_asset,
+          to: to_addr,
           belief_price,
           max_slippage,
-        )
+          max_ticks,
+          partial_fill: partial_fill.unwrap_or(false),
+          referral,
+        })
+      },
+      Ok(Cw20HookMsg::ProvideLiquidity {
+        other_asset,
+        token_id,
+        tick_indexes,
+      }) => {
+        self.provide(deps, env, info, Addr::unchecked(cw20_msg.sender), ProvideParams {
+          assets: [sent_asset, other_asset],
+          token_id,
+          tick_indexes,
+        })
+      },
+      Ok(Cw20HookMsg::ZapIn {
+        tick_indexes,
+        min_liquidity,
+      }) => {
+        self.zap_in(deps, env, info, Addr::unchecked(cw20_msg.sender), ZapInParams {
+          offer_asset: sent_asset,
+          tick_indexes,
+          min_liquidity,
+        })
       },
       Err(err) => Err(ContractError::Std(err)),
     }
@@ -217,10 +388,11 @@ impl<'a> PairContract<'a> {
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    assets: [Asset; 2],
-    token_id: Option<String>,
-    tick_indexes: Option<TickIndexes>,
+    sender: Addr,
+    params: ProvideParams,
   ) -> Result<Response, ContractError> {
+    let ProvideParams { assets, token_id, tick_indexes } = params;
+
     // native sent balance check
     for asset in assets.iter() {
       asset.assert_sent_native_token_balance(&info)?;
@@ -253,7 +425,7 @@ impl<'a> PairContract<'a> {
       let liquidity_token = config.liquidity_token.to_string();
       let liquidity = self.get_liquidity_info(deps.querier, liquidity_token.clone(), token_id.clone())?;
 
-      if info.sender != liquidity.owner {
+      if sender != liquidity.owner {
         return Err(ContractError::Unauthorized {})
       }
 
@@ -262,21 +434,9 @@ impl<'a> PairContract<'a> {
     // when newly provide
     } else if let Some(tick_indexes) = tick_indexes {  
       additional_provide = false;
+      validate_tick_indexes(&tick_indexes, config.tick_space)?;
       lower_tick_index = tick_indexes.lower_tick_index;
       upper_tick_index = tick_indexes.upper_tick_index;
-      // tick check
-      if lower_tick_index > upper_tick_index {
-        return Err(ContractError::InvalidTickRange {})
-      }
-  
-      if tick_index_to_tick(upper_tick_index, config.tick_space) > MAX_TICK 
-      || tick_index_to_tick(lower_tick_index, config.tick_space) < MIN_TICK {
-        return Err(ContractError::InvalidTickRange {})
-      }
-  
-      if upper_tick_index - lower_tick_index > TICK_RANGE_LIMIT {
-        return Err(ContractError::TickRangeLimit {})
-      }
     } else {
       return Err(ContractError::ProvideOptionError {})
     }
@@ -308,29 +468,41 @@ impl<'a> PairContract<'a> {
     
     let mut messages: Vec<CosmosMsg> = vec![];
     for (i, asset_info) in asset_infos.iter().enumerate() {
-      // if token, get token by transfer_from
-      if let AssetInfo::Token { contract_addr, .. } = &asset_info {
-        if !provide_amount[i].is_zero() {
-          messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: contract_addr.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-              owner: info.sender.to_string(),
-              recipient: env.contract.address.to_string(),
-              amount: provide_amount[i],
-            })?,
-            funds: vec![],
-          }));
-        }
-      } else {
-        // If native asset provided more than need, return asset
-        let return_amount = token_amount[i].checked_sub(provide_amount[i])?;
-        if return_amount != Uint128::zero() {
-          let return_asset = Asset{
-            info: asset_info.clone(),
-            amount: return_amount
-          };
-          if !return_asset.deduct_tax(&deps.querier)?.amount.is_zero() {
-            messages.push(return_asset.into_msg(&deps.querier, info.sender.clone())?);
+      match asset_info {
+        // cw20 sent through the receive hook is already in the pair, return the rest
+        AssetInfo::Token { contract_addr, .. } if contract_addr == info.sender.as_str() => {
+          let return_amount = token_amount[i].checked_sub(provide_amount[i])?;
+          if !return_amount.is_zero() {
+            let return_asset = Asset{
+              info: asset_info.clone(),
+              amount: return_amount
+            };
+            messages.push(return_asset.into_msg(&deps.querier, sender.clone())?);
+          }
+        },
+        // if token, get token by transfer_from
+        AssetInfo::Token { contract_addr, .. } => {
+          if !provide_amount[i].is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+              contract_addr: contract_addr.to_string(),
+              msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: provide_amount[i],
+              })?,
+              funds: vec![],
+            }));
+          }
+        },
+        AssetInfo::NativeToken { .. } => {
+          // If native asset provided more than need, return asset
+          let return_amount = token_amount[i].checked_sub(provide_amount[i])?;
+          if return_amount != Uint128::zero() {
+            let return_asset = Asset{
+              info: asset_info.clone(),
+              amount: return_amount
+            };
+            messages.extend(transfer_after_tax(&deps.querier, return_asset, sender.clone())?);
           }
         }
       }
@@ -341,28 +513,7 @@ impl<'a> PairContract<'a> {
     }
   
     // update ticks
-    for i in lower_tick_index..(upper_tick_index + 1) {
-      self.tick_data
-        .update(deps.storage, NewInt32Key::new(i), |tick| match tick {
-          // if tick data exist, update
-          Some(tick) => {
-            let mut new_tick = tick.clone();
-            // update liquidity
-            new_tick.total_liquidity = tick.total_liquidity + liquidity;
-            Ok(new_tick)
-          }
-          // if not exist make new
-          None => {
-            let tick = TickInfo {
-              last_fee_growth_0: Decimal::zero(),
-              last_fee_growth_1: Decimal::zero(),
-              total_liquidity: liquidity,
-            };
-            Ok(tick)
-          }
-          _ => Err(ContractError::UpdateFail {})
-        })?;
-    }
+    let events = self.add_liquidity(deps.storage, lower_tick_index, upper_tick_index, liquidity)?;
 
     if additional_provide {
       // have to claim reward first
@@ -388,7 +539,7 @@ impl<'a> PairContract<'a> {
       messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: config.liquidity_token.to_string(),
         msg: to_binary(&TokenExecuteMsg::Mint {
-          owner: info.sender.clone().into_string(),
+          owner: sender.to_string(),
           liquidity,
           upper_tick_index,
           lower_tick_index
@@ -398,8 +549,9 @@ impl<'a> PairContract<'a> {
     }
 
     Ok(Response::new().add_messages(messages)
+      .add_events(events)
       .add_attribute("action", "provide_liquidity")
-      .add_attribute("sender", info.sender.to_string())
+      .add_attribute("sender", sender.to_string())
       .add_attribute("provide_assets", format!("{}, {}", 
         Asset {
           info: asset_infos[0].clone(),
@@ -424,11 +576,11 @@ impl<'a> PairContract<'a> {
   pub fn withdraw(
     &self,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    token_id: String,
-    amount: Option<Uint128>
+    params: WithdrawParams,
   ) -> Result<Response, ContractError> {
+    let WithdrawParams { token_id, amount, receive_asset, minimum_receive } = params;
     let config = self.config.load(deps.storage)?;
     let liquidity_token = config.liquidity_token.to_string();
     let partial_withdraw: bool;
@@ -448,9 +600,17 @@ impl<'a> PairContract<'a> {
       return Err(ContractError::Unauthorized {})
     }
 
-    let asset_infos = config.asset_infos;
+    let asset_infos = config.asset_infos.clone();
     let current_price_sqrt = self.current_price_sqrt.load(deps.storage)?;
 
+    // index of receive asset of the swap after withdraw
+    let mut zap_out = match receive_asset {
+      Some(receive_asset) if receive_asset.equal(&asset_infos[0]) => Some(0),
+      Some(receive_asset) if receive_asset.equal(&asset_infos[1]) => Some(1),
+      Some(_) => return Err(ContractError::AssetMismatch {}),
+      None => None,
+    };
+
     // caculate withdraw amount
     let (token0_amount, token1_amount) = get_token_amount_from_liquidity(
       liquidity.upper_tick_index,
@@ -461,8 +621,9 @@ impl<'a> PairContract<'a> {
     );
 
     // update ticks
+    let mut events: Vec<Event> = vec![];
     for i in liquidity.lower_tick_index..(liquidity.upper_tick_index + 1) {
-      self.tick_data
+      let tick = self.tick_data
         .update(deps.storage, NewInt32Key::new(i), |tick| match tick {
           Some(tick) => {
             let mut new_tick = tick.clone();
@@ -475,34 +636,46 @@ impl<'a> PairContract<'a> {
             Err(ContractError::UpdateFail {})
           }
         })?;
+
+      events.push(TickLiquidityEvent {
+        tick_index: i,
+        liquidity_delta: withdraw_amount,
+        add: false,
+        liquidity: tick.total_liquidity,
+      }.into());
+    }
+
+    let token_amount = [token0_amount, token1_amount];
+
+    // nothing to swap when the position is only on the receive side
+    if let Some(receive_index) = zap_out {
+      if token_amount[1 - receive_index].is_zero() {
+        if let Some(minimum_receive) = minimum_receive {
+          if token_amount[receive_index] < minimum_receive {
+            return Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive: token_amount[receive_index] })
+          }
+        }
+        zap_out = None;
+      }
     }
 
     let assets = [
       Asset{
         info: asset_infos[0].clone(),
-        amount: token0_amount,
+        amount: token_amount[0],
       },
       Asset {
         info: asset_infos[1].clone(),
-        amount: token1_amount,
+        amount: token_amount[1],
       },
     ];
 
     let mut messages: Vec<CosmosMsg> = vec![];
 
-    // refund assets
-    for asset in assets.clone() {
-      match asset.clone().info {
-        AssetInfo::Token { .. } => {
-          if !asset.amount.is_zero() {
-            messages.push(asset.clone().into_msg(&deps.querier, liquidity.owner.clone())?);
-          }
-        },
-        AssetInfo::NativeToken { .. } => {
-          if !asset.deduct_tax(&deps.querier)?.amount.is_zero() {
-            messages.push(asset.clone().into_msg(&deps.querier, liquidity.owner.clone())?);
-          }
-        }
+    // refund assets, the zap out sends them after the swap
+    if zap_out.is_none() {
+      for asset in assets.clone() {
+        messages.extend(transfer_after_tax(&deps.querier, asset, liquidity.owner.clone())?);
       }
     }
 
@@ -535,7 +708,23 @@ impl<'a> PairContract<'a> {
       }));
     }
 
+    // swap the other asset after the reward is claimed. the commission raises fee growth,
+    // the position would be paid for the withdrawn liquidity if it is claimed after the swap
+    if let Some(receive_index) = zap_out {
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::ZapOut {
+          owner: liquidity.owner.to_string(),
+          offer_asset: assets[1 - receive_index].clone(),
+          receive_amount: token_amount[receive_index],
+          minimum_receive,
+        })?,
+        funds: vec![],
+      }));
+    }
+
     Ok(Response::new().add_messages(messages)
+      .add_events(events)
       .add_attribute("action", "withdraw_liquidity")
       .add_attribute("sender", info.sender.to_string())
       .add_attribute("withdraw_assets", format!("{}, {}", assets[0], assets[1]))
@@ -543,32 +732,84 @@ impl<'a> PairContract<'a> {
     )
   }
 
+  pub fn zap_out(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: ZapOutParams,
+  ) -> Result<Response, ContractError> {
+    let ZapOutParams { owner, offer_asset, receive_amount, minimum_receive } = params;
+
+    // only the withdraw of the pair
+    if info.sender != env.contract.address {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let owner = deps.api.addr_validate(&owner)?;
+    let mut config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+
+    let (offer_token, receive_index) = if offer_asset.info.equal(&asset_infos[0]) {
+      (TokenNumber::Token0, 1)
+    } else if offer_asset.info.equal(&asset_infos[1]) {
+      (TokenNumber::Token1, 0)
+    } else {
+      return Err(ContractError::AssetMismatch {});
+    };
+
+    // commission of the swap with the fee discount of the owner
+    config.fee_rate = discount_fee_rate(config.fee_rate, self.fee_discount(deps.as_ref(), &owner)?);
+    let (return_amount, commission_amount, events) = if offer_asset.amount.is_zero() {
+      (Uint128::zero(), Uint128::zero(), vec![])
+    } else {
+      let max_swap_ticks = self.load_max_swap_ticks(deps.storage)?;
+      let steps = self.compute_swap(deps.storage, &config, &offer_token, offer_asset.amount, max_swap_ticks, false)?;
+      self.apply_swap(deps.storage, &env, &offer_token, offer_asset.amount, &steps)?
+    };
+
+    let receive_amount = receive_amount.checked_add(return_amount.checked_sub(commission_amount)?)?;
+    if let Some(minimum_receive) = minimum_receive {
+      if receive_amount < minimum_receive {
+        return Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive: receive_amount })
+      }
+    }
+
+    let receive_asset = Asset {
+      info: asset_infos[receive_index].clone(),
+      amount: receive_amount,
+    };
+    let messages: Vec<CosmosMsg> = transfer_after_tax(&deps.querier, receive_asset.clone(), owner.clone())?
+      .into_iter()
+      .collect();
+
+    Ok(Response::new().add_messages(messages)
+      .add_events(events)
+      .add_attribute("action", "zap_out")
+      .add_attribute("owner", owner.to_string())
+      .add_attribute("receive_asset", receive_asset.to_string())
+      .add_attribute("swap_amount", offer_asset.amount.to_string())
+      .add_attribute("commission_amount", commission_amount.to_string())
+    )
+  }
+
   pub fn swap(
     &self,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     sender:Addr,
-    offer_asset: Asset,
-    to: Option<Addr>,
-    belief_price: Option<Decimal>,
-    max_slippage: Option<Decimal>
+    params: SwapParams,
   ) -> Result<Response, ContractError> {
+    let SwapParams { offer_asset, to, belief_price, max_slippage, max_ticks, partial_fill, referral } = params;
+
     // native sent balance check
     offer_asset.assert_sent_native_token_balance(&info)?;
 
-    let config = self.config.load(deps.storage)?;
-    let tick_index = self.current_tick_index.load(deps.storage)?;
-
-    let asset_infos = config.asset_infos;
-    let current_price_sqrt = self.current_price_sqrt.load(deps.storage)?;
-
-    let mut remain = offer_asset.amount.clone();
-    let mut tick_index_temp = tick_index.clone();
-    let mut price_sqrt_temp = current_price_sqrt.clone();
-    let mut total_return_amount = Uint128::zero();
-    let mut total_commission_amount = Uint128::zero();
-
+    let mut config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+    // commission of the swap with the fee discount of the sender
+    config.fee_rate = discount_fee_rate(config.fee_rate, self.fee_discount(deps.as_ref(), &sender)?);
 
     let offer_token: TokenNumber;
     let return_token_info: AssetInfo;
@@ -583,75 +824,36 @@ impl<'a> PairContract<'a> {
       return Err(ContractError::AssetMismatch {});
     }
 
-    while remain > Uint128::zero() {
-      let tick_data = match self.tick_data.may_load(deps.storage, NewInt32Key::from(tick_index_temp))? {
-        Some(tick_data) => tick_data,
-        None => return Err(ContractError::CanNotSwap {})
-      };
+    let max_swap_ticks = self.load_max_swap_ticks(deps.storage)?;
+    let max_ticks = max_ticks.map_or(max_swap_ticks, |max_ticks| max_ticks.min(max_swap_ticks));
 
-      if tick_data.total_liquidity.is_zero() {
-        return Err(ContractError::CanNotSwap {}) 
-      }
-      
-
-      // compute swap
-      let (offer_amount, return_amount, commission_amount, next_price_sqrt, next_tick_index) 
-        = compute_swap_tick(tick_index_temp, config.tick_space, price_sqrt_temp, tick_data.total_liquidity, &offer_token, remain, config.fee_rate);
-
-      // update
-      // update commission
-      self.tick_data.update(deps.storage, NewInt32Key::new(tick_index_temp), |tick| match tick {
-        Some(tick) => {
-          let mut new_tick = tick.clone();
-          match offer_token {
-            TokenNumber::Token0 => {
-              new_tick.last_fee_growth_1 = tick.last_fee_growth_1 
-                + Decimal::from_ratio(commission_amount, tick.total_liquidity);
-            },
-            TokenNumber::Token1 => {
-              new_tick.last_fee_growth_0 = tick.last_fee_growth_0 
-                + Decimal::from_ratio(commission_amount, tick.total_liquidity);
-            }
-          }
-          Ok(new_tick)
-        }
-        None => {
-          // never get this.
-          Err(ContractError::UpdateFail {})
-        }
-      })?;
-
-      remain = remain.checked_sub(offer_amount)?; 
-      total_return_amount += return_amount;
-      total_commission_amount += commission_amount;
-      tick_index_temp = next_tick_index;
-      price_sqrt_temp = next_price_sqrt;
-    }
-
-    // update volume fee
-    let mut volume = self.cumulative_volume.load(deps.storage)?;
-
-    match offer_token {
-      TokenNumber::Token0 => {
-        volume[0] = volume[0].wrapping_add(offer_asset.amount);
-        volume[1] = volume[1].wrapping_add(total_return_amount);
-      },
-      TokenNumber::Token1 => {
-        volume[1] = volume[1].wrapping_add(offer_asset.amount);
-        volume[0] = volume[0].wrapping_add(total_return_amount);
-      }
-    }
-
-    self.cumulative_volume.save(deps.storage, &volume)?;
+    let steps = self.compute_swap(deps.storage, &config, &offer_token, offer_asset.amount, max_ticks, partial_fill)?;
+    // less than the offer amount if the swap is partially filled
+    let offer_amount = steps.iter().fold(Uint128::zero(), |sum, step| sum + step.offer_amount);
+    let refund_amount = offer_asset.amount.checked_sub(offer_amount)?;
+    let (total_return_amount, total_commission_amount, events)
+      = self.apply_swap(deps.storage, &env, &offer_token, offer_amount, &steps)?;
 
     let user_return_amount = total_return_amount.checked_sub(total_commission_amount)?;
 
+    // referral commission is taken from the user return, not from the LP commission
+    let (referral_addr, referral_amount) = if let Some(referral) = referral {
+      let max_commission_bps = self.max_referral_commission_bps(deps.as_ref())?;
+      (
+        Some(deps.api.addr_validate(&referral.address)?),
+        referral.commission_amount(user_return_amount, max_commission_bps),
+      )
+    } else {
+      (None, Uint128::zero())
+    };
+    let user_return_amount = user_return_amount.checked_sub(referral_amount)?;
+
     // slippage protection
     if let (Some(max_slippage), Some(belief_price)) = (max_slippage, belief_price) {
       // min_return = expected_return * (1 - max_slippage)
       // = offer_amount / belief_price * (1 - max_slippage)
       let min_return = Decimal::from_ratio(
-        offer_asset.amount * DECIMAL_FRACTION,
+        offer_amount * DECIMAL_FRACTION,
         belief_price * DECIMAL_FRACTION
       ) * Uint128::from(1u128) 
       * (Decimal::one() - max_slippage);
@@ -661,10 +863,6 @@ impl<'a> PairContract<'a> {
       }
     };
 
-    // update state
-    self.current_price_sqrt.save(deps.storage, &price_sqrt_temp)?;
-    self.current_tick_index.save(deps.storage, &tick_index_temp)?;
-
     let return_asset = Asset {
       info: return_token_info.clone(),
       amount: user_return_amount,
@@ -679,19 +877,292 @@ impl<'a> PairContract<'a> {
         messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
     }
 
+    // unfilled offer goes back to the sender
+    if !refund_amount.is_zero() {
+      let refund_asset = Asset {
+        info: offer_asset.info.clone(),
+        amount: refund_amount,
+      };
+      messages.push(refund_asset.into_msg(&deps.querier, sender.clone())?);
+    }
+
+    if let Some(referral_addr) = referral_addr.clone().filter(|_| !referral_amount.is_zero()) {
+      let referral_asset = Asset {
+        info: return_token_info.clone(),
+        amount: referral_amount,
+      };
+      messages.push(referral_asset.into_msg(&deps.querier, referral_addr)?);
+    }
+
     Ok(Response::new().add_messages(messages)
+      .add_events(events)
       .add_attribute("action", "swap")
       .add_attribute("sender", sender.to_string())
       .add_attribute("receiver", receiver.to_string())
       .add_attribute("offer_asset", offer_asset.info.to_string())
       .add_attribute("return_asset", return_token_info.to_string())
-      .add_attribute("offer_amount", offer_asset.amount.to_string())
+      .add_attribute("offer_amount", offer_amount.to_string())
+      .add_attribute("refund_amount", refund_amount.to_string())
       .add_attribute("return_amount", user_return_amount.to_string())
       .add_attribute("tax_amount", tax_amount.to_string())
       .add_attribute("commission_amount", total_commission_amount.to_string())
+      .add_attribute("referral_address", referral_addr.map(|addr| addr.to_string()).unwrap_or_default())
+      .add_attribute("referral_amount", referral_amount.to_string())
+    )
+  }
+
+  pub fn zap_in(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    params: ZapInParams,
+  ) -> Result<Response, ContractError> {
+    let ZapInParams { offer_asset, tick_indexes, min_liquidity } = params;
+
+    // native sent balance check
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+    validate_tick_indexes(&tick_indexes, config.tick_space)?;
+    // commission of the swap with the fee discount of the sender
+    config.fee_rate = discount_fee_rate(config.fee_rate, self.fee_discount(deps.as_ref(), &sender)?);
+
+    let offer_token = if offer_asset.info.equal(&asset_infos[0]) {
+      TokenNumber::Token0
+    } else if offer_asset.info.equal(&asset_infos[1]) {
+      TokenNumber::Token1
+    } else {
+      return Err(ContractError::AssetMismatch {});
+    };
+
+    // swap first, in the same pool
+    let swap_amount = self.compute_zap_swap_amount(
+      deps.storage,
+      &config,
+      &offer_token,
+      offer_asset.amount,
+      &tick_indexes
+    )?;
+    let max_swap_ticks = self.load_max_swap_ticks(deps.storage)?;
+    let steps = self.compute_swap(deps.storage, &config, &offer_token, swap_amount, max_swap_ticks, false)?;
+    let (return_amount, commission_amount, mut events)
+      = self.apply_swap(deps.storage, &env, &offer_token, swap_amount, &steps)?;
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    let remain_amount = offer_asset.amount.checked_sub(swap_amount)?;
+    let token_amount: [Uint128; 2] = match offer_token {
+      TokenNumber::Token0 => [remain_amount, return_amount],
+      TokenNumber::Token1 => [return_amount, remain_amount],
+    };
+
+    // provide with the price after the swap
+    let current_price_sqrt = self.current_price_sqrt.load(deps.storage)?;
+    let liquidity = compute_liquidity(
+      token_amount[0],
+      token_amount[1],
+      current_price_sqrt,
+      tick_indexes.upper_tick_index,
+      tick_indexes.lower_tick_index,
+      config.tick_space,
+    );
+
+    if liquidity.is_zero() {
+      return Err(ContractError::ZeroLiquidity {})
+    }
+
+    if let Some(min_liquidity) = min_liquidity {
+      if liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityAssertion { min_liquidity, liquidity })
+      }
+    }
+
+    let (token0_provide_amount, token1_provide_amount) = get_token_amount_from_liquidity(
+      tick_indexes.upper_tick_index,
+      tick_indexes.lower_tick_index,
+      config.tick_space,
+      current_price_sqrt,
+      liquidity
+    );
+    let provide_amount = [token0_provide_amount, token1_provide_amount];
+
+    events.extend(self.add_liquidity(
+      deps.storage,
+      tick_indexes.lower_tick_index,
+      tick_indexes.upper_tick_index,
+      liquidity
+    )?);
+
+    // refund dust
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut refund_assets: Vec<Asset> = vec![];
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+      let refund_asset = Asset {
+        info: asset_info.clone(),
+        amount: token_amount[i].checked_sub(provide_amount[i])?,
+      };
+
+      if !refund_asset.amount.is_zero() {
+        messages.extend(transfer_after_tax(&deps.querier, refund_asset.clone(), sender.clone())?);
+      }
+      refund_assets.push(refund_asset);
+    }
+
+    // mint Lp token
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+      contract_addr: config.liquidity_token.to_string(),
+      msg: to_binary(&TokenExecuteMsg::Mint {
+        owner: sender.to_string(),
+        liquidity,
+        upper_tick_index: tick_indexes.upper_tick_index,
+        lower_tick_index: tick_indexes.lower_tick_index,
+      })?,
+      funds: vec![],
+    }));
+
+    Ok(Response::new().add_messages(messages)
+      .add_events(events)
+      .add_attribute("action", "zap_in")
+      .add_attribute("sender", sender.to_string())
+      .add_attribute("offer_asset", offer_asset.to_string())
+      .add_attribute("swap_amount", swap_amount.to_string())
+      .add_attribute("return_amount", return_amount.to_string())
+      .add_attribute("commission_amount", commission_amount.to_string())
+      .add_attribute("provide_assets", format!("{}, {}",
+        Asset {
+          info: asset_infos[0].clone(),
+          amount: provide_amount[0]
+        },
+        Asset {
+          info: asset_infos[1].clone(),
+          amount: provide_amount[1]
+        }
+      ))
+      .add_attribute("refund_assets", format!("{}, {}", refund_assets[0], refund_assets[1]))
+      .add_attribute("liquidity", liquidity.to_string())
     )
   }
 
+  pub fn update_config(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    max_swap_ticks: Option<u32>,
+    history_config: Option<HistoryConfig>,
+  ) -> Result<Response, ContractError> {
+    // pairs instantiated before the max swap ticks don't have the factory
+    match self.factory.may_load(deps.storage)? {
+      Some(factory) if factory == info.sender => {},
+      _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    if let Some(max_swap_ticks) = max_swap_ticks {
+      if max_swap_ticks == 0 {
+        return Err(ContractError::InvalidMaxSwapTicks {});
+      }
+      self.max_swap_ticks.save(deps.storage, &max_swap_ticks)?;
+    }
+
+    if let Some(history_config) = history_config {
+      if history_config.hourly_size == 0 || history_config.daily_size == 0 {
+        return Err(ContractError::InvalidHistorySize {});
+      }
+      self.history_config.save(deps.storage, &history_config)?;
+    }
+
+    Ok(Response::new()
+      .add_attribute("action", "update_config")
+      .add_attribute("max_swap_ticks", self.load_max_swap_ticks(deps.storage)?.to_string())
+    )
+  }
+
+  /// the lp token moves to the new minter (e.g. the next version of the pair) when it claims
+  pub fn propose_new_minter(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+    expires: Expiration,
+  ) -> Result<Response, ContractError> {
+    self.assert_factory_timelock(deps.as_ref(), &info)?;
+
+    let config = self.config.load(deps.storage)?;
+    let minter = deps.api.addr_validate(&minter)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::ProposeNewMinter { minter: minter.to_string(), expires })?,
+        funds: vec![],
+      })
+      .add_attribute("action", "propose_new_minter")
+      .add_attribute("minter", minter.to_string())
+      .add_attribute("expires", expires.to_string())
+    )
+  }
+
+  pub fn drop_minter_proposal(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    let factory_config = self.query_factory_config(deps.as_ref())?.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != factory_config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let config = self.config.load(deps.storage)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::DropMinterProposal {})?,
+        funds: vec![],
+      })
+      .add_attribute("action", "drop_minter_proposal")
+    )
+  }
+
+  // pairs instantiated before the max swap ticks don't have the factory
+  fn query_factory_config(&self, deps: Deps) -> StdResult<Option<FactoryConfig>> {
+    match self.factory.may_load(deps.storage)? {
+      Some(factory) => Ok(Some(deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory.to_string(),
+        msg: to_binary(&FactoryQueryMsg::Config {})?,
+      }))?)),
+      None => Ok(None),
+    }
+  }
+
+  // factory owner while the factory has no timelock, otherwise the factory executing the queued action
+  fn assert_factory_timelock(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    if self.factory.may_load(deps.storage)?.as_ref() == Some(&info.sender) {
+      return Ok(())
+    }
+
+    let factory_config = self.query_factory_config(deps)?.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != factory_config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if factory_config.timelock_delay > 0 {
+      return Err(ContractError::Timelocked {})
+    }
+
+    Ok(())
+  }
+
+  // the referral fails instead of being dropped without the factory
+  fn max_referral_commission_bps(&self, deps: Deps) -> Result<u16, ContractError> {
+    let factory_config = self.query_factory_config(deps)?.ok_or(ContractError::ReferralNotSupported {})?;
+    Ok(factory_config.max_referral_commission_bps)
+  }
+
   pub fn claim(
     &self,
     deps: DepsMut,
@@ -714,20 +1185,9 @@ impl<'a> PairContract<'a> {
     let mut messages: Vec<CosmosMsg> = vec![];
 
     for reward in rewards.clone() {
-      match reward.clone().info {
-        AssetInfo::Token { .. } => {
-          if !reward.amount.is_zero() {
-            messages.push(reward.clone().into_msg(&deps.querier, owner.clone())?);
-          }
-        },
-        AssetInfo::NativeToken { .. } => {
-          if !reward.deduct_tax(&deps.querier)?.amount.is_zero() {
-            messages.push(reward.clone().into_msg(&deps.querier, owner.clone())?);
-          }
-        }
-      }
+      messages.extend(transfer_after_tax(&deps.querier, reward, owner.clone())?);
     }
-    
+
 
     Ok(Response::new().add_messages(messages)
       .add_attribute("action", "claim_reward")
@@ -736,4 +1196,373 @@ impl<'a> PairContract<'a> {
       .add_attribute("claim_amount", format!("{}, {}", rewards[0], rewards[1]))
     )
   }
-}
\ No newline at end of file
+}
+/// help function
+impl<'a> PairContract<'a> {
+  /// add liquidity to every tick of the range, return tick liquidity events
+  fn add_liquidity(
+    &self,
+    storage: &mut dyn Storage,
+    lower_tick_index: i32,
+    upper_tick_index: i32,
+    liquidity: Uint128,
+  ) -> Result<Vec<Event>, ContractError> {
+    let mut events: Vec<Event> = vec![];
+    for i in lower_tick_index..(upper_tick_index + 1) {
+      let tick = self.tick_data
+        .update::<_, ContractError>(storage, NewInt32Key::new(i), |tick| match tick {
+          // if tick data exist, update
+          Some(tick) => {
+            let mut new_tick = tick.clone();
+            // update liquidity
+            new_tick.total_liquidity = tick.total_liquidity + liquidity;
+            Ok(new_tick)
+          }
+          // if not exist make new
+          None => {
+            let tick = TickInfo {
+              last_fee_growth_0: Uint256::zero(),
+              last_fee_growth_1: Uint256::zero(),
+              total_liquidity: liquidity,
+            };
+            Ok(tick)
+          }
+        })?;
+
+      events.push(TickLiquidityEvent {
+        tick_index: i,
+        liquidity_delta: liquidity,
+        add: true,
+        liquidity: tick.total_liquidity,
+      }.into());
+    }
+
+    Ok(events)
+  }
+
+  // offer amount to swap before zap in provide. binary search the amount where
+  // the rest of the offer and the return amount make the same liquidity at the price after the swap.
+  // the search is cut at the tolerance or the iteration cap, the lower bound is returned
+  // and the unmatched rest is refunded as dust
+  fn compute_zap_swap_amount(
+    &self,
+    storage: &dyn Storage,
+    config: &Config,
+    offer_token: &TokenNumber,
+    offer_amount: Uint128,
+    tick_indexes: &TickIndexes,
+  ) -> Result<Uint128, ContractError> {
+    let current_price_sqrt = self.current_price_sqrt.load(storage)?;
+    let max_swap_ticks = self.load_max_swap_ticks(storage)?;
+
+    let tolerance = offer_amount.multiply_ratio(1u128, ZAP_SEARCH_TOLERANCE);
+    let mut low = Uint128::zero();
+    let mut high = offer_amount;
+    for _ in 0..ZAP_SEARCH_ITERATIONS {
+      if high - low <= tolerance {
+        break;
+      }
+
+      let mid = low + (high - low).multiply_ratio(1u128, 2u128);
+
+      // swap more while the offer token makes more liquidity than the return token
+      let swap_more = match self.compute_swap(storage, config, offer_token, mid, max_swap_ticks, false) {
+        Ok(steps) => {
+          let return_amount = steps
+            .iter()
+            .fold(Uint128::zero(), |sum, step| sum + step.return_amount - step.commission_amount);
+          let price_sqrt = steps.last().map_or(current_price_sqrt, |step| step.price_sqrt_after);
+          let remain_amount = offer_amount - mid;
+
+          let (token0_amount, token1_amount) = match offer_token {
+            TokenNumber::Token0 => (remain_amount, return_amount),
+            TokenNumber::Token1 => (return_amount, remain_amount),
+          };
+          let (liquidity0, liquidity1) = compute_liquidity_of_tokens(
+            token0_amount,
+            token1_amount,
+            price_sqrt,
+            tick_indexes.upper_tick_index,
+            tick_indexes.lower_tick_index,
+            config.tick_space,
+          );
+
+          match offer_token {
+            TokenNumber::Token0 => liquidity0 > liquidity1,
+            TokenNumber::Token1 => liquidity1 > liquidity0,
+          }
+        },
+        // not enough liquidity to swap the amount
+        Err(ContractError::CanNotSwap {}) | Err(ContractError::MaxTicksReached { .. }) => false,
+        Err(err) => return Err(err),
+      };
+
+      if swap_more {
+        low = mid + Uint128::from(1u128);
+      } else {
+        high = mid;
+      }
+    }
+
+    Ok(low)
+  }
+
+  /// swap steps of the offer amount from the current price, storage is not changed
+  /// at most max_ticks steps, the rest of the offer amount is not swapped if partial_fill
+  pub fn compute_swap(
+    &self,
+    storage: &dyn Storage,
+    config: &Config,
+    offer_token: &TokenNumber,
+    offer_amount: Uint128,
+    max_ticks: u32,
+    partial_fill: bool,
+  ) -> Result<Vec<SwapStep>, ContractError> {
+    let mut remain = offer_amount;
+    let mut tick_index = self.current_tick_index.load(storage)?;
+    let mut price_sqrt = self.current_price_sqrt.load(storage)?;
+
+    let mut steps: Vec<SwapStep> = vec![];
+    while remain > Uint128::zero() {
+      if steps.len() as u32 >= max_ticks {
+        if partial_fill {
+          break;
+        }
+
+        return Err(ContractError::MaxTicksReached {
+          max_ticks,
+          offer_amount: offer_amount - remain,
+          return_amount: steps.iter().fold(Uint128::zero(), |sum, step| sum + step.return_amount - step.commission_amount),
+        })
+      }
+
+      let tick_data = match self.tick_data.may_load(storage, NewInt32Key::from(tick_index))? {
+        Some(tick_data) => tick_data,
+        None => return Err(ContractError::CanNotSwap {})
+      };
+
+      if tick_data.total_liquidity.is_zero() {
+        return Err(ContractError::CanNotSwap {})
+      }
+
+      // compute swap
+      let (offer_amount, return_amount, commission_amount, next_price_sqrt, next_tick_index) = compute_swap_tick(
+        tick_index, config.tick_space, price_sqrt, tick_data.total_liquidity, offer_token, remain, config.fee_rate
+      );
+
+      steps.push(SwapStep {
+        tick_index,
+        liquidity: tick_data.total_liquidity,
+        offer_amount,
+        return_amount,
+        commission_amount,
+        price_sqrt_before: price_sqrt,
+        price_sqrt_after: next_price_sqrt,
+        next_tick_index,
+      });
+
+      remain = remain.checked_sub(offer_amount)?;
+      tick_index = next_tick_index;
+      price_sqrt = next_price_sqrt;
+    }
+
+    Ok(steps)
+  }
+
+  /// update fee growth, volume, history and price with the swap steps
+  /// return (return amount, commission amount, tick swap events)
+  pub fn apply_swap(
+    &self,
+    storage: &mut dyn Storage,
+    env: &Env,
+    offer_token: &TokenNumber,
+    offer_amount: Uint128,
+    steps: &[SwapStep],
+  ) -> Result<(Uint128, Uint128, Vec<Event>), ContractError> {
+    let tick_index = self.current_tick_index.load(storage)?;
+
+    let mut total_return_amount = Uint128::zero();
+    let mut total_commission_amount = Uint128::zero();
+    let mut events: Vec<Event> = vec![];
+    for step in steps.iter() {
+      // commission is paid in return asset
+      let fee_growth = compute_fee_growth(step.commission_amount, step.liquidity);
+      let fee_growth_delta = match offer_token {
+        TokenNumber::Token0 => [Uint256::zero(), fee_growth],
+        TokenNumber::Token1 => [fee_growth, Uint256::zero()],
+      };
+
+      // update commission
+      self.tick_data.update(storage, NewInt32Key::new(step.tick_index), |tick| match tick {
+        Some(tick) => {
+          let mut new_tick = tick.clone();
+          new_tick.last_fee_growth_0 = fee_growth_add(tick.last_fee_growth_0, fee_growth_delta[0]);
+          new_tick.last_fee_growth_1 = fee_growth_add(tick.last_fee_growth_1, fee_growth_delta[1]);
+          Ok(new_tick)
+        }
+        None => {
+          // never get this.
+          Err(ContractError::UpdateFail {})
+        }
+      })?;
+
+      events.push(TickSwapEvent {
+        tick_index: step.tick_index,
+        liquidity: step.liquidity,
+        offer_amount: step.offer_amount,
+        return_amount: step.return_amount,
+        commission_amount: step.commission_amount,
+        fee_growth_delta,
+        price_sqrt_before: step.price_sqrt_before,
+        price_sqrt_after: step.price_sqrt_after,
+        price_before: price_sqrt_to_price(step.price_sqrt_before),
+        price_after: price_sqrt_to_price(step.price_sqrt_after),
+      }.into());
+
+      total_return_amount += step.return_amount;
+      total_commission_amount += step.commission_amount;
+    }
+
+    // update volume fee
+    let mut volume = self.cumulative_volume.load(storage)?;
+
+    match offer_token {
+      TokenNumber::Token0 => {
+        volume[0] = volume[0].wrapping_add(offer_amount);
+        volume[1] = volume[1].wrapping_add(total_return_amount);
+      },
+      TokenNumber::Token1 => {
+        volume[1] = volume[1].wrapping_add(offer_amount);
+        volume[0] = volume[0].wrapping_add(total_return_amount);
+      }
+    }
+
+    self.cumulative_volume.save(storage, &volume)?;
+
+    // offer, return
+    let (swap_volume, commission) = match offer_token {
+      TokenNumber::Token0 => (
+        [offer_amount, total_return_amount],
+        [Uint128::zero(), total_commission_amount],
+      ),
+      TokenNumber::Token1 => (
+        [total_return_amount, offer_amount],
+        [total_commission_amount, Uint128::zero()],
+      ),
+    };
+
+    let next_tick_index = steps.last().map_or(tick_index, |step| step.next_tick_index);
+
+    let swap_history = HistoryInfo {
+      start_time: env.block.time.seconds(),
+      volume: swap_volume,
+      commission,
+      open_tick_index: tick_index,
+      close_tick_index: next_tick_index,
+      swap_count: 1,
+    };
+
+    for period in [HistoryPeriod::Hourly, HistoryPeriod::Daily] {
+      self.update_history(storage, period, &swap_history)?;
+    }
+
+    // update state
+    if let Some(step) = steps.last() {
+      self.current_price_sqrt.save(storage, &step.price_sqrt_after)?;
+      self.current_tick_index.save(storage, &step.next_tick_index)?;
+    }
+
+    Ok((total_return_amount, total_commission_amount, events))
+  }
+
+  // add the swap to the period which contains swap.start_time
+  fn update_history(
+    &self,
+    storage: &mut dyn Storage,
+    period: HistoryPeriod,
+    swap: &HistoryInfo,
+  ) -> StdResult<()> {
+    let history = self.history_map(period);
+    let start_time = swap.start_time / period.seconds() * period.seconds();
+
+    let info = match history.may_load(storage, U64Key::new(start_time))? {
+      Some(info) => HistoryInfo {
+        volume: [info.volume[0].wrapping_add(swap.volume[0]), info.volume[1].wrapping_add(swap.volume[1])],
+        commission: [
+          info.commission[0].wrapping_add(swap.commission[0]),
+          info.commission[1].wrapping_add(swap.commission[1]),
+        ],
+        close_tick_index: swap.close_tick_index,
+        swap_count: info.swap_count + swap.swap_count,
+        ..info
+      },
+      None => {
+        // new period, prune old ones
+        let history_config = self.history_config.may_load(storage)?.unwrap_or_default();
+        let size = match period {
+          HistoryPeriod::Hourly => history_config.hourly_size,
+          HistoryPeriod::Daily => history_config.daily_size,
+        };
+        let expired_time = start_time.saturating_sub(u64::from(size) * period.seconds());
+        let expired_keys = history
+          .keys(storage, None, Some(Bound::inclusive(U64Key::new(expired_time))), Order::Ascending)
+          .collect::<Vec<_>>();
+        for key in expired_keys {
+          history.remove(storage, U64Key::from(key));
+        }
+
+        HistoryInfo {
+          start_time,
+          ..swap.clone()
+        }
+      }
+    };
+
+    history.save(storage, U64Key::new(start_time), &info)
+  }
+}
+
+fn validate_tick_indexes(tick_indexes: &TickIndexes, tick_space: u16) -> Result<(), ContractError> {
+  if tick_indexes.lower_tick_index > tick_indexes.upper_tick_index {
+    return Err(ContractError::InvalidTickRange {})
+  }
+
+  if tick_index_to_tick(tick_indexes.upper_tick_index, tick_space) > MAX_TICK
+  || tick_index_to_tick(tick_indexes.lower_tick_index, tick_space) < MIN_TICK {
+    return Err(ContractError::InvalidTickRange {})
+  }
+
+  if tick_indexes.upper_tick_index - tick_indexes.lower_tick_index > TICK_RANGE_LIMIT {
+    return Err(ContractError::TickRangeLimit {})
+  }
+
+  Ok(())
+}
+
+// swap in one tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapStep {
+  pub tick_index: i32,
+  pub liquidity: Uint128,
+  pub offer_amount: Uint128,
+  // include commission
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+  pub price_sqrt_before: Uint256,
+  pub price_sqrt_after: Uint256,
+  pub next_tick_index: i32,
+}
+
+// transfer msg of the asset. None if nothing is left after tax
+fn transfer_after_tax(querier: &QuerierWrapper, asset: Asset, recipient: Addr) -> StdResult<Option<CosmosMsg>> {
+  let amount = match asset.info {
+    AssetInfo::Token { .. } => asset.amount,
+    AssetInfo::NativeToken { .. } => asset.deduct_tax_with::<ChainTax>(querier)?.amount,
+  };
+
+  if amount.is_zero() {
+    return Ok(None)
+  }
+
+  Ok(Some(asset.into_msg_with::<ChainTax>(querier, recipient)?))
+}
@@ -4,7 +4,7 @@ mod query;
 mod state;
 mod response;
 
-pub use wineswap::pair::{InstantiateMsg, ExecuteMsg, QueryMsg};
+pub use wineswap::pair::{InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg};
 pub use crate::error::ContractError;
 pub use crate::state::PairContract;
 
@@ -54,6 +54,12 @@ pub mod entry {
     tract.execute(deps, env, info, msg)
   }
 
+  #[entry_point]
+  pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let tract = PairContract::default();
+    tract.migrate(deps, env, msg)
+  }
+
   #[entry_point]
   pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let tract = PairContract::default();
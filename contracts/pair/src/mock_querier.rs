@@ -10,7 +10,7 @@ use std::collections::HashMap;
 
 use wineswap::lp_token::{RewardResponse, LiquidityInfoResponse};
 use wineswap::asset::{Asset, AssetInfo};
-use wineswap::factory::Config;
+use wineswap::factory::{Config, FeeDiscountResponse};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -42,7 +42,8 @@ pub enum QueryMsg {
     TickInfos { start_after: Option<i32>, limit: Option<u32> },
     LiquidityInfo { token_id: String },
     Reward { token_id: String },
-    Config {}
+    Config {},
+    FeeDiscount { address: String },
 }
 
 
@@ -50,6 +51,7 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     tax_querier: TaxQuerier,
     lp_querier: LpQuerier,
+    timelock_delay: u64,
 }
 
 #[derive(Clone, Default)]
@@ -173,10 +175,29 @@ impl WasmMockQuerier {
                                 owner: Addr::unchecked("owner"),
                                 pair_code_id: 123,
                                 token_code_id: 312,
+                                quote_assets: vec![],
+                                stable_pair_code_id: None,
+                                max_swap_ticks: None,
+                                max_referral_commission_bps: 100,
+                                ownership_proposal: None,
+                                timelock_delay: self.timelock_delay,
+                                pair_creation_fee: None,
+                                fee_collector: None,
                             })
                             .unwrap(),
                         ))
                     }
+                    Ok(QueryMsg::FeeDiscount { address }) => {
+                        // factory fee discount, half of the fee for "discounted"
+                        let discount = if address == "discounted" {
+                            Decimal::from_ratio(1u128, 2u128)
+                        } else {
+                            Decimal::zero()
+                        };
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&FeeDiscountResponse { address, discount }).unwrap(),
+                        ))
+                    }
                     _ =>  panic!("DO NOT ENTER HERE"),
                         
                 }
@@ -191,7 +212,8 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             tax_querier: TaxQuerier::default(),
-            lp_querier: LpQuerier::default()
+            lp_querier: LpQuerier::default(),
+            timelock_delay: 0,
         }
     }
     pub fn with_lp_infos(&mut self, lp_infos: &[(&String, &LiquidityInfoResponse)]) {
@@ -202,4 +224,9 @@ impl WasmMockQuerier {
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
+
+    // configure the timelock delay of the factory config
+    pub fn with_timelock_delay(&mut self, timelock_delay: u64) {
+        self.timelock_delay = timelock_delay;
+    }
 }
\ No newline at end of file
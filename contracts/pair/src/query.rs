@@ -1,17 +1,23 @@
-use cosmwasm_std::{to_binary, Binary, Deps, Order, QuerierWrapper, QueryRequest, StdError, StdResult,
-  Uint128, WasmQuery};
-use cw_storage_plus::Bound;
+use cosmwasm_std::{to_binary, Addr, Binary, Decimal, Decimal256, Deps, Order, QuerierWrapper, QueryRequest, StdError,
+  StdResult, Uint128, Uint256, WasmQuery};
+use cw_storage_plus::{Bound, U64Key};
 use wineswap::new_int_key::NewInt32Key;
 
-use wineswap::pair::{PairInfoResponse, ProvideCalculationResponse, QueryMsg, ReverseSimulationResponse,
-  SimulationResponse, TickInfoResponse, TickInfosResponse, WithdrawCalculationResponse};
+use wineswap::factory::{FeeDiscountResponse, QueryMsg as FactoryQueryMsg};
+use wineswap::pair::{AmountToPriceResponse, ConfigResponse, EffectiveFeeResponse, HistoryPeriod, HistoryResponse, PairInfoResponse,
+  PriceToTickIndexResponse, ProvideCalculationResponse, QueryMsg, ReverseSimulationResponse, Rounding, SimulationDetail,
+  SimulationResponse, SwapStepResponse, TickIndexToPriceRangeResponse, TickInfoResponse, TickInfosResponse,
+  WithdrawCalculationResponse};
 use wineswap::asset::{Asset, AssetInfo, TokenNumber};
 use wineswap::lp_token::{LiquidityInfoResponse, QueryMsg::LiquidityInfo};
-use wineswap_math::tick::{get_tick_price_sqrt};
+use wineswap_math::tick::{get_tick_from_price_sqrt, get_tick_price_sqrt, tick_index_to_tick, tick_to_tick_index,
+  MAX_TICK, MIN_TICK};
 use wineswap_math::liquidity::{compute_token_liquidity, get_token_amount_from_liquidity};
-use wineswap_math::swap::{compute_swap_tick, compute_swap_tick_reverse};
-use wineswap_math::price::price_sqrt_to_price;
+use wineswap_math::swap::{compute_swap_tick, compute_swap_tick_reverse, compute_swap_tick_to_price};
+use wineswap_math::price::{compute_price, price_sqrt_to_decimal_price, price_sqrt_to_price, price_to_price_sqrt};
+use wineswap_math::fee::discount_fee_rate;
 use crate::state::PairContract;
+use crate::error::ContractError;
 
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -31,10 +37,26 @@ impl<'a> PairContract<'a> {
       tick_space: config.tick_space,
       fee_rate: config.fee_rate,
       price,
+      current_price_sqrt: price_sqrt,
       current_tick_index
     })
   }
 
+  fn config(&self, deps: Deps) -> StdResult<ConfigResponse> {
+    let config = self.config.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+      asset_infos: config.asset_infos,
+      tick_space: config.tick_space,
+      fee_rate: config.fee_rate,
+      liquidity_token: config.liquidity_token.to_string(),
+      factory: self.factory.may_load(deps.storage)?.map(|factory| factory.to_string()),
+      max_swap_ticks: self.load_max_swap_ticks(deps.storage)?,
+      asset_decimals: config.asset_decimals,
+      history_config: self.history_config.may_load(deps.storage)?.unwrap_or_default(),
+    })
+  }
+
   fn tick_info(&self, deps: Deps, tick_index: i32) -> StdResult<TickInfoResponse> {
     let tick_info = self.tick_data.load(deps.storage, NewInt32Key::from(tick_index))?;
     Ok(TickInfoResponse {
@@ -186,16 +208,25 @@ impl<'a> PairContract<'a> {
     return Ok(WithdrawCalculationResponse { assets })
   }
 
-  fn swap_simulation(&self, deps: Deps, asset: Asset) -> StdResult<SimulationResponse> {
+  fn swap_simulation(
+    &self,
+    deps: Deps,
+    asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  ) -> StdResult<SimulationResponse> {
     let price_sqrt = self.current_price_sqrt.load(deps.storage)?;
     let tick_index = self.current_tick_index.load(deps.storage)?;
-    let config = self.config.load(deps.storage)?;
+    let mut config = self.config.load(deps.storage)?;
+    config.fee_rate = self.trader_fee_rate(deps, config.fee_rate, trader)?;
+    let max_swap_ticks = self.load_max_swap_ticks(deps.storage)?;
 
     let mut remain = asset.amount.clone();
     let mut tick_index_temp = tick_index.clone();
     let mut price_sqrt_temp = price_sqrt.clone();
     let mut total_return_amount = Uint128::zero();
     let mut total_commission_amount = Uint128::zero();
+    let mut steps: Vec<SwapStepResponse> = vec![];
 
     let offer_token: TokenNumber;
 
@@ -210,6 +241,15 @@ impl<'a> PairContract<'a> {
     }
     
     while remain > Uint128::zero() {
+      // the swap fails with the same amount
+      if steps.len() as u32 >= max_swap_ticks {
+        return Err(StdError::generic_err(ContractError::MaxTicksReached {
+          max_ticks: max_swap_ticks,
+          offer_amount: asset.amount - remain,
+          return_amount: total_return_amount - total_commission_amount,
+        }.to_string()))
+      }
+
       let tick_data = match self.tick_data.may_load(deps.storage, NewInt32Key::from(tick_index_temp))? {
         Some(tick_data) => tick_data,
         None => return Err(StdError::generic_err("Can't swap"))
@@ -220,6 +260,16 @@ impl<'a> PairContract<'a> {
       let (offer_amount_, return_amount, commission_amount, next_price_sqrt, next_tick_index) 
         = compute_swap_tick(tick_index_temp, config.tick_space, price_sqrt_temp, tick_data.total_liquidity, &offer_token, remain, config.fee_rate);
 
+      steps.push(SwapStepResponse {
+        tick_index: tick_index_temp,
+        liquidity: tick_data.total_liquidity,
+        offer_amount: offer_amount_,
+        return_amount: return_amount - commission_amount,
+        commission_amount,
+        price_sqrt_before: price_sqrt_temp,
+        price_sqrt_after: next_price_sqrt,
+      });
+
       // update
       remain = remain - offer_amount_; 
       total_return_amount = total_return_amount + return_amount;
@@ -230,43 +280,80 @@ impl<'a> PairContract<'a> {
 
     Ok(SimulationResponse {
       return_amount: total_return_amount - total_commission_amount,
-      commission_amount: total_commission_amount
+      commission_amount: total_commission_amount,
+      detail: self.simulation_detail(detailed, steps, tick_index_temp, price_sqrt_temp, config.asset_decimals)?,
     })
   }
 
-  fn swap_simulation_reverse(&self, deps: Deps, asset: Asset) -> StdResult<ReverseSimulationResponse> {
+  fn swap_simulation_reverse(
+    &self,
+    deps: Deps,
+    asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  ) -> StdResult<ReverseSimulationResponse> {
     let price_sqrt = self.current_price_sqrt.load(deps.storage)?;
     let tick_index = self.current_tick_index.load(deps.storage)?;
-    let config = self.config.load(deps.storage)?;
+    let mut config = self.config.load(deps.storage)?;
+    config.fee_rate = self.trader_fee_rate(deps, config.fee_rate, trader)?;
+    let max_swap_ticks = self.load_max_swap_ticks(deps.storage)?;
 
     let mut remain = asset.amount.clone();
     let mut tick_index_temp = tick_index.clone();
     let mut price_sqrt_temp = price_sqrt.clone();
     let mut total_offer_amount = Uint128::zero();
     let mut total_commission_amount = Uint128::zero();
+    let mut steps: Vec<SwapStepResponse> = vec![];
 
     let return_token: TokenNumber;
+    let offer_token: TokenNumber;
 
     let asset_infos: [AssetInfo; 2] = config.asset_infos;
 
     if asset.info.equal(&asset_infos[0]) {
       return_token = TokenNumber::Token0;
+      offer_token = TokenNumber::Token1;
     } else if asset.info.equal(&asset_infos[1]){
       return_token = TokenNumber::Token1;
+      offer_token = TokenNumber::Token0;
     } else {
       return Err(StdError::generic_err("Token missmatched"));
     }
     
     while remain > Uint128::zero() {
+      // the swap of the offer amount needs more ticks
+      if steps.len() as u32 >= max_swap_ticks {
+        return Err(StdError::generic_err(ContractError::MaxTicksReached {
+          max_ticks: max_swap_ticks,
+          offer_amount: total_offer_amount,
+          return_amount: asset.amount - remain,
+        }.to_string()))
+      }
+
       let tick_data = match self.tick_data.may_load(deps.storage, NewInt32Key::from(tick_index_temp))? {
         Some(tick_data) => tick_data,
         None => return Err(StdError::generic_err("Can't swap"))
       };
 
       // compute swap
-      let (offer_amount_, return_amount, commission_amount, next_price_sqrt, next_tick_index) 
+      let (offer_amount_, return_amount, commission_amount, mut next_price_sqrt, next_tick_index) 
         = compute_swap_tick_reverse(tick_index_temp, config.tick_space, price_sqrt_temp, tick_data.total_liquidity, &return_token, remain, config.fee_rate);
 
+      // the last step doesn't compute the next price
+      if next_tick_index == tick_index_temp {
+        next_price_sqrt = compute_price(price_sqrt_temp, tick_data.total_liquidity, offer_amount_, &offer_token);
+      }
+
+      steps.push(SwapStepResponse {
+        tick_index: tick_index_temp,
+        liquidity: tick_data.total_liquidity,
+        offer_amount: offer_amount_,
+        return_amount: return_amount - commission_amount,
+        commission_amount,
+        price_sqrt_before: price_sqrt_temp,
+        price_sqrt_after: next_price_sqrt,
+      });
+
       // update
       remain = remain + commission_amount - return_amount; 
       total_offer_amount = total_offer_amount + offer_amount_;
@@ -277,10 +364,226 @@ impl<'a> PairContract<'a> {
 
     Ok(ReverseSimulationResponse {
       offer_amount: total_offer_amount,
-      commission_amount: total_commission_amount
+      commission_amount: total_commission_amount,
+      detail: self.simulation_detail(detailed, steps, tick_index_temp, price_sqrt_temp, config.asset_decimals)?,
+    })
+  }
+
+  /// fee discount of the trader in the factory, pairs without the factory have no discount
+  pub fn fee_discount(&self, deps: Deps, trader: &Addr) -> StdResult<Decimal> {
+    match self.factory.may_load(deps.storage)? {
+      Some(factory) => {
+        let res: FeeDiscountResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+          contract_addr: factory.to_string(),
+          msg: to_binary(&FactoryQueryMsg::FeeDiscount { address: trader.to_string() })?,
+        }))?;
+        Ok(res.discount)
+      },
+      None => Ok(Decimal::zero()),
+    }
+  }
+
+  fn trader_fee_rate(&self, deps: Deps, fee_rate: Decimal, trader: Option<String>) -> StdResult<Decimal> {
+    match trader {
+      Some(trader) => {
+        let trader = deps.api.addr_validate(&trader)?;
+        Ok(discount_fee_rate(fee_rate, self.fee_discount(deps, &trader)?))
+      },
+      None => Ok(fee_rate),
+    }
+  }
+
+  fn effective_fee(&self, deps: Deps, trader: String) -> StdResult<EffectiveFeeResponse> {
+    let config = self.config.load(deps.storage)?;
+    let discount = self.fee_discount(deps, &deps.api.addr_validate(&trader)?)?;
+
+    Ok(EffectiveFeeResponse {
+      fee_rate: config.fee_rate,
+      discount,
+      effective_fee_rate: discount_fee_rate(config.fee_rate, discount),
+    })
+  }
+
+  fn simulation_detail(
+    &self,
+    detailed: Option<bool>,
+    steps: Vec<SwapStepResponse>,
+    current_tick_index: i32,
+    current_price_sqrt: Uint256,
+    asset_decimals: Option<[u8; 2]>,
+  ) -> StdResult<Option<SimulationDetail>> {
+    if !detailed.unwrap_or(false) {
+      return Ok(None);
+    }
+
+    Ok(Some(SimulationDetail {
+      steps,
+      current_tick_index,
+      current_price_sqrt,
+      price: match asset_decimals {
+        Some(decimals) => price_sqrt_to_decimal_price(current_price_sqrt, decimals)?,
+        None => price_sqrt_to_price(current_price_sqrt),
+      },
+    }))
+  }
+
+  fn price_to_tick_index(
+    &self,
+    deps: Deps,
+    price: Decimal256,
+    rounding: Rounding,
+    decimals: Option<[u8; 2]>,
+  ) -> StdResult<PriceToTickIndexResponse> {
+    let config = self.config.load(deps.storage)?;
+    let decimals = decimals.or(config.asset_decimals).unwrap_or([0, 0]);
+    let price_sqrt = price_to_price_sqrt(price, decimals)?;
+
+    if price_sqrt < get_tick_price_sqrt(MIN_TICK) || price_sqrt >= get_tick_price_sqrt(MAX_TICK + 1i32) {
+      return Err(StdError::generic_err("Invalid price"));
+    }
+
+    // tick index that contains the price
+    let tick_index = tick_to_tick_index(get_tick_from_price_sqrt(price_sqrt), config.tick_space);
+    let lower_price_sqrt = get_tick_price_sqrt(tick_index_to_tick(tick_index, config.tick_space));
+    let upper_price_sqrt = get_tick_price_sqrt(tick_index_to_tick(tick_index + 1, config.tick_space));
+
+    let tick_index = match rounding {
+      Rounding::Down => tick_index,
+      Rounding::Up if price_sqrt == lower_price_sqrt => tick_index,
+      Rounding::Up => tick_index + 1,
+      Rounding::Nearest if price_sqrt - lower_price_sqrt <= upper_price_sqrt - price_sqrt => tick_index,
+      Rounding::Nearest => tick_index + 1,
+    };
+
+    let (price_sqrt, _) = self.tick_index_price_sqrt_range(config.tick_space, tick_index)?;
+
+    Ok(PriceToTickIndexResponse {
+      tick_index,
+      price: price_sqrt_to_decimal_price(price_sqrt, decimals)?,
+      price_sqrt,
     })
   }
 
+  fn tick_index_to_price_range(
+    &self,
+    deps: Deps,
+    tick_index: i32,
+    decimals: Option<[u8; 2]>,
+  ) -> StdResult<TickIndexToPriceRangeResponse> {
+    let config = self.config.load(deps.storage)?;
+    let decimals = decimals.or(config.asset_decimals).unwrap_or([0, 0]);
+    let (lower_price_sqrt, upper_price_sqrt) = self.tick_index_price_sqrt_range(config.tick_space, tick_index)?;
+
+    Ok(TickIndexToPriceRangeResponse {
+      tick_index,
+      lower_price: price_sqrt_to_decimal_price(lower_price_sqrt, decimals)?,
+      upper_price: price_sqrt_to_decimal_price(upper_price_sqrt, decimals)?,
+      lower_price_sqrt,
+      upper_price_sqrt,
+    })
+  }
+
+  fn amount_to_price(
+    &self,
+    deps: Deps,
+    target_price: Decimal256,
+    decimals: Option<[u8; 2]>,
+    detailed: Option<bool>,
+  ) -> StdResult<AmountToPriceResponse> {
+    let config = self.config.load(deps.storage)?;
+    let target_price_sqrt = price_to_price_sqrt(target_price, decimals.or(config.asset_decimals).unwrap_or([0, 0]))?;
+
+    if target_price_sqrt < get_tick_price_sqrt(MIN_TICK) || target_price_sqrt >= get_tick_price_sqrt(MAX_TICK + 1i32) {
+      return Err(StdError::generic_err("Invalid price"));
+    }
+
+    let mut price_sqrt = self.current_price_sqrt.load(deps.storage)?;
+    let mut tick_index = self.current_tick_index.load(deps.storage)?;
+
+    // sell asset0 to lower the price, buy asset0 to raise the price
+    let (offer_token, offer_asset_info) = if target_price_sqrt < price_sqrt {
+      (TokenNumber::Token0, config.asset_infos[0].clone())
+    } else {
+      (TokenNumber::Token1, config.asset_infos[1].clone())
+    };
+
+    let mut total_offer_amount = Uint128::zero();
+    let mut total_return_amount = Uint128::zero();
+    let mut total_commission_amount = Uint128::zero();
+    let mut steps: Vec<SwapStepResponse> = vec![];
+
+    while price_sqrt != target_price_sqrt {
+      let tick_data = match self.tick_data.may_load(deps.storage, NewInt32Key::from(tick_index))? {
+        Some(tick_data) => tick_data,
+        None => return Err(StdError::generic_err("Can't swap"))
+      };
+
+      if tick_data.total_liquidity.is_zero() {
+        return Err(StdError::generic_err("Can't swap"));
+      }
+
+      let (offer_amount, return_amount, commission_amount, next_price_sqrt, next_tick_index) = compute_swap_tick_to_price(
+        tick_index, config.tick_space, price_sqrt, target_price_sqrt, tick_data.total_liquidity, &offer_token, config.fee_rate
+      );
+
+      total_offer_amount += offer_amount;
+      total_return_amount += return_amount;
+      total_commission_amount += commission_amount;
+      steps.push(SwapStepResponse {
+        tick_index,
+        liquidity: tick_data.total_liquidity,
+        offer_amount,
+        return_amount: return_amount - commission_amount,
+        commission_amount,
+        price_sqrt_before: price_sqrt,
+        price_sqrt_after: next_price_sqrt,
+      });
+
+      price_sqrt = next_price_sqrt;
+
+      // target price is in the tick
+      if next_tick_index == tick_index {
+        break;
+      }
+      tick_index = next_tick_index;
+    }
+
+    Ok(AmountToPriceResponse {
+      offer_asset: Asset {
+        info: offer_asset_info,
+        amount: total_offer_amount,
+      },
+      return_amount: total_return_amount - total_commission_amount,
+      commission_amount: total_commission_amount,
+      price_sqrt,
+      steps: if detailed.unwrap_or(false) { Some(steps) } else { None },
+    })
+  }
+
+  // (lower, upper) price_sqrt of the tick index, error if the range is out of the ticks
+  fn tick_index_price_sqrt_range(&self, tick_space: u16, tick_index: i32) -> StdResult<(Uint256, Uint256)> {
+    let lower_tick = i64::from(tick_index) * i64::from(tick_space);
+    let upper_tick = (i64::from(tick_index) + 1) * i64::from(tick_space);
+    if lower_tick < i64::from(MIN_TICK) || upper_tick > i64::from(MAX_TICK) + 1 {
+      return Err(StdError::generic_err("Invalid tick index"));
+    }
+
+    Ok((get_tick_price_sqrt(lower_tick as i32), get_tick_price_sqrt(upper_tick as i32)))
+  }
+
+  fn history(&self, deps: Deps, period: HistoryPeriod, start_after: Option<u64>, limit: Option<u32>) -> StdResult<HistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|start_after| Bound::exclusive(U64Key::new(start_after)));
+
+    let infos: StdResult<Vec<_>> = self.history_map(period)
+      .range(deps.storage, start, None, Order::Ascending)
+      .take(limit)
+      .map(|item| item.map(|(_, v)| v))
+      .collect();
+
+    Ok(HistoryResponse { infos: infos? })
+  }
+
   pub fn get_liquidity_info(&self, querier: QuerierWrapper, lp_contract: String, token_id: String) -> StdResult<LiquidityInfoResponse> {
     Ok(querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
       contract_addr: lp_contract,
@@ -293,6 +596,7 @@ impl<'a> PairContract<'a> {
   pub fn query(&self, deps: Deps, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
       QueryMsg::PairInfo {} => to_binary(&self.pair_info(deps)?),
+      QueryMsg::Config {} => to_binary(&self.config(deps)?),
       QueryMsg::TickInfo { tick_index } => to_binary(&self.tick_info(deps, tick_index)?), 
       QueryMsg::TickInfos { start_after, limit } => {
         to_binary(&self.tick_infos(deps, start_after, limit)?)
@@ -301,9 +605,20 @@ impl<'a> PairContract<'a> {
         => to_binary(&self.provide_calculation(deps, asset, upper_tick_index, lower_tick_index)?),
       QueryMsg::WithdrawCalculation { token_id } 
         => to_binary(&self.withdraw_calculation(deps, token_id)?),
-      QueryMsg::Simulation { offer_asset } => to_binary(&self.swap_simulation(deps, offer_asset)?),
-      QueryMsg::ReverseSimulation { ask_asset } => to_binary(&self.swap_simulation_reverse(deps, ask_asset)?),
+      QueryMsg::Simulation { offer_asset, detailed, trader }
+        => to_binary(&self.swap_simulation(deps, offer_asset, detailed, trader)?),
+      QueryMsg::ReverseSimulation { ask_asset, detailed, trader }
+        => to_binary(&self.swap_simulation_reverse(deps, ask_asset, detailed, trader)?),
       QueryMsg::CumulativeVolume {} => to_binary(&self.cumulative_volume.load(deps.storage)?),
+      QueryMsg::History { period, start_after, limit }
+        => to_binary(&self.history(deps, period, start_after, limit)?),
+      QueryMsg::PriceToTickIndex { price, rounding, decimals }
+        => to_binary(&self.price_to_tick_index(deps, price, rounding, decimals)?),
+      QueryMsg::TickIndexToPriceRange { tick_index, decimals }
+        => to_binary(&self.tick_index_to_price_range(deps, tick_index, decimals)?),
+      QueryMsg::AmountToPrice { target_price, decimals, detailed }
+        => to_binary(&self.amount_to_price(deps, target_price, decimals, detailed)?),
+      QueryMsg::EffectiveFee { trader } => to_binary(&self.effective_fee(deps, trader)?),
     }
   }
 }
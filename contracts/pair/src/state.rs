@@ -1,10 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::{Map, Item};
-use cosmwasm_std::{Uint128, Uint256, Decimal, Addr};
+use cw_storage_plus::{Map, Item, U64Key};
+use cosmwasm_std::{Uint128, Uint256, Decimal, Addr, StdResult, Storage};
 
-use wineswap::pair::TickInfo;
+use wineswap::pair::{HistoryConfig, HistoryInfo, HistoryPeriod, TickInfo};
 use wineswap::asset::AssetInfo;
 use wineswap::new_int_key::NewInt32Key;
 
@@ -15,14 +15,34 @@ pub struct PairContract<'a> {
   // price = Asset0 price as Asset1, Q128.128
   pub current_price_sqrt: Item<'a, Uint256>,
   pub cumulative_volume: Item<'a, [Uint128; 2]>,
+  pub history_config: Item<'a, HistoryConfig>,
+  // key: start time of the period
+  pub hourly_history: Map<'a, U64Key, HistoryInfo>,
+  pub daily_history: Map<'a, U64Key, HistoryInfo>,
+  // not stored on pairs instantiated before the max swap ticks
+  pub factory: Item<'a, Addr>,
+  pub max_swap_ticks: Item<'a, u32>,
 }
 
+pub const DEFAULT_MAX_SWAP_TICKS: u32 = 100;
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
   pub asset_infos: [AssetInfo; 2],
   pub tick_space: u16,
   pub fee_rate: Decimal,
   pub liquidity_token: Addr,
+  /// not stored on pairs instantiated before it
+  #[serde(default)]
+  pub asset_decimals: Option<[u8; 2]>,
+}
+
+/// fee growth format before Q128.128, only used for migration
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyTickInfo {
+  pub last_fee_growth_0: Decimal,
+  pub last_fee_growth_1: Decimal,
+  pub total_liquidity: Uint128,
 }
 
 impl Default for PairContract<'static> {
@@ -33,17 +53,29 @@ impl Default for PairContract<'static> {
       "current_tick",
       "current_price_sqrt",
       "cumulative_volume",
+      "history_config",
+      "hourly_history",
+      "daily_history",
+      "factory",
+      "max_swap_ticks",
     )
   }
 }
 
 impl<'a> PairContract<'a> {
+  // one storage key per item
+  #[allow(clippy::too_many_arguments)]
   fn new(
     config_key: &'a str,
     tick_data_key: &'a str,
     current_tick_key: &'a str,
     current_price_sqrt_key: &'a str,
     cumulative_volume_key: &'a str,
+    history_config_key: &'a str,
+    hourly_history_key: &'a str,
+    daily_history_key: &'a str,
+    factory_key: &'a str,
+    max_swap_ticks_key: &'a str,
   ) -> Self {
     Self {
       config: Item::new(config_key),
@@ -51,6 +83,22 @@ impl<'a> PairContract<'a> {
       current_tick_index: Item::new(current_tick_key),
       current_price_sqrt: Item::new(current_price_sqrt_key),
       cumulative_volume: Item::new(cumulative_volume_key),
+      history_config: Item::new(history_config_key),
+      hourly_history: Map::new(hourly_history_key),
+      daily_history: Map::new(daily_history_key),
+      factory: Item::new(factory_key),
+      max_swap_ticks: Item::new(max_swap_ticks_key),
+    }
+  }
+
+  pub fn load_max_swap_ticks(&self, storage: &dyn Storage) -> StdResult<u32> {
+    Ok(self.max_swap_ticks.may_load(storage)?.unwrap_or(DEFAULT_MAX_SWAP_TICKS))
+  }
+
+  pub fn history_map(&self, period: HistoryPeriod) -> &Map<'a, U64Key, HistoryInfo> {
+    match period {
+      HistoryPeriod::Hourly => &self.hourly_history,
+      HistoryPeriod::Daily => &self.daily_history,
     }
   }
 }
\ No newline at end of file
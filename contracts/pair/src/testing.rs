@@ -1,20 +1,38 @@
-use cosmwasm_std::{to_binary, Addr, Decimal, CosmosMsg, SubMsg, WasmMsg, Uint128, Coin, ReplyOn};
-use cosmwasm_std::testing::{mock_env, mock_info};
-use wineswap::pair::{InstantiateMsg, ExecuteMsg, Cw20HookMsg, TickIndexes};
+use cosmwasm_std::{attr, to_binary, from_binary, Addr, Order, Decimal, Decimal256, CosmosMsg, Event, SubMsg, WasmMsg, Uint128, Uint256, Coin, ReplyOn,
+  StdError, StdResult, Timestamp};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use wineswap::pair::{InstantiateMsg, ExecuteMsg, MigrateMsg, Cw20HookMsg, TickIndexes, HistoryConfig, HistoryPeriod,
+  HistoryResponse, QueryMsg, PriceToTickIndexResponse, Rounding, TickIndexToPriceRangeResponse, AmountToPriceResponse,
+  SimulationResponse, ReverseSimulationResponse, ConfigResponse, Referral, EffectiveFeeResponse};
 use wineswap::lp_token::{InstantiateMsg as TokenInstantiateMsg, ExecuteMsg as TokenExecuteMsg, LiquidityInfoResponse};
 use wineswap::asset::{Asset, AssetInfo, TokenNumber};
+use wineswap::new_int_key::NewInt32Key;
+use wineswap::event::{TickLiquidityEvent, TickSwapEvent};
 
+use cw0::Expiration;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use wineswap_math::{
   liquidity::{get_token_amount_from_liquidity, compute_liquidity},
-  tick::DENOMINATOR,
-  swap::{compute_swap_tick}
+  tick::{get_tick_price_sqrt, DENOMINATOR},
+  swap::{compute_swap_tick},
+  fee::{compute_fee_growth, compute_reward, fee_growth_add, fee_growth_sub},
+  price::{price_sqrt_to_decimal_price, price_sqrt_to_price},
 };
-use crate::state::PairContract;
+use cw_storage_plus::Map;
+use wineswap_simulator::Pool;
+use crate::state::{LegacyTickInfo, PairContract};
 use crate::mock_querier::mock_dependencies;
 use crate::error::ContractError;
 
+// sum of the fee growth of the ticks in the range, same with the reward of the lp token
+fn range_fee_growth(pair: &PairContract, storage: &dyn cosmwasm_std::Storage, lower: i32, upper: i32) -> [Uint256; 2] {
+  (lower..(upper + 1)).fold([Uint256::zero(), Uint256::zero()], |sum, i| {
+    let tick = pair.tick_data.load(storage, NewInt32Key::new(i)).unwrap();
+    [fee_growth_add(sum[0], tick.last_fee_growth_0), fee_growth_add(sum[1], tick.last_fee_growth_1)]
+  })
+}
+
 #[test]
 fn instantiate_test() {
   let pair = PairContract::default();
@@ -29,7 +47,10 @@ fn instantiate_test() {
     fee_rate: Decimal::from_ratio(1u128, 100u128),
     token_code_id: 123,
     tick_space: 100,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -40,7 +61,7 @@ fn instantiate_test() {
     res.messages,
     vec![SubMsg {
       msg: WasmMsg::Instantiate {
-        admin: None,
+        admin: Some("factory".to_string()),
         code_id: 123u64,
         label: "".to_string(),
         funds: vec![],
@@ -81,7 +102,10 @@ fn instantiate_test() {
     fee_rate: Decimal::from_ratio(1u128, 100u128),
     token_code_id: 123,
     tick_space: 0,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -102,7 +126,10 @@ fn instantiate_test() {
     fee_rate: Decimal::from_ratio(1000u128, 100u128),
     token_code_id: 123,
     tick_space: 0,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -130,7 +157,10 @@ fn provide_withdraw_test() {
     fee_rate: Decimal::from_ratio(1u128, 100u128),
     token_code_id: 123,
     tick_space: 100,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -172,6 +202,17 @@ fn provide_withdraw_test() {
 
   let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), provide_msg).unwrap();
 
+  // liquidity event for every tick in the range
+  assert_eq!(
+    res.events,
+    (-10..11).map(|tick_index| TickLiquidityEvent {
+      tick_index,
+      liquidity_delta: liquidity,
+      add: true,
+      liquidity,
+    }.into()).collect::<Vec<Event>>()
+  );
+
   deps.querier.with_lp_infos(&[
     (&"0".to_string(), &LiquidityInfoResponse{
       approvals: vec![],
@@ -371,7 +412,9 @@ fn provide_withdraw_test() {
 
   let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
     token_id: "0".to_string(),
-    amount: None
+    amount: None,
+    receive_asset: None,
+    minimum_receive: None,
   };
 
   // try withdraw who is not the owner of the liqudity
@@ -386,7 +429,9 @@ fn provide_withdraw_test() {
   // partial withdraw
   let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
     token_id: "0".to_string(),
-    amount: Some(liquidity)
+    amount: Some(liquidity),
+    receive_asset: None,
+    minimum_receive: None,
   };
 
   let info = mock_info("user", &[]);
@@ -429,6 +474,17 @@ fn provide_withdraw_test() {
     ]
   );
 
+  assert_eq!(res.events.len(), 21);
+  assert_eq!(
+    res.events[0],
+    TickLiquidityEvent {
+      tick_index: -10,
+      liquidity_delta: liquidity,
+      add: false,
+      liquidity,
+    }.into()
+  );
+
   deps.querier.with_lp_infos(&[
     (&"0".to_string(), &LiquidityInfoResponse{
       approvals: vec![],
@@ -441,7 +497,9 @@ fn provide_withdraw_test() {
 
   let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
     token_id: "0".to_string(),
-    amount: None
+    amount: None,
+    receive_asset: None,
+    minimum_receive: None,
   };
   let info = mock_info("user", &[]);
   let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), withdraw_msg).unwrap();
@@ -469,6 +527,108 @@ fn provide_withdraw_test() {
   );
 }
 
+#[test]
+fn provide_cw20_hook_test() {
+  // instantiate
+  let pair = PairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::Token { contract_addr: "soju".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(1u128, 100u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let info = mock_info("factory", &[]);
+  let env = mock_env();
+  let _res = pair.instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  // send more wine than needed
+  let provide_msg = ExecuteMsg::Receive(
+    Cw20ReceiveMsg {
+      sender: "user".to_string(),
+      amount: Uint128::from(2000000u128),
+      msg: to_binary(&Cw20HookMsg::ProvideLiquidity {
+        other_asset: Asset {
+          info: AssetInfo::Token {contract_addr: "soju".to_string()},
+          amount: Uint128::from(1000000u128)
+        },
+        token_id: None,
+        tick_indexes: Some(TickIndexes {
+          upper_tick_index: 10,
+          lower_tick_index: -10,
+        }),
+      }).unwrap()
+    }
+  );
+
+  // calculate amount
+  let liquidity = compute_liquidity(Uint128::from(2000000u128), Uint128::from(1000000u128), DENOMINATOR, 10, -10, 100);
+  let (amount0, amount1) = get_token_amount_from_liquidity(10, -10, 100, DENOMINATOR, liquidity);
+
+  let info = mock_info("wine", &[]);
+  let res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg.clone()).unwrap();
+
+  // refund wine
+  let return_wine = Asset {
+    info: AssetInfo::Token {contract_addr: "wine".to_string()},
+    amount: Uint128::from(2000000u128) - amount0
+  };
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(return_wine.into_msg(&deps.as_mut().querier, Addr::unchecked("user")).unwrap()),
+      // tranfer from soju
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "soju".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+          owner: "user".to_string(),
+          recipient: env.contract.address.to_string(),
+          amount: amount1,
+        }).unwrap(),
+        funds: vec![],
+      })),
+      // mint
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::Mint {
+          owner: "user".to_string(),
+          liquidity,
+          upper_tick_index: 10,
+          lower_tick_index: -10
+        }).unwrap(),
+        funds: vec![],
+      }))
+    ]
+  );
+
+  let tick = pair.tick_data.load(&deps.storage, NewInt32Key::new(0)).unwrap();
+  assert_eq!(tick.total_liquidity, liquidity);
+
+  // try to provide via hook who is not the asset token
+  let info = mock_info("makgeolli", &[]);
+  let res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg);
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+}
+
 #[test]
 fn swap_test() {
   // instantiate
@@ -484,7 +644,10 @@ fn swap_test() {
     fee_rate: Decimal::from_ratio(1u128, 100u128),
     token_code_id: 123,
     tick_space: 100,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -533,6 +696,9 @@ fn swap_test() {
     },
     belief_price: None,
     max_slippage: None,
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
     to: None
   };
 
@@ -540,7 +706,7 @@ fn swap_test() {
 
   let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), swap_msg).unwrap();
 
-  let (_, return_amount, commission_amount, _, _) = compute_swap_tick(
+  let (offer_amount, return_amount, commission_amount, next_price_sqrt, _) = compute_swap_tick(
     0, 100, DENOMINATOR, liquidity, &TokenNumber::Token1, Uint128::from(50000u128), Decimal::from_ratio(1u128, 100u128)
   );
 
@@ -556,6 +722,30 @@ fn swap_test() {
     ]
   );
 
+  // commission is accumulated to token0 fee growth
+  let tick = pair.tick_data.load(&deps.storage, NewInt32Key::new(0)).unwrap();
+  assert_eq!(tick.last_fee_growth_0, compute_fee_growth(commission_amount, liquidity));
+  assert_eq!(tick.last_fee_growth_1, Uint256::zero());
+
+  // swap is done in a single tick
+  assert_eq!(
+    res.events,
+    vec![
+      TickSwapEvent {
+        tick_index: 0,
+        liquidity,
+        offer_amount,
+        return_amount,
+        commission_amount,
+        fee_growth_delta: [compute_fee_growth(commission_amount, liquidity), Uint256::zero()],
+        price_sqrt_before: DENOMINATOR,
+        price_sqrt_after: next_price_sqrt,
+        price_before: price_sqrt_to_price(DENOMINATOR),
+        price_after: price_sqrt_to_price(next_price_sqrt),
+      }.into()
+    ]
+  );
+
   // swap test2 (opposite direction) and to test
 
   let swap_msg = ExecuteMsg::Receive(
@@ -565,6 +755,9 @@ fn swap_test() {
       msg: to_binary(&Cw20HookMsg::Swap {
         belief_price: None,
         max_slippage: None,
+        max_ticks: None,
+        partial_fill: None,
+        referral: None,
         to: Some("user2".to_string())
       }).unwrap()
     }
@@ -601,6 +794,9 @@ fn swap_test() {
     // fail due to belief_price is too low
     belief_price: Some(Decimal::from_ratio(1u128, 10u128)),
     max_slippage: Some(Decimal::from_ratio(1u128, 10u128)),
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
     to: None
   };
 
@@ -620,6 +816,9 @@ fn swap_test() {
     },
     belief_price: Some(Decimal::from_ratio(1u128, 1u128)),
     max_slippage: Some(Decimal::from_ratio(1u128, 10u128)),
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
     to: None
   };
 
@@ -641,6 +840,9 @@ fn swap_test() {
     },
     belief_price: Some(Decimal::from_ratio(1u128, 1u128)),
     max_slippage: Some(Decimal::from_ratio(1u128, 10u128)),
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
     to: None
   };
 
@@ -661,6 +863,9 @@ fn swap_test() {
     },
     belief_price: Some(Decimal::from_ratio(1u128, 1u128)),
     max_slippage: Some(Decimal::from_ratio(1u128, 10u128)),
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
     to: None
   };
 
@@ -674,6 +879,244 @@ fn swap_test() {
   }
 }
 
+#[test]
+fn history_test() {
+  // instantiate
+  let pair = PairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(1u128, 100u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: Some(HistoryConfig {
+      hourly_size: 2,
+      daily_size: 2,
+    }),
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let info = mock_info("factory", &[]);
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes {
+      upper_tick_index: 10,
+      lower_tick_index: -10,
+    }),
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "wine".to_string()},
+        amount: Uint128::from(1000000u128)
+      }
+    ]
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000u128)}]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+
+  let swap_msg = ExecuteMsg::Swap{
+    offer_asset: Asset{
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: Uint128::from(1000u128)
+    },
+    belief_price: None,
+    max_slippage: None,
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
+    to: None
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000u128)}]);
+
+  // two swaps in the same hour
+  let mut env = mock_env();
+  env.block.time = Timestamp::from_seconds(86400 * 10 + 10);
+  let _res = pair.execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+  env.block.time = Timestamp::from_seconds(86400 * 10 + 3599);
+  let _res = pair.execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+
+  let (_, return_amount, commission_amount, _, _) = compute_swap_tick(
+    0, 100, DENOMINATOR, pair.tick_data.load(&deps.storage, NewInt32Key::new(0)).unwrap().total_liquidity,
+    &TokenNumber::Token1, Uint128::from(1000u128), Decimal::from_ratio(1u128, 100u128)
+  );
+
+  let res: HistoryResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::History {
+    period: HistoryPeriod::Hourly,
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+
+  assert_eq!(res.infos.len(), 1);
+  let history = &res.infos[0];
+  assert_eq!(history.start_time, 86400 * 10);
+  assert_eq!(history.swap_count, 2);
+  assert_eq!(history.volume[1], Uint128::from(2000u128));
+  // price moves a little between two swaps
+  assert!(history.volume[0] <= return_amount * Uint128::from(2u128));
+  assert!(history.commission[0] >= commission_amount);
+  assert_eq!(history.commission[1], Uint128::zero());
+  assert_eq!(history.open_tick_index, 0);
+  assert_eq!(history.close_tick_index, pair.current_tick_index.load(&deps.storage).unwrap());
+
+  // next hours, the oldest bucket is pruned
+  env.block.time = Timestamp::from_seconds(86400 * 10 + 3600);
+  let _res = pair.execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+  env.block.time = Timestamp::from_seconds(86400 * 10 + 3600 * 2);
+  let _res = pair.execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+
+  let res: HistoryResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::History {
+    period: HistoryPeriod::Hourly,
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+
+  assert_eq!(
+    res.infos.iter().map(|info| info.start_time).collect::<Vec<u64>>(),
+    vec![86400 * 10 + 3600, 86400 * 10 + 3600 * 2]
+  );
+
+  // pagination
+  let res: HistoryResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::History {
+    period: HistoryPeriod::Hourly,
+    start_after: Some(86400 * 10 + 3600),
+    limit: Some(1),
+  }).unwrap()).unwrap();
+
+  assert_eq!(res.infos.len(), 1);
+  assert_eq!(res.infos[0].start_time, 86400 * 10 + 3600 * 2);
+
+  // all swaps are in the same day
+  let res: HistoryResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::History {
+    period: HistoryPeriod::Daily,
+    start_after: None,
+    limit: None,
+  }).unwrap()).unwrap();
+
+  assert_eq!(res.infos.len(), 1);
+  assert_eq!(res.infos[0].start_time, 86400 * 10);
+  assert_eq!(res.infos[0].swap_count, 4);
+  assert_eq!(res.infos[0].volume[1], Uint128::from(4000u128));
+}
+
+#[test]
+fn simulator_test() {
+  // the same actions on the contract and the simulator make the same state
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::from_ratio(3u128, 2u128),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let info = mock_info("factory", &[]);
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+  let mut pool = Pool::new(Decimal::from_ratio(3u128, 2u128), 10, Decimal::from_ratio(3u128, 1000u128)).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  for (lower_tick_index, upper_tick_index) in [(300, 500), (380, 430)] {
+    let amounts = [Uint128::from(1000000u128), Uint128::from(1500000u128)];
+    let provide_msg = ExecuteMsg::ProvideLiquidity {
+      token_id: None,
+      tick_indexes: Some(TickIndexes { upper_tick_index, lower_tick_index }),
+      assets: [
+        Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: amounts[0] },
+        Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: amounts[1] },
+      ]
+    };
+    let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: amounts[1] }]);
+    let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+    let _res = pool.provide(None, Some(TickIndexes { upper_tick_index, lower_tick_index }), amounts).unwrap();
+  }
+
+  // swaps crossing ticks
+  for (offer_token, amount) in [
+    (TokenNumber::Token1, Uint128::from(700000u128)),
+    (TokenNumber::Token0, Uint128::from(900000u128)),
+    (TokenNumber::Token1, Uint128::from(123456u128)),
+  ] {
+    let (info, swap_msg) = match offer_token {
+      TokenNumber::Token0 => (
+        mock_info("wine", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+          sender: "user".to_string(),
+          amount,
+          msg: to_binary(&Cw20HookMsg::Swap { belief_price: None, max_slippage: None, to: None, max_ticks: None, partial_fill: None, referral: None }).unwrap()
+        })
+      ),
+      TokenNumber::Token1 => (
+        mock_info("user", &[Coin{ denom: "uusd".to_string(), amount }]),
+        ExecuteMsg::Swap {
+          offer_asset: Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount },
+          belief_price: None,
+          max_slippage: None,
+          max_ticks: None,
+          partial_fill: None,
+          referral: None,
+          to: None
+        }
+      ),
+    };
+
+    let res = pair.execute(deps.as_mut(), mock_env(), info, swap_msg).unwrap();
+    let swap = pool.swap(offer_token, amount).unwrap();
+
+    assert!(res.attributes.contains(&attr("return_amount", swap.return_amount.to_string())));
+    assert!(res.attributes.contains(&attr("commission_amount", swap.commission_amount.to_string())));
+  }
+
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), pool.current_price_sqrt);
+  assert_eq!(pair.current_tick_index.load(&deps.storage).unwrap(), pool.current_tick_index);
+
+  let ticks = pair.tick_data
+    .range(&deps.storage, None, None, Order::Ascending)
+    .map(|item| {
+      let (k, v) = item.unwrap();
+      (i32::from(NewInt32Key::from(k)), v)
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(ticks, pool.ticks.into_iter().collect::<Vec<_>>());
+}
+
 // claim test
 #[test]
 fn claim_test() {
@@ -690,7 +1133,10 @@ fn claim_test() {
     fee_rate: Decimal::from_ratio(1u128, 100u128),
     token_code_id: 123,
     tick_space: 100,
-    initial_price: Decimal::one()
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
   };
 
   let info = mock_info("factory", &[]);
@@ -782,3 +1228,1229 @@ fn claim_test() {
     _ => panic!("Must return unauthoerized error"),
   }
 }
+
+#[test]
+fn migrate_test() {
+  let pair = PairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  // tick data saved before fee growth was Q128.128
+  let legacy_tick_data: Map<NewInt32Key, LegacyTickInfo> = Map::new("tick_data");
+  legacy_tick_data.save(deps.as_mut().storage, NewInt32Key::new(-1), &LegacyTickInfo {
+    last_fee_growth_0: Decimal::one(),
+    last_fee_growth_1: Decimal::from_ratio(1u128, 4u128),
+    total_liquidity: Uint128::from(100u128),
+  }).unwrap();
+
+  let _res = pair.migrate(deps.as_mut(), mock_env(), MigrateMsg { factory: None }).unwrap();
+
+  let tick = pair.tick_data.load(&deps.storage, NewInt32Key::new(-1)).unwrap();
+  assert_eq!(tick.last_fee_growth_0, DENOMINATOR);
+  assert_eq!(tick.last_fee_growth_1, DENOMINATOR / Uint256::from(4u128));
+  assert_eq!(tick.total_liquidity, Uint128::from(100u128));
+
+  // migrating a versioned contract keeps the data
+  let res = pair.migrate(deps.as_mut(), mock_env(), MigrateMsg { factory: None }).unwrap();
+  assert_eq!(res.attributes, vec![attr("action", "migrate")]);
+  let tick = pair.tick_data.load(&deps.storage, NewInt32Key::new(-1)).unwrap();
+  assert_eq!(tick.last_fee_growth_0, DENOMINATOR);
+
+  // old pair without the factory can't change the config
+  let update_config_msg = ExecuteMsg::UpdateConfig { max_swap_ticks: Some(200), history_config: None };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("factory", &[]), update_config_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // the factory is set by the migration
+  let _res = pair.migrate(deps.as_mut(), mock_env(), MigrateMsg { factory: Some("factory".to_string()) }).unwrap();
+  let _res = pair.execute(deps.as_mut(), mock_env(), mock_info("factory", &[]), update_config_msg).unwrap();
+  assert_eq!(pair.load_max_swap_ticks(&deps.storage).unwrap(), 200);
+}
+
+#[test]
+fn zap_in_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let info = mock_info("factory", &[]);
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes {
+      upper_tick_index: 10,
+      lower_tick_index: -10,
+    }),
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "wine".to_string()},
+        amount: Uint128::from(1000000000u128)
+      }
+    ]
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000000u128)}]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+  let pool_liquidity = pair.tick_data.load(&deps.storage, NewInt32Key::new(0)).unwrap().total_liquidity;
+
+  // cw20 can't be zapped in with execute msg
+  let zap_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::Token { contract_addr: "wine".to_string() },
+      amount: Uint128::from(10000000u128),
+    },
+    tick_indexes: TickIndexes { upper_tick_index: 2, lower_tick_index: -2 },
+    min_liquidity: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), zap_msg);
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // too high min liquidity
+  let zap_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: Uint128::from(10000000u128),
+    },
+    tick_indexes: TickIndexes { upper_tick_index: 2, lower_tick_index: -2 },
+    min_liquidity: Some(Uint128::from(10000000000000u128)),
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(10000000u128)}]);
+  let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), zap_msg);
+
+  match res {
+    Err(ContractError::MinLiquidityAssertion { .. }) => assert!(true),
+    _ => panic!("Must return min liquidity assertion error"),
+  }
+
+  // uusd only, around the current price
+  let zap_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: Uint128::from(10000000u128),
+    },
+    tick_indexes: TickIndexes { upper_tick_index: 2, lower_tick_index: -2 },
+    min_liquidity: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), info, zap_msg).unwrap();
+
+  let attribute = |key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+  let swap_amount = Uint128::from(attribute("swap_amount").parse::<u128>().unwrap());
+  let return_amount = Uint128::from(attribute("return_amount").parse::<u128>().unwrap());
+  let liquidity = Uint128::from(attribute("liquidity").parse::<u128>().unwrap());
+
+  // range is tick -200 ~ 300, needs more wine than uusd. the price moved up
+  assert!(swap_amount > Uint128::from(5000000u128) && swap_amount < Uint128::from(6000000u128));
+  let current_price_sqrt = pair.current_price_sqrt.load(&deps.storage).unwrap();
+  assert!(current_price_sqrt > DENOMINATOR);
+
+  // the rest fits the range at the new price, only dust is refunded
+  let (token0_amount, token1_amount) = get_token_amount_from_liquidity(2, -2, 100, current_price_sqrt, liquidity);
+  let wine_refund = return_amount - token0_amount;
+  let uusd_refund = Uint128::from(10000000u128) - swap_amount - token1_amount;
+  // the swap amount is searched within 10 uusd (1 / 1_000_000 of the offer)
+  assert!(wine_refund + uusd_refund <= Uint128::from(20u128));
+
+  let mut messages: Vec<SubMsg> = vec![];
+  if !wine_refund.is_zero() {
+    messages.push(SubMsg::new(WasmMsg::Execute {
+      contract_addr: "wine".to_string(),
+      msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: "user".to_string(), amount: wine_refund }).unwrap(),
+      funds: vec![],
+    }));
+  }
+  if !uusd_refund.is_zero() {
+    messages.push(SubMsg::new(CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+      to_address: "user".to_string(),
+      amount: vec![Coin { denom: "uusd".to_string(), amount: uusd_refund }],
+    })));
+  }
+  messages.push(SubMsg::new(WasmMsg::Execute {
+    contract_addr: "liquidity".to_string(),
+    msg: to_binary(&TokenExecuteMsg::Mint {
+      owner: "user".to_string(),
+      liquidity,
+      upper_tick_index: 2,
+      lower_tick_index: -2,
+    }).unwrap(),
+    funds: vec![],
+  }));
+  assert_eq!(res.messages, messages);
+
+  let tick = pair.tick_data.load(&deps.storage, NewInt32Key::new(0)).unwrap();
+  assert_eq!(tick.total_liquidity, pool_liquidity + liquidity);
+
+  // wine only, range above the price needs only wine, nothing is swapped
+  let zap_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+    sender: "user".to_string(),
+    amount: Uint128::from(10000000u128),
+    msg: to_binary(&Cw20HookMsg::ZapIn {
+      tick_indexes: TickIndexes { upper_tick_index: 8, lower_tick_index: 5 },
+      min_liquidity: None,
+    }).unwrap(),
+  });
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("wine", &[]), zap_msg).unwrap();
+
+  assert!(res.attributes.contains(&attr("swap_amount", "0")));
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), current_price_sqrt);
+}
+
+#[test]
+fn zap_out_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let info = mock_info("factory", &[]);
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+  let mut pool = Pool::new(Decimal::one(), 100, Decimal::from_ratio(3u128, 1000u128)).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes {
+      upper_tick_index: 10,
+      lower_tick_index: -10,
+    }),
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "wine".to_string()},
+        amount: Uint128::from(1000000000u128)
+      }
+    ]
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000000u128)}]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+  let (position_id, liquidity, _) = pool.provide(
+    None,
+    Some(TickIndexes { upper_tick_index: 10, lower_tick_index: -10 }),
+    [Uint128::from(1000000000u128), Uint128::from(1000000000u128)]
+  ).unwrap();
+
+  deps.querier.with_lp_infos(&[
+    (&"0".to_string(), &LiquidityInfoResponse{
+      approvals: vec![],
+      liquidity,
+      upper_tick_index: 10,
+      lower_tick_index: -10,
+      owner: Addr::unchecked("user")
+    })
+  ]);
+
+  // asset not in the pair
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
+    token_id: "0".to_string(),
+    amount: Some(liquidity.multiply_ratio(1u128, 10u128)),
+    receive_asset: Some(AssetInfo::NativeToken { denom: "ukrw".to_string() }),
+    minimum_receive: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), withdraw_msg);
+
+  match res {
+    Err(ContractError::AssetMismatch {}) => assert!(true),
+    _ => panic!("Must return asset mismatch error"),
+  }
+
+  // withdraw 10% and swap the wine side to uusd
+  let withdrawn = pool.withdraw(position_id, Some(liquidity.multiply_ratio(1u128, 10u128))).unwrap();
+  let swap = pool.swap(TokenNumber::Token0, withdrawn[0]).unwrap();
+  let receive_amount = withdrawn[1] + swap.return_amount;
+  let growth_before = range_fee_growth(&pair, &deps.storage, -10, 10);
+
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
+    token_id: "0".to_string(),
+    amount: Some(liquidity.multiply_ratio(1u128, 10u128)),
+    receive_asset: Some(AssetInfo::NativeToken { denom: "uusd".to_string() }),
+    minimum_receive: Some(receive_amount),
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), withdraw_msg).unwrap();
+
+  // the reward is claimed first, then the pair swaps the withdrawn wine
+  let zap_out_msg = ExecuteMsg::ZapOut {
+    owner: "user".to_string(),
+    offer_asset: Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: withdrawn[0] },
+    receive_amount: withdrawn[1],
+    minimum_receive: Some(receive_amount),
+  };
+  assert_eq!(res.messages, vec![
+    SubMsg::new(WasmMsg::Execute {
+      contract_addr: "liquidity".to_string(),
+      msg: to_binary(&TokenExecuteMsg::ClaimReward { token_id: "0".to_string() }).unwrap(),
+      funds: vec![],
+    }),
+    SubMsg::new(WasmMsg::Execute {
+      contract_addr: "liquidity".to_string(),
+      msg: to_binary(&TokenExecuteMsg::UpdateLiquidity {
+        token_id: "0".to_string(),
+        amount: liquidity.multiply_ratio(1u128, 10u128),
+        add: false,
+      }).unwrap(),
+      funds: vec![],
+    }),
+    SubMsg::new(WasmMsg::Execute {
+      contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+      msg: to_binary(&zap_out_msg).unwrap(),
+      funds: vec![],
+    }),
+  ]);
+  // the claim sees the fee growth before the swap
+  assert_eq!(range_fee_growth(&pair, &deps.storage, -10, 10), growth_before);
+
+  // only the pair itself can swap
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), zap_out_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), zap_out_msg).unwrap();
+
+  assert!(res.attributes.contains(&attr("swap_amount", withdrawn[0].to_string())));
+  assert!(res.attributes.contains(&attr("commission_amount", swap.commission_amount.to_string())));
+
+  // only uusd is sent
+  assert_eq!(
+    res.messages[0],
+    SubMsg::new(CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+      to_address: "user".to_string(),
+      amount: vec![Coin { denom: "uusd".to_string(), amount: receive_amount }],
+    }))
+  );
+  assert!(!res.messages.iter().any(|msg| match &msg.msg {
+    CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => contract_addr == "wine",
+    _ => false,
+  }));
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), pool.current_price_sqrt);
+  assert_eq!(pair.current_tick_index.load(&deps.storage).unwrap(), pool.current_tick_index);
+
+  // the commission goes to the remaining liquidity, the withdrawn part earns nothing of it
+  let growth_after = range_fee_growth(&pair, &deps.storage, -10, 10);
+  let remaining_liquidity = liquidity - liquidity.multiply_ratio(1u128, 10u128);
+  assert_eq!(growth_after[0], growth_before[0]);
+  let reward = compute_reward(fee_growth_sub(growth_after[1], growth_before[1]), remaining_liquidity).unwrap();
+  assert!(reward <= swap.commission_amount);
+  assert!(swap.commission_amount - reward <= Uint128::from(10u128));
+
+  // position only on the receive side is sent without the swap
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes { upper_tick_index: 30, lower_tick_index: 20 }),
+    assets: [
+      Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::zero() },
+      Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: Uint128::from(1000000u128) },
+    ]
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), provide_msg).unwrap();
+  let attribute = |key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+  let one_side_liquidity = Uint128::from(attribute("liquidity").parse::<u128>().unwrap());
+
+  deps.querier.with_lp_infos(&[
+    (&"1".to_string(), &LiquidityInfoResponse{
+      approvals: vec![],
+      liquidity: one_side_liquidity,
+      upper_tick_index: 30,
+      lower_tick_index: 20,
+      owner: Addr::unchecked("user")
+    })
+  ]);
+  let hourly_history = pair.hourly_history
+    .range(&deps.storage, None, None, Order::Ascending)
+    .collect::<StdResult<Vec<_>>>()
+    .unwrap();
+
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
+    token_id: "1".to_string(),
+    amount: None,
+    receive_asset: Some(AssetInfo::Token { contract_addr: "wine".to_string() }),
+    minimum_receive: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), withdraw_msg).unwrap();
+
+  assert!(!res.messages.iter().any(|msg| match &msg.msg {
+    CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => contract_addr == MOCK_CONTRACT_ADDR,
+    _ => false,
+  }));
+  assert!(res.messages.iter().any(|msg| match &msg.msg {
+    CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => contract_addr == "wine",
+    _ => false,
+  }));
+  assert_eq!(
+    pair.hourly_history.range(&deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap(),
+    hourly_history
+  );
+
+  // zero offer doesn't swap
+  let zap_out_msg = ExecuteMsg::ZapOut {
+    owner: "user".to_string(),
+    offer_asset: Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::zero() },
+    receive_amount: Uint128::from(1000u128),
+    minimum_receive: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), zap_out_msg).unwrap();
+  assert!(res.events.is_empty());
+  assert_eq!(
+    pair.hourly_history.range(&deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap(),
+    hourly_history
+  );
+
+  // minimum receive assertion of the swap (state is not reverted in unit test, so run last)
+  let zap_out_msg = ExecuteMsg::ZapOut {
+    owner: "user".to_string(),
+    offer_asset: Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(1000000u128) },
+    receive_amount: Uint128::from(1000000u128),
+    minimum_receive: Some(Uint128::from(1000000000u128)),
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), zap_out_msg);
+
+  match res {
+    Err(ContractError::MinimumReceiveAssertion { .. }) => assert!(true),
+    _ => panic!("Must return minimum receive assertion error"),
+  }
+}
+
+#[test]
+fn price_tick_index_test() {
+  let pair = PairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(1u128, 100u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+
+  let price_to_tick_index = |price: Decimal256, rounding: Rounding, decimals: Option<[u8; 2]>| -> PriceToTickIndexResponse {
+    from_binary(&pair.query(deps.as_ref(), QueryMsg::PriceToTickIndex { price, rounding, decimals }).unwrap()).unwrap()
+  };
+
+  // exactly on the boundary
+  for rounding in [Rounding::Down, Rounding::Up, Rounding::Nearest] {
+    let res = price_to_tick_index(Decimal256::one(), rounding, None);
+    assert_eq!(res.tick_index, 0);
+    assert_eq!(res.price_sqrt, DENOMINATOR);
+    assert_eq!(res.price, Decimal256::one());
+  }
+
+  // tick 89
+  let price = Decimal256::from_ratio(1009u128, 1000u128);
+  assert_eq!(price_to_tick_index(price, Rounding::Down, None).tick_index, 0);
+  assert_eq!(price_to_tick_index(price, Rounding::Up, None).tick_index, 1);
+  assert_eq!(price_to_tick_index(price, Rounding::Nearest, None).tick_index, 1);
+
+  // tick -51, negative tick rounds down to -1
+  let price = Decimal256::from_ratio(995u128, 1000u128);
+  assert_eq!(price_to_tick_index(price, Rounding::Down, None).tick_index, -1);
+  assert_eq!(price_to_tick_index(price, Rounding::Up, None).tick_index, 0);
+  assert_eq!(price_to_tick_index(price, Rounding::Nearest, None).tick_index, -1);
+
+  // 1 wine (6 decimals) = 1 asset1 (8 decimals), raw price is 100 (tick 46054)
+  let res = price_to_tick_index(Decimal256::one(), Rounding::Down, Some([6, 8]));
+  assert_eq!(res.tick_index, 460);
+  assert_eq!(res.price_sqrt, get_tick_price_sqrt(46000));
+  assert!(res.price < Decimal256::one());
+
+  let res: TickIndexToPriceRangeResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::TickIndexToPriceRange {
+    tick_index: 460,
+    decimals: Some([6, 8]),
+  }).unwrap()).unwrap();
+  assert_eq!(res.lower_price_sqrt, get_tick_price_sqrt(46000));
+  assert_eq!(res.upper_price_sqrt, get_tick_price_sqrt(46100));
+  assert!(res.lower_price < Decimal256::one() && Decimal256::one() < res.upper_price);
+
+  // out of the ticks
+  let res = pair.query(deps.as_ref(), QueryMsg::TickIndexToPriceRange {
+    tick_index: 8873,
+    decimals: None,
+  });
+
+  match res {
+    Err(_) => assert!(true),
+    _ => panic!("Must return invalid tick index error"),
+  }
+
+  // asset decimals of the pair are used without decimals
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.asset_decimals = Some([6, 8]);
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  let res: PriceToTickIndexResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::PriceToTickIndex {
+    price: Decimal256::one(),
+    rounding: Rounding::Down,
+    decimals: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res.tick_index, 460);
+
+  let res: TickIndexToPriceRangeResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::TickIndexToPriceRange {
+    tick_index: 460,
+    decimals: None,
+  }).unwrap()).unwrap();
+  assert!(res.lower_price < Decimal256::one() && Decimal256::one() < res.upper_price);
+
+  // given decimals override them
+  let res: PriceToTickIndexResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::PriceToTickIndex {
+    price: Decimal256::one(),
+    rounding: Rounding::Down,
+    decimals: Some([0, 0]),
+  }).unwrap()).unwrap();
+  assert_eq!(res.tick_index, 0);
+
+  // decimals over 18 are rejected instead of overflowing
+  let res = pair.query(deps.as_ref(), QueryMsg::PriceToTickIndex {
+    price: Decimal256::one(),
+    rounding: Rounding::Down,
+    decimals: Some([0, 255]),
+  });
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, "Invalid decimals"),
+    _ => panic!("Must return invalid decimals error"),
+  }
+
+  let res = pair.query(deps.as_ref(), QueryMsg::TickIndexToPriceRange {
+    tick_index: 0,
+    decimals: Some([19, 0]),
+  });
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, "Invalid decimals"),
+    _ => panic!("Must return invalid decimals error"),
+  }
+
+  let res = pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::one(),
+    decimals: Some([0, 19]),
+    detailed: None,
+  });
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, "Invalid decimals"),
+    _ => panic!("Must return invalid decimals error"),
+  }
+}
+
+#[test]
+fn amount_to_price_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::from_ratio(3u128, 2u128),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  for (lower_tick_index, upper_tick_index) in [(300, 500), (380, 430)] {
+    let amounts = [Uint128::from(1000000u128), Uint128::from(1500000u128)];
+    let provide_msg = ExecuteMsg::ProvideLiquidity {
+      token_id: None,
+      tick_indexes: Some(TickIndexes { upper_tick_index, lower_tick_index }),
+      assets: [
+        Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: amounts[0] },
+        Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: amounts[1] },
+      ]
+    };
+    let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: amounts[1] }]);
+    let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+  }
+
+  // current price is 1.5 (tick index 405), raise it to 1.6 (tick index 470)
+  let res: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(8u128, 5u128),
+    decimals: None,
+    detailed: Some(true),
+  }).unwrap()).unwrap();
+
+  assert_eq!(res.offer_asset.info, AssetInfo::NativeToken { denom: "uusd".to_string() });
+  let steps = res.steps.clone().unwrap();
+  assert_eq!(steps.first().unwrap().tick_index, 405);
+  assert_eq!(steps.last().unwrap().tick_index, 470);
+  assert_eq!(steps.iter().map(|step| step.offer_amount).sum::<Uint128>(), res.offer_asset.amount);
+  assert_eq!(steps.iter().map(|step| step.return_amount).sum::<Uint128>(), res.return_amount);
+  assert_eq!(steps.iter().map(|step| step.commission_amount).sum::<Uint128>(), res.commission_amount);
+
+  let simulation: SimulationResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::Simulation {
+    offer_asset: res.offer_asset.clone(),
+    detailed: Some(true),
+    trader: None,
+  }).unwrap()).unwrap();
+  assert_eq!(simulation.return_amount, res.return_amount);
+  assert_eq!(simulation.commission_amount, res.commission_amount);
+
+  let detail = simulation.detail.unwrap();
+  assert_eq!(detail.steps, steps);
+  assert_eq!(detail.current_tick_index, 470);
+  assert_eq!(detail.current_price_sqrt, res.price_sqrt);
+  assert_eq!(detail.price, price_sqrt_to_price(res.price_sqrt));
+
+  // reverse simulation walks the same ticks
+  let reverse: ReverseSimulationResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::ReverseSimulation {
+    ask_asset: Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: res.return_amount },
+    detailed: Some(true),
+    trader: None,
+  }).unwrap()).unwrap();
+  let reverse_detail = reverse.detail.unwrap();
+  assert_eq!(reverse_detail.steps.len(), steps.len());
+  assert_eq!(reverse_detail.steps.last().unwrap().price_sqrt_after, reverse_detail.current_price_sqrt);
+  assert_eq!(reverse_detail.current_tick_index, 470);
+
+  let reverse: ReverseSimulationResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::ReverseSimulation {
+    ask_asset: Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: res.return_amount },
+    detailed: None,
+    trader: None,
+  }).unwrap()).unwrap();
+  assert_eq!(reverse.detail, None);
+
+  // without detail
+  let res_simple: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(8u128, 5u128),
+    decimals: None,
+    detailed: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res_simple.steps, None);
+  assert_eq!(res_simple.offer_asset, res.offer_asset);
+
+  // swap the amount moves the price to the target
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset: res.offer_asset.clone(),
+    belief_price: None,
+    max_slippage: None,
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
+    to: None
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: res.offer_asset.amount }]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, swap_msg).unwrap();
+
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), res.price_sqrt);
+  assert_eq!(pair.current_tick_index.load(&deps.storage).unwrap(), 470);
+
+  // lower the price, offer asset0
+  let res: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(3u128, 2u128),
+    decimals: None,
+    detailed: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res.offer_asset.info, AssetInfo::Token { contract_addr: "wine".to_string() });
+  assert!(res.price_sqrt < get_tick_price_sqrt(4060) && res.price_sqrt >= get_tick_price_sqrt(4050));
+
+  // not enough liquidity to reach the price
+  let res = pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(2u128, 1u128),
+    decimals: None,
+    detailed: None,
+  });
+
+  match res {
+    Err(_) => assert!(true),
+    _ => panic!("Must return can't swap error"),
+  }
+
+  // asset decimals of the pair are used without decimals, 1 wine (8 decimals) is 100 times of the raw price
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.asset_decimals = Some([8, 6]);
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  let res: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(150u128, 1u128),
+    decimals: None,
+    detailed: None,
+  }).unwrap()).unwrap();
+  let res_decimals: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(150u128, 1u128),
+    decimals: Some([8, 6]),
+    detailed: None,
+  }).unwrap()).unwrap();
+  assert_eq!(res, res_decimals);
+
+  let simulation: SimulationResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::Simulation {
+    offer_asset: res.offer_asset,
+    detailed: Some(true),
+    trader: None,
+  }).unwrap()).unwrap();
+  let detail = simulation.detail.unwrap();
+  assert_eq!(detail.price, price_sqrt_to_decimal_price(detail.current_price_sqrt, [8, 6]).unwrap());
+}
+
+#[test]
+fn max_swap_ticks_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::from_ratio(3u128, 2u128),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: Some([6, 6]),
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes { upper_tick_index: 500, lower_tick_index: 300 }),
+    assets: [
+      Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: Uint128::from(1000000u128) },
+      Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(1500000u128) },
+    ]
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1500000u128) }]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+
+  let res: ConfigResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(res.factory, Some("factory".to_string()));
+  assert_eq!(res.max_swap_ticks, 100);
+  assert_eq!(res.asset_decimals, Some([6, 6]));
+
+  // only factory can update
+  let update_msg = ExecuteMsg::UpdateConfig {
+    max_swap_ticks: Some(50),
+    history_config: Some(HistoryConfig { hourly_size: 24, daily_size: 30 }),
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("user", &[]), update_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("factory", &[]), ExecuteMsg::UpdateConfig { max_swap_ticks: Some(0), history_config: None });
+  match res {
+    Err(ContractError::InvalidMaxSwapTicks {}) => assert!(true),
+    _ => panic!("Must return invalid max swap ticks error"),
+  }
+
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("factory", &[]), ExecuteMsg::UpdateConfig {
+    max_swap_ticks: None,
+    history_config: Some(HistoryConfig { hourly_size: 0, daily_size: 30 }),
+  });
+  match res {
+    Err(ContractError::InvalidHistorySize {}) => assert!(true),
+    _ => panic!("Must return invalid history size error"),
+  }
+
+  let _res = pair.execute(deps.as_mut(), mock_env(), mock_info("factory", &[]), update_msg).unwrap();
+  let res: ConfigResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::Config {}).unwrap()).unwrap();
+  assert_eq!(res.max_swap_ticks, 50);
+  assert_eq!(res.history_config, HistoryConfig { hourly_size: 24, daily_size: 30 });
+
+  // 1.5 (tick index 405) to 1.6 (tick index 470) walks 66 ticks
+  let res: AmountToPriceResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::AmountToPrice {
+    target_price: Decimal256::from_ratio(8u128, 5u128),
+    decimals: None,
+    detailed: Some(true),
+  }).unwrap()).unwrap();
+  let steps = res.steps.clone().unwrap();
+  assert_eq!(steps.len(), 66);
+
+  let swap_msg = |max_ticks: Option<u32>, partial_fill: Option<bool>| ExecuteMsg::Swap {
+    offer_asset: res.offer_asset.clone(),
+    belief_price: None,
+    max_slippage: None,
+    to: None,
+    max_ticks,
+    partial_fill,
+    referral: None,
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: res.offer_asset.amount }]);
+  let swap_msg_offer_asset = res.offer_asset.clone();
+
+  // fail with the filled amount of the pair limit
+  let err = pair.execute(deps.as_mut(), mock_env(), info.clone(), swap_msg(None, None)).unwrap_err();
+  assert_eq!(err, ContractError::MaxTicksReached {
+    max_ticks: 50,
+    offer_amount: steps[..50].iter().map(|step| step.offer_amount).sum(),
+    return_amount: steps[..50].iter().map(|step| step.return_amount).sum(),
+  });
+
+  // simulations report the same limit
+  let res = pair.query(deps.as_ref(), QueryMsg::Simulation {
+    offer_asset: swap_msg_offer_asset.clone(),
+    detailed: None,
+    trader: None,
+  });
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, err.to_string()),
+    _ => panic!("Must return max ticks reached error"),
+  }
+
+  let res = pair.query(deps.as_ref(), QueryMsg::ReverseSimulation {
+    ask_asset: Asset {
+      info: AssetInfo::Token { contract_addr: "wine".to_string() },
+      amount: steps.iter().map(|step| step.return_amount).sum(),
+    },
+    detailed: None,
+    trader: None,
+  });
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert!(msg.starts_with("Max ticks reached, max_ticks: 50,")),
+    _ => panic!("Must return max ticks reached error"),
+  }
+
+  let _res = pair.query(deps.as_ref(), QueryMsg::Simulation {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: steps[..50].iter().map(|step| step.offer_amount).sum(),
+    },
+    detailed: None,
+    trader: None,
+  }).unwrap();
+
+  // partial fill with the swap limit, the rest is refunded
+  let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), swap_msg(Some(10), Some(true))).unwrap();
+  let offer_amount: Uint128 = steps[..10].iter().map(|step| step.offer_amount).sum();
+  let refund_amount = info.funds[0].amount - offer_amount;
+  assert!(res.attributes.contains(&attr("offer_amount", offer_amount.to_string())));
+  assert!(res.attributes.contains(&attr("refund_amount", refund_amount.to_string())));
+  assert!(res.attributes.contains(&attr("return_amount", steps[..10].iter().map(|step| step.return_amount).sum::<Uint128>().to_string())));
+  assert_eq!(
+    res.messages[1],
+    SubMsg::new(Asset {
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: refund_amount,
+    }.into_msg(&deps.as_mut().querier, Addr::unchecked("user")).unwrap())
+  );
+  assert_eq!(pair.current_tick_index.load(&deps.storage).unwrap(), 415);
+}
+
+#[test]
+fn referral_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::from_ratio(3u128, 2u128),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes { upper_tick_index: 500, lower_tick_index: 300 }),
+    assets: [
+      Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: Uint128::from(1000000u128) },
+      Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(1500000u128) },
+    ]
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1500000u128) }]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+
+  let offer_asset = Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(10000u128) };
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset: offer_asset.clone(),
+    belief_price: None,
+    max_slippage: None,
+    to: None,
+    max_ticks: None,
+    partial_fill: None,
+    // capped to 100 bps of the factory
+    referral: Some(Referral { address: "referrer".to_string(), commission_bps: 200 }),
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: offer_asset.amount }]);
+
+  let simulation: SimulationResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::Simulation {
+    offer_asset: offer_asset.clone(),
+    detailed: None,
+    trader: None,
+  }).unwrap()).unwrap();
+  let referral_amount = simulation.return_amount.multiply_ratio(100u128, 10000u128);
+
+  let res = pair.execute(deps.as_mut(), mock_env(), info.clone(), swap_msg.clone()).unwrap();
+  assert!(res.attributes.contains(&attr("referral_address", "referrer")));
+  assert!(res.attributes.contains(&attr("referral_amount", referral_amount.to_string())));
+  assert!(res.attributes.contains(&attr("return_amount", (simulation.return_amount - referral_amount).to_string())));
+  // LP commission is not changed
+  assert!(res.attributes.contains(&attr("commission_amount", simulation.commission_amount.to_string())));
+  assert_eq!(
+    res.messages[1],
+    SubMsg::new(Asset {
+      info: AssetInfo::Token { contract_addr: "wine".to_string() },
+      amount: referral_amount,
+    }.into_msg(&deps.as_mut().querier, Addr::unchecked("referrer")).unwrap())
+  );
+
+  // referral fails without the factory instead of being dropped
+  pair.factory.remove(deps.as_mut().storage);
+  let res = pair.execute(deps.as_mut(), mock_env(), info, swap_msg);
+
+  match res {
+    Err(ContractError::ReferralNotSupported {}) => assert!(true),
+    _ => panic!("Must return referral not supported error"),
+  }
+}
+
+#[test]
+fn fee_discount_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::from_ratio(3u128, 2u128),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes { upper_tick_index: 500, lower_tick_index: 300 }),
+    assets: [
+      Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: Uint128::from(1000000u128) },
+      Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(1500000u128) },
+    ]
+  };
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1500000u128) }]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+
+  // half of the fee for "discounted" in the factory
+  let res: EffectiveFeeResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::EffectiveFee {
+    trader: "discounted".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(res, EffectiveFeeResponse {
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    discount: Decimal::from_ratio(1u128, 2u128),
+    effective_fee_rate: Decimal::from_ratio(15u128, 10000u128),
+  });
+
+  let res: EffectiveFeeResponse = from_binary(&pair.query(deps.as_ref(), QueryMsg::EffectiveFee {
+    trader: "user".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(res.effective_fee_rate, Decimal::from_ratio(3u128, 1000u128));
+
+  let offer_asset = Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(100000u128) };
+  let simulation = |trader: Option<String>| -> SimulationResponse {
+    from_binary(&pair.query(deps.as_ref(), QueryMsg::Simulation {
+      offer_asset: offer_asset.clone(),
+      detailed: None,
+      trader,
+    }).unwrap()).unwrap()
+  };
+  let full_fee = simulation(None);
+  let discounted = simulation(Some("discounted".to_string()));
+  assert!(discounted.commission_amount < full_fee.commission_amount);
+  assert!(discounted.return_amount > full_fee.return_amount);
+
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset: offer_asset.clone(),
+    belief_price: None,
+    max_slippage: None,
+    to: None,
+    max_ticks: None,
+    partial_fill: None,
+    referral: None,
+  };
+  let info = mock_info("discounted", &[Coin{ denom: "uusd".to_string(), amount: offer_asset.amount }]);
+  let res = pair.execute(deps.as_mut(), mock_env(), info, swap_msg).unwrap();
+  assert!(res.attributes.contains(&attr("return_amount", discounted.return_amount.to_string())));
+  assert!(res.attributes.contains(&attr("commission_amount", discounted.commission_amount.to_string())));
+}
+
+#[test]
+fn fee_discount_zap_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+
+  let instantiate_msg = InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 100,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  };
+
+  let _res = pair.instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), instantiate_msg).unwrap();
+  // "discounted" pays half of the fee
+  let mut pool = Pool::new(Decimal::one(), 100, Decimal::from_ratio(15u128, 10000u128)).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: Some(TickIndexes { upper_tick_index: 10, lower_tick_index: -10 }),
+    assets: [
+      Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::from(1000000000u128) },
+      Asset { info: AssetInfo::Token { contract_addr: "wine".to_string() }, amount: Uint128::from(1000000000u128) },
+    ]
+  };
+  let info = mock_info("discounted", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000000u128) }]);
+  let _res = pair.execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+  let (position_id, liquidity, _) = pool.provide(
+    None,
+    Some(TickIndexes { upper_tick_index: 10, lower_tick_index: -10 }),
+    [Uint128::from(1000000000u128), Uint128::from(1000000000u128)]
+  ).unwrap();
+
+  deps.querier.with_lp_infos(&[
+    (&"0".to_string(), &LiquidityInfoResponse{
+      approvals: vec![],
+      liquidity,
+      upper_tick_index: 10,
+      lower_tick_index: -10,
+      owner: Addr::unchecked("discounted")
+    })
+  ]);
+
+  // zap out with the discounted fee
+  let withdrawn = pool.withdraw(position_id, Some(liquidity.multiply_ratio(1u128, 10u128))).unwrap();
+  let swap = pool.swap(TokenNumber::Token0, withdrawn[0]).unwrap();
+
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity{
+    token_id: "0".to_string(),
+    amount: Some(liquidity.multiply_ratio(1u128, 10u128)),
+    receive_asset: Some(AssetInfo::NativeToken { denom: "uusd".to_string() }),
+    minimum_receive: None,
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info("discounted", &[]), withdraw_msg).unwrap();
+  let zap_out_msg = match &res.messages.last().unwrap().msg {
+    CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_binary::<ExecuteMsg>(msg).unwrap(),
+    _ => panic!("Must swap with the pair itself"),
+  };
+  let res = pair.execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), zap_out_msg).unwrap();
+  assert!(res.attributes.contains(&attr("commission_amount", swap.commission_amount.to_string())));
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), pool.current_price_sqrt);
+
+  // zap in with the discounted fee
+  let zap_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken { denom: "uusd".to_string() },
+      amount: Uint128::from(10000000u128),
+    },
+    tick_indexes: TickIndexes { upper_tick_index: 2, lower_tick_index: -2 },
+    min_liquidity: None,
+  };
+  let info = mock_info("discounted", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(10000000u128) }]);
+  let res = pair.execute(deps.as_mut(), mock_env(), info, zap_msg).unwrap();
+
+  let attribute = |key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+  let swap_amount = Uint128::from(attribute("swap_amount").parse::<u128>().unwrap());
+  let swap = pool.swap(TokenNumber::Token1, swap_amount).unwrap();
+  assert!(res.attributes.contains(&attr("commission_amount", swap.commission_amount.to_string())));
+  assert_eq!(pair.current_price_sqrt.load(&deps.storage).unwrap(), pool.current_price_sqrt);
+}
+
+#[test]
+fn minter_proposal_test() {
+  let pair = PairContract::default();
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+
+  let _res = pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), InstantiateMsg {
+    asset_infos: [
+      AssetInfo::Token { contract_addr: "wine".to_string() },
+      AssetInfo::NativeToken { denom: "uusd".to_string() }
+    ],
+    fee_rate: Decimal::from_ratio(3u128, 1000u128),
+    token_code_id: 123,
+    tick_space: 10,
+    initial_price: Decimal::one(),
+    history_config: None,
+    max_swap_ticks: None,
+    asset_decimals: None,
+  }).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  let propose_msg = ExecuteMsg::ProposeNewMinter {
+    minter: "new_pair".to_string(),
+    expires: Expiration::AtHeight(env.block.height + 100),
+  };
+
+  // only the factory owner
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), propose_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // the pair proposes to its lp token
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg.clone()).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "liquidity".to_string(),
+    msg: to_binary(&TokenExecuteMsg::ProposeNewMinter {
+      minter: "new_pair".to_string(),
+      expires: Expiration::AtHeight(env.block.height + 100),
+    }).unwrap(),
+    funds: vec![],
+  })]);
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), ExecuteMsg::DropMinterProposal {});
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::DropMinterProposal {}).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "liquidity".to_string(),
+    msg: to_binary(&TokenExecuteMsg::DropMinterProposal {}).unwrap(),
+    funds: vec![],
+  })]);
+
+  // the factory queues it while the timelock is set
+  deps.querier.with_timelock_delay(86400);
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg.clone());
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  let _res = pair.execute(deps.as_mut(), env, mock_info("factory", &[]), propose_msg).unwrap();
+}
@@ -0,0 +1,38 @@
+use cosmwasm_std::{StdError, OverflowError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+  #[error("{0}")]
+  Std(#[from] StdError),
+
+  #[error("{0}")]
+  OverflowError(#[from] OverflowError),
+
+  #[error("Unauthorized")]
+  Unauthorized {},
+
+  #[error("Zero liquidity error")]
+  ZeroLiquidity {},
+
+  #[error("Max slippage assertion")]
+  MaxSlippage,
+
+  #[error("Asset mismatch")]
+  AssetMismatch {},
+
+  #[error("Invalid amp")]
+  InvalidAmp {},
+
+  #[error("Amp must be ramped for at least a day")]
+  InvalidRampTime {},
+
+  #[error("Amp ramp and minter proposal must be queued in the factory timelock")]
+  Timelocked {},
+
+  #[error("Min liquidity assertion, min: {min_liquidity}, liquidity: {liquidity}")]
+  MinLiquidityAssertion { min_liquidity: Uint128, liquidity: Uint128 },
+
+  #[error("Minimum receive assertion, minimum: {minimum_receive}, receive: {receive}")]
+  MinimumReceiveAssertion { minimum_receive: Uint128, receive: Uint128 },
+}
@@ -0,0 +1,992 @@
+use cosmwasm_std::{to_binary, from_binary, Addr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, QuerierWrapper,
+  QueryRequest, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery};
+use cw2::set_contract_version;
+use protobuf::Message;
+use cw0::Expiration;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use wineswap::{
+  stable_pair::{InstantiateMsg, ExecuteMsg},
+  pair::{Cw20HookMsg, MigrateMsg, Referral, TickInfo},
+  asset::{Asset, AssetInfo},
+  factory::{Config as FactoryConfig, QueryMsg as FactoryQueryMsg},
+  lp_token::{InstantiateMsg as TokenInstantiateMsg, ExecuteMsg as TokenExecuteMsg},
+  tax::ChainTax,
+};
+use wineswap_math::{
+  stable::{compute_stable_liquidity, compute_stable_swap, MAX_AMP, MAX_AMP_CHANGE, MIN_RAMP_TIME},
+  fee::{compute_fee_growth, discount_fee_rate, fee_growth_add},
+};
+
+use crate::response::MsgInstantiateContractResponse;
+use crate::error::ContractError;
+use crate::state::{AmpConfig, Config, StablePairContract, POOL_TICK_INDEX};
+
+const CONTRACT_NAME: &str = "crates.io:wine-stable-pair";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+
+/// `ProvideLiquidity` of the execute msg and the cw20 hook, and the provide of `ZapIn`
+pub struct ProvideParams {
+  pub assets: [Asset; 2],
+  pub token_id: Option<String>,
+  pub min_liquidity: Option<Uint128>,
+}
+
+/// `WithdrawLiquidity` of the execute msg
+pub struct WithdrawParams {
+  pub token_id: String,
+  pub amount: Option<Uint128>,
+  pub receive_asset: Option<AssetInfo>,
+  pub minimum_receive: Option<Uint128>,
+}
+
+/// `ZapOut` of the execute msg, the swap of the zap out withdraw
+pub struct ZapOutParams {
+  pub owner: String,
+  pub offer_asset: Asset,
+  pub receive_amount: Uint128,
+  pub minimum_receive: Option<Uint128>,
+}
+
+/// `Swap` of the execute msg and the cw20 hook, with the offer asset and the validated receiver
+pub struct SwapParams {
+  pub offer_asset: Asset,
+  pub to: Option<Addr>,
+  pub belief_price: Option<Decimal>,
+  pub max_slippage: Option<Decimal>,
+  pub referral: Option<Referral>,
+}
+
+impl<'a> StablePairContract<'a> {
+  pub fn instantiate(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+  ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.tick_space == 0u16 {
+      return Err(StdError::generic_err("Invalid tick space"));
+    }
+
+    if msg.fee_rate >= Decimal::one() {
+      return Err(StdError::generic_err("Invalid fee rate"));
+    }
+
+    if msg.amp == 0 || msg.amp > MAX_AMP {
+      return Err(StdError::generic_err("Invalid amp"));
+    }
+
+    // the invariant takes the pools as the same unit
+    if let Some(asset_decimals) = msg.asset_decimals {
+      if asset_decimals[0] != asset_decimals[1] {
+        return Err(StdError::generic_err("Asset decimals must be the same"));
+      }
+    }
+
+    // save config
+    let config = Config {
+      asset_infos: msg.asset_infos,
+      tick_space: msg.tick_space,
+      fee_rate: msg.fee_rate,
+      // temp addr
+      liquidity_token: info.sender.clone(),
+      factory: info.sender,
+      asset_decimals: msg.asset_decimals,
+    };
+
+    self.config.save(deps.storage, &config)?;
+
+    self.amp_config.save(deps.storage, &AmpConfig {
+      init_amp: msg.amp,
+      init_amp_time: env.block.time.seconds(),
+      next_amp: msg.amp,
+      next_amp_time: env.block.time.seconds(),
+    })?;
+    self.pools.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    self.pool_info.save(deps.storage, &TickInfo::default())?;
+
+    Ok(Response::new().add_submessage(SubMsg {
+      msg: WasmMsg::Instantiate {
+        // the factory migrates the lp token with `MigrateContract`
+        admin: Some(config.factory.to_string()),
+        code_id: msg.token_code_id,
+        label: "".to_string(),
+        funds: vec![],
+        msg: to_binary(&TokenInstantiateMsg{
+          name: "wineswap stable liquidity token".to_string(),
+          symbol: ("WINELP".to_string()),
+          minter: env.contract.address.to_string(),
+        })?,
+      }
+      .into(),
+      gas_limit: None,
+      id: 1,
+      reply_on: ReplyOn::Success,
+    }))
+  }
+
+  pub fn reply(&self, deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    let data = msg.result.unwrap().data.unwrap();
+    let res: MsgInstantiateContractResponse =
+      Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+        StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+      })?;
+    let liquidity_token = res.get_contract_address();
+    let mut config = self.config.load(deps.storage)?;
+    // update liquidity_token from the temp addr
+    config.liquidity_token = deps.api.addr_validate(liquidity_token)?;
+
+    self.config.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
+  }
+
+  pub fn migrate(&self, deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new())
+  }
+
+  pub fn execute(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg
+  ) -> Result<Response, ContractError> {
+    match msg {
+      ExecuteMsg::Receive(msg) => self.receive_cw20(deps, env, info, msg),
+      ExecuteMsg::ProvideLiquidity {
+        assets,
+        token_id,
+        tick_indexes: _,
+      } => {
+        let sender = info.sender.clone();
+        self.provide(deps, env, info, sender, ProvideParams { assets, token_id, min_liquidity: None })
+      },
+      ExecuteMsg::WithdrawLiquidity {
+        token_id,
+        amount,
+        receive_asset,
+        minimum_receive,
+      } => self.withdraw(deps, env, info, WithdrawParams { token_id, amount, receive_asset, minimum_receive }),
+      ExecuteMsg::Swap {
+        offer_asset,
+        to,
+        belief_price,
+        max_slippage,
+        referral,
+      } => {
+        if !offer_asset.is_native_token() {
+          return Err(ContractError::Unauthorized {});
+        }
+
+        let to_addr = if let Some(to_addr) = to {
+          Some(deps.api.addr_validate(&to_addr)?)
+        } else {
+          None
+        };
+
+        self.swap(deps, env, info.clone(), info.sender, SwapParams {
+          offer_asset,
+          to: to_addr,
+          belief_price,
+          max_slippage,
+          referral,
+        })
+      },
+      ExecuteMsg::ClaimReward {
+        token_id,
+        rewards,
+      } => self.claim(deps, env, info, token_id, rewards),
+      ExecuteMsg::ZapIn {
+        offer_asset,
+        tick_indexes: _,
+        min_liquidity,
+      } => {
+        if !offer_asset.is_native_token() {
+          return Err(ContractError::Unauthorized {});
+        }
+
+        let sender = info.sender.clone();
+        self.zap_in(deps, env, info, sender, offer_asset, min_liquidity)
+      },
+      ExecuteMsg::ZapOut {
+        owner,
+        offer_asset,
+        receive_amount,
+        minimum_receive,
+      } => self.zap_out(deps, env, info, ZapOutParams { owner, offer_asset, receive_amount, minimum_receive }),
+      ExecuteMsg::RampAmp {
+        next_amp,
+        next_amp_time,
+      } => self.ramp_amp(deps, env, info, next_amp, next_amp_time),
+      ExecuteMsg::StopRampAmp {} => self.stop_ramp_amp(deps, env, info),
+      ExecuteMsg::ProposeNewMinter { minter, expires } => self.propose_new_minter(deps, env, info, minter, expires),
+      ExecuteMsg::DropMinterProposal {} => self.drop_minter_proposal(deps, env, info),
+    }
+  }
+}
+
+/// execute function
+impl<'a> StablePairContract<'a> {
+  pub fn receive_cw20(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+  ) -> Result<Response, ContractError> {
+    // only asset contract can execute this message
+    let config = self.config.load(deps.storage)?;
+    let authorized = config.asset_infos.iter().any(|asset_info| match asset_info {
+      AssetInfo::Token { contract_addr } => contract_addr == info.sender.as_str(),
+      AssetInfo::NativeToken { .. } => false,
+    });
+
+    if !authorized {
+      return Err(ContractError::Unauthorized {});
+    }
+
+    let sent_asset = Asset {
+      info: AssetInfo::Token {
+        contract_addr: info.sender.to_string(),
+      },
+      amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg) {
+      // no ticks to walk, max_ticks and partial_fill are not used
+      Ok(Cw20HookMsg::Swap {
+        to,
+        belief_price,
+        max_slippage,
+        referral,
+        ..
+      }) => {
+        let to_addr = if let Some(to_addr) = to {
+          Some(deps.api.addr_validate(to_addr.as_str())?)
+        } else {
+          None
+        };
+
+        self.swap(deps, env, info, Addr::unchecked(cw20_msg.sender), SwapParams {
+          offer_asset: sent_asset,
+          to: to_addr,
+          belief_price,
+          max_slippage,
+          referral,
+        })
+      },
+      Ok(Cw20HookMsg::ProvideLiquidity {
+        other_asset,
+        token_id,
+        tick_indexes: _,
+      }) => {
+        self.provide(deps, env, info, Addr::unchecked(cw20_msg.sender), ProvideParams {
+          assets: [sent_asset, other_asset],
+          token_id,
+          min_liquidity: None,
+        })
+      },
+      Ok(Cw20HookMsg::ZapIn {
+        tick_indexes: _,
+        min_liquidity,
+      }) => {
+        self.zap_in(
+          deps,
+          env,
+          info,
+          Addr::unchecked(cw20_msg.sender),
+          sent_asset,
+          min_liquidity,
+        )
+      },
+      Err(err) => Err(ContractError::Std(err)),
+    }
+  }
+
+  /// every given amount is provided, imbalanced amounts pay commission for the imbalanced part
+  pub fn provide(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    params: ProvideParams,
+  ) -> Result<Response, ContractError> {
+    let ProvideParams { assets, token_id, min_liquidity } = params;
+
+    // native sent balance check
+    for asset in assets.iter() {
+      asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+
+    let mut token_amount = [Uint128::zero(), Uint128::zero()];
+    for asset in assets.iter() {
+      if asset.info.equal(&asset_infos[0]) {
+        token_amount[0] += asset.amount;
+      } else if asset.info.equal(&asset_infos[1]) {
+        token_amount[1] += asset.amount;
+      } else {
+        return Err(ContractError::AssetMismatch {});
+      }
+    }
+
+    // when provide to already exist position
+    if let Some(token_id) = token_id.clone() {
+      let liquidity = self.get_liquidity_info(deps.querier, config.liquidity_token.to_string(), token_id)?;
+      if sender != liquidity.owner {
+        return Err(ContractError::Unauthorized {})
+      }
+    }
+
+    let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+    let mut pools = self.pools.load(deps.storage)?;
+    let mut pool_info = self.pool_info.load(deps.storage)?;
+
+    let liquidity = compute_stable_liquidity(amp, pools, token_amount, pool_info.total_liquidity, config.fee_rate)?;
+
+    if liquidity.is_zero() {
+      return Err(ContractError::ZeroLiquidity {})
+    }
+
+    if let Some(min_liquidity) = min_liquidity {
+      if liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityAssertion { min_liquidity, liquidity })
+      }
+    }
+
+    pools = [pools[0].checked_add(token_amount[0])?, pools[1].checked_add(token_amount[1])?];
+    pool_info.total_liquidity = pool_info.total_liquidity.checked_add(liquidity)?;
+    self.pools.save(deps.storage, &pools)?;
+    self.pool_info.save(deps.storage, &pool_info)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+      match asset_info {
+        // cw20 sent through the receive hook is already in the pair
+        AssetInfo::Token { contract_addr, .. } if contract_addr == info.sender.as_str() => {},
+        // if token, get token by transfer_from
+        AssetInfo::Token { contract_addr, .. } => {
+          if !token_amount[i].is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+              contract_addr: contract_addr.to_string(),
+              msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: token_amount[i],
+              })?,
+              funds: vec![],
+            }));
+          }
+        },
+        AssetInfo::NativeToken { .. } => {},
+      }
+    }
+
+    if let Some(token_id) = token_id.clone() {
+      // have to claim reward first
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::ClaimReward {
+          token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+      }));
+      // update liquidity
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::UpdateLiquidity {
+          token_id,
+          amount: liquidity,
+          add: true
+        })?,
+        funds: vec![],
+      }));
+    } else {
+      // mint Lp token
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::Mint {
+          owner: sender.to_string(),
+          liquidity,
+          upper_tick_index: POOL_TICK_INDEX,
+          lower_tick_index: POOL_TICK_INDEX,
+        })?,
+        funds: vec![],
+      }));
+    }
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "provide_liquidity")
+      .add_attribute("sender", sender.to_string())
+      .add_attribute("provide_assets", format!("{}, {}",
+        Asset {
+          info: asset_infos[0].clone(),
+          amount: token_amount[0]
+        },
+        Asset {
+          info: asset_infos[1].clone(),
+          amount: token_amount[1]
+        }
+      ))
+      .add_attribute("liquidity", liquidity.to_string())
+      .add_attribute("token_id", token_id.unwrap_or_else(|| "new".to_string()))
+    )
+  }
+
+  /// stable pool takes any ratio, so zap in is a single asset provide
+  pub fn zap_in(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    min_liquidity: Option<Uint128>,
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+    let other_asset_info = if offer_asset.info.equal(&config.asset_infos[0]) {
+      config.asset_infos[1].clone()
+    } else {
+      config.asset_infos[0].clone()
+    };
+
+    let assets = [
+      offer_asset,
+      Asset {
+        info: other_asset_info,
+        amount: Uint128::zero(),
+      },
+    ];
+
+    self.provide(deps, env, info, sender, ProvideParams { assets, token_id: None, min_liquidity })
+  }
+
+  pub fn withdraw(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: WithdrawParams,
+  ) -> Result<Response, ContractError> {
+    let WithdrawParams { token_id, amount, receive_asset, minimum_receive } = params;
+    let config = self.config.load(deps.storage)?;
+    let liquidity_token = config.liquidity_token.to_string();
+    let liquidity = self.get_liquidity_info(deps.querier, liquidity_token.clone(), token_id.clone())?;
+
+    // partial withdraw
+    let partial_withdraw = amount.is_some();
+    let withdraw_amount = amount.unwrap_or(liquidity.liquidity);
+
+    if info.sender != liquidity.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let asset_infos = config.asset_infos.clone();
+
+    // receive index of the swap after withdraw
+    let mut zap_out = match receive_asset {
+      Some(receive_asset) if receive_asset.equal(&asset_infos[0]) => Some(0),
+      Some(receive_asset) if receive_asset.equal(&asset_infos[1]) => Some(1),
+      Some(_) => return Err(ContractError::AssetMismatch {}),
+      None => None,
+    };
+
+    let mut pools = self.pools.load(deps.storage)?;
+    let mut pool_info = self.pool_info.load(deps.storage)?;
+
+    // share of the pools
+    let token_amount = [
+      pools[0].multiply_ratio(withdraw_amount, pool_info.total_liquidity),
+      pools[1].multiply_ratio(withdraw_amount, pool_info.total_liquidity),
+    ];
+    pools = [pools[0].checked_sub(token_amount[0])?, pools[1].checked_sub(token_amount[1])?];
+    pool_info.total_liquidity = pool_info.total_liquidity.checked_sub(withdraw_amount)?;
+
+    // nothing to swap when the share of the other asset is zero
+    if let Some(receive_index) = zap_out {
+      if token_amount[1 - receive_index].is_zero() {
+        if let Some(minimum_receive) = minimum_receive {
+          if token_amount[receive_index] < minimum_receive {
+            return Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive: token_amount[receive_index] })
+          }
+        }
+        zap_out = None;
+      }
+    }
+
+    self.pools.save(deps.storage, &pools)?;
+    self.pool_info.save(deps.storage, &pool_info)?;
+
+    let assets = [
+      Asset {
+        info: asset_infos[0].clone(),
+        amount: token_amount[0],
+      },
+      Asset {
+        info: asset_infos[1].clone(),
+        amount: token_amount[1],
+      },
+    ];
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    // refund assets, the zap out sends them after the swap
+    if zap_out.is_none() {
+      for asset in assets.clone() {
+        messages.extend(transfer_after_tax(&deps.querier, asset, liquidity.owner.clone())?);
+      }
+    }
+
+    // claim reward
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+      contract_addr: liquidity_token.clone(),
+      msg: to_binary(&TokenExecuteMsg::ClaimReward {
+        token_id: token_id.clone(),
+      })?,
+      funds: vec![],
+    }));
+
+    if partial_withdraw {
+      // update liquidity
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_token,
+        msg: to_binary(&TokenExecuteMsg::UpdateLiquidity {
+          token_id: token_id.clone(),
+          amount: withdraw_amount,
+          add: false,
+        })?,
+        funds: vec![],
+      }));
+    } else {
+      // burn liquidity
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_token,
+        msg: to_binary(&TokenExecuteMsg::Burn { token_id: token_id.clone() })?,
+        funds: vec![],
+      }));
+    }
+
+    // swap the other asset after the reward is claimed. the commission raises fee growth,
+    // the position would be paid for the withdrawn liquidity if it is claimed after the swap
+    if let Some(receive_index) = zap_out {
+      messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::ZapOut {
+          owner: liquidity.owner.to_string(),
+          offer_asset: assets[1 - receive_index].clone(),
+          receive_amount: token_amount[receive_index],
+          minimum_receive,
+        })?,
+        funds: vec![],
+      }));
+    }
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "withdraw_liquidity")
+      .add_attribute("sender", info.sender.to_string())
+      .add_attribute("withdraw_assets", format!("{}, {}", assets[0], assets[1]))
+      .add_attribute("liquidity_token_id", token_id),
+    )
+  }
+
+  pub fn zap_out(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: ZapOutParams,
+  ) -> Result<Response, ContractError> {
+    let ZapOutParams { owner, offer_asset, receive_amount, minimum_receive } = params;
+
+    // only the withdraw of the pair
+    if info.sender != env.contract.address {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let owner = deps.api.addr_validate(&owner)?;
+    let config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+
+    let (offer_index, receive_index) = if offer_asset.info.equal(&asset_infos[0]) {
+      (0, 1)
+    } else if offer_asset.info.equal(&asset_infos[1]) {
+      (1, 0)
+    } else {
+      return Err(ContractError::AssetMismatch {});
+    };
+
+    let mut return_amount = Uint128::zero();
+    let mut commission_amount = Uint128::zero();
+    if !offer_asset.amount.is_zero() {
+      let mut pools = self.pools.load(deps.storage)?;
+      let mut pool_info = self.pool_info.load(deps.storage)?;
+      let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+      let (swap_return, commission) = compute_stable_swap(
+        amp,
+        pools[offer_index],
+        pools[receive_index],
+        offer_asset.amount,
+        config.fee_rate,
+      )?;
+      return_amount = swap_return;
+      commission_amount = commission;
+
+      pools[offer_index] = pools[offer_index].checked_add(offer_asset.amount)?;
+      pools[receive_index] = pools[receive_index].checked_sub(return_amount)?;
+      add_fee_growth(&mut pool_info, receive_index, commission_amount);
+
+      self.pools.save(deps.storage, &pools)?;
+      self.pool_info.save(deps.storage, &pool_info)?;
+    }
+
+    let receive_amount = receive_amount.checked_add(return_amount.checked_sub(commission_amount)?)?;
+    if let Some(minimum_receive) = minimum_receive {
+      if receive_amount < minimum_receive {
+        return Err(ContractError::MinimumReceiveAssertion { minimum_receive, receive: receive_amount })
+      }
+    }
+
+    let receive_asset = Asset {
+      info: asset_infos[receive_index].clone(),
+      amount: receive_amount,
+    };
+    let messages: Vec<CosmosMsg> = transfer_after_tax(&deps.querier, receive_asset.clone(), owner.clone())?
+      .into_iter()
+      .collect();
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "zap_out")
+      .add_attribute("owner", owner.to_string())
+      .add_attribute("receive_asset", receive_asset.to_string())
+      .add_attribute("swap_amount", offer_asset.amount.to_string())
+      .add_attribute("commission_amount", commission_amount.to_string())
+    )
+  }
+
+  pub fn swap(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    params: SwapParams,
+  ) -> Result<Response, ContractError> {
+    let SwapParams { offer_asset, to, belief_price, max_slippage, referral } = params;
+
+    // native sent balance check
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let config = self.config.load(deps.storage)?;
+    let asset_infos = config.asset_infos.clone();
+    // commission of the swap with the fee discount of the sender
+    let fee_rate = discount_fee_rate(config.fee_rate, self.fee_discount(deps.as_ref(), &sender)?);
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&asset_infos[0]) {
+      (0, 1)
+    } else if offer_asset.info.equal(&asset_infos[1]) {
+      (1, 0)
+    } else {
+      return Err(ContractError::AssetMismatch {});
+    };
+    let return_token_info = asset_infos[ask_index].clone();
+
+    let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+    let mut pools = self.pools.load(deps.storage)?;
+    let mut pool_info = self.pool_info.load(deps.storage)?;
+
+    if pool_info.total_liquidity.is_zero() {
+      return Err(ContractError::ZeroLiquidity {})
+    }
+
+    let (total_return_amount, total_commission_amount) = compute_stable_swap(
+      amp,
+      pools[offer_index],
+      pools[ask_index],
+      offer_asset.amount,
+      fee_rate,
+    )?;
+
+    // commission leaves the pool and is shared by the liquidity
+    pools[offer_index] = pools[offer_index].checked_add(offer_asset.amount)?;
+    pools[ask_index] = pools[ask_index].checked_sub(total_return_amount)?;
+    add_fee_growth(&mut pool_info, ask_index, total_commission_amount);
+    self.pools.save(deps.storage, &pools)?;
+    self.pool_info.save(deps.storage, &pool_info)?;
+
+    let user_return_amount = total_return_amount.checked_sub(total_commission_amount)?;
+
+    // referral commission is taken from the user return, not from the LP commission
+    let (referral_addr, referral_amount) = if let Some(referral) = referral {
+      let factory_config = self.query_factory_config(deps.as_ref())?;
+      (
+        Some(deps.api.addr_validate(&referral.address)?),
+        referral.commission_amount(user_return_amount, factory_config.max_referral_commission_bps),
+      )
+    } else {
+      (None, Uint128::zero())
+    };
+    let user_return_amount = user_return_amount.checked_sub(referral_amount)?;
+
+    // slippage protection
+    if let (Some(max_slippage), Some(belief_price)) = (max_slippage, belief_price) {
+      // min_return = expected_return * (1 - max_slippage)
+      // = offer_amount / belief_price * (1 - max_slippage)
+      let min_return = Decimal::from_ratio(
+        offer_asset.amount * DECIMAL_FRACTION,
+        belief_price * DECIMAL_FRACTION
+      ) * Uint128::from(1u128)
+      * (Decimal::one() - max_slippage);
+
+      if min_return > user_return_amount {
+        return Err(ContractError::MaxSlippage {} )
+      }
+    }
+
+    let return_asset = Asset {
+      info: return_token_info.clone(),
+      amount: user_return_amount,
+    };
+
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !user_return_amount.is_zero() {
+      messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
+    }
+
+    if let Some(referral_addr) = referral_addr.clone().filter(|_| !referral_amount.is_zero()) {
+      let referral_asset = Asset {
+        info: return_token_info.clone(),
+        amount: referral_amount,
+      };
+      messages.push(referral_asset.into_msg(&deps.querier, referral_addr)?);
+    }
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "swap")
+      .add_attribute("sender", sender.to_string())
+      .add_attribute("receiver", receiver.to_string())
+      .add_attribute("offer_asset", offer_asset.info.to_string())
+      .add_attribute("return_asset", return_token_info.to_string())
+      .add_attribute("offer_amount", offer_asset.amount.to_string())
+      .add_attribute("return_amount", user_return_amount.to_string())
+      .add_attribute("tax_amount", tax_amount.to_string())
+      .add_attribute("commission_amount", total_commission_amount.to_string())
+      .add_attribute("referral_address", referral_addr.map(|addr| addr.to_string()).unwrap_or_default())
+      .add_attribute("referral_amount", referral_amount.to_string())
+    )
+  }
+
+  pub fn claim(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    rewards: [Asset; 2],
+  ) -> Result<Response, ContractError> {
+    let config = self.config.load(deps.storage)?;
+
+    // only liquidity token contract can execute
+    if config.liquidity_token != info.sender {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    let liquidity = self.get_liquidity_info(deps.querier, config.liquidity_token.to_string(), token_id)?;
+    let owner = liquidity.owner;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for reward in rewards.clone() {
+      messages.extend(transfer_after_tax(&deps.querier, reward, owner.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages)
+      .add_attribute("action", "claim_reward")
+      .add_attribute("sender", info.sender.to_string())
+      .add_attribute("owner", owner.to_string())
+      .add_attribute("claim_amount", format!("{}, {}", rewards[0], rewards[1]))
+    )
+  }
+
+  pub fn ramp_amp(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    next_amp: u64,
+    next_amp_time: u64,
+  ) -> Result<Response, ContractError> {
+    self.assert_factory_timelock(deps.as_ref(), &info)?;
+
+    let now = env.block.time.seconds();
+    let amp = self.amp_config.load(deps.storage)?.amp(now);
+
+    if next_amp == 0 || next_amp > MAX_AMP
+      || next_amp > amp * MAX_AMP_CHANGE || next_amp * MAX_AMP_CHANGE < amp {
+      return Err(ContractError::InvalidAmp {})
+    }
+
+    if next_amp_time < now + MIN_RAMP_TIME {
+      return Err(ContractError::InvalidRampTime {})
+    }
+
+    self.amp_config.save(deps.storage, &AmpConfig {
+      init_amp: amp,
+      init_amp_time: now,
+      next_amp,
+      next_amp_time,
+    })?;
+
+    Ok(Response::new()
+      .add_attribute("action", "ramp_amp")
+      .add_attribute("init_amp", amp.to_string())
+      .add_attribute("next_amp", next_amp.to_string())
+      .add_attribute("next_amp_time", next_amp_time.to_string())
+    )
+  }
+
+  pub fn stop_ramp_amp(
+    &self,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    self.assert_factory_owner(deps.as_ref(), &info)?;
+
+    let now = env.block.time.seconds();
+    let amp = self.amp_config.load(deps.storage)?.amp(now);
+
+    self.amp_config.save(deps.storage, &AmpConfig {
+      init_amp: amp,
+      init_amp_time: now,
+      next_amp: amp,
+      next_amp_time: now,
+    })?;
+
+    Ok(Response::new()
+      .add_attribute("action", "stop_ramp_amp")
+      .add_attribute("amp", amp.to_string())
+    )
+  }
+
+  /// the lp token moves to the new minter (e.g. the next version of the pair) when it claims
+  pub fn propose_new_minter(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+    expires: Expiration,
+  ) -> Result<Response, ContractError> {
+    self.assert_factory_timelock(deps.as_ref(), &info)?;
+
+    let config = self.config.load(deps.storage)?;
+    let minter = deps.api.addr_validate(&minter)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::ProposeNewMinter { minter: minter.to_string(), expires })?,
+        funds: vec![],
+      })
+      .add_attribute("action", "propose_new_minter")
+      .add_attribute("minter", minter.to_string())
+      .add_attribute("expires", expires.to_string())
+    )
+  }
+
+  pub fn drop_minter_proposal(
+    &self,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+  ) -> Result<Response, ContractError> {
+    self.assert_factory_owner(deps.as_ref(), &info)?;
+
+    let config = self.config.load(deps.storage)?;
+
+    Ok(Response::new()
+      .add_message(WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&TokenExecuteMsg::DropMinterProposal {})?,
+        funds: vec![],
+      })
+      .add_attribute("action", "drop_minter_proposal")
+    )
+  }
+}
+
+/// help function
+impl<'a> StablePairContract<'a> {
+  fn query_factory_config(&self, deps: Deps) -> StdResult<FactoryConfig> {
+    let config = self.config.load(deps.storage)?;
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: config.factory.to_string(),
+      msg: to_binary(&FactoryQueryMsg::Config {})?,
+    }))
+  }
+
+  fn assert_factory_owner(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    let factory_config = self.query_factory_config(deps)?;
+
+    if info.sender != factory_config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    Ok(())
+  }
+
+  // factory owner while the factory has no timelock, otherwise the factory executing the queued action
+  fn assert_factory_timelock(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    let config = self.config.load(deps.storage)?;
+    if info.sender == config.factory {
+      return Ok(())
+    }
+
+    let factory_config = self.query_factory_config(deps)?;
+    if info.sender != factory_config.owner {
+      return Err(ContractError::Unauthorized {})
+    }
+
+    if factory_config.timelock_delay > 0 {
+      return Err(ContractError::Timelocked {})
+    }
+
+    Ok(())
+  }
+}
+
+// commission of the asset is shared by the total liquidity of the pool
+fn add_fee_growth(pool_info: &mut TickInfo, index: usize, commission_amount: Uint128) {
+  let fee_growth = compute_fee_growth(commission_amount, pool_info.total_liquidity);
+  if index == 0 {
+    pool_info.last_fee_growth_0 = fee_growth_add(pool_info.last_fee_growth_0, fee_growth);
+  } else {
+    pool_info.last_fee_growth_1 = fee_growth_add(pool_info.last_fee_growth_1, fee_growth);
+  }
+}
+
+// transfer msg of the asset. None if nothing is left after tax
+fn transfer_after_tax(querier: &QuerierWrapper, asset: Asset, recipient: Addr) -> StdResult<Option<CosmosMsg>> {
+  let amount = match asset.info {
+    AssetInfo::Token { .. } => asset.amount,
+    AssetInfo::NativeToken { .. } => asset.deduct_tax_with::<ChainTax>(querier)?.amount,
+  };
+
+  if amount.is_zero() {
+    return Ok(None)
+  }
+
+  Ok(Some(asset.into_msg_with::<ChainTax>(querier, recipient)?))
+}
@@ -0,0 +1,69 @@
+mod error;
+mod execute;
+mod query;
+mod state;
+mod response;
+
+pub use wineswap::stable_pair::{InstantiateMsg, ExecuteMsg, QueryMsg};
+pub use wineswap::pair::MigrateMsg;
+pub use crate::error::ContractError;
+pub use crate::state::StablePairContract;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(test)]
+mod mock_querier;
+
+
+#[cfg(not(feature = "library"))]
+pub mod entry {
+  use super::*;
+
+  use cosmwasm_std::entry_point;
+  use cosmwasm_std::{Binary, Deps, DepsMut, Env, Reply, MessageInfo, Response, StdResult};
+
+  #[entry_point]
+  pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+  ) -> StdResult<Response> {
+    let tract = StablePairContract::default();
+    tract.instantiate(deps, env, info, msg)
+  }
+
+  #[entry_point]
+  pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply
+  ) -> StdResult<Response> {
+    let tract = StablePairContract::default();
+    tract.reply(deps, env, msg)
+  }
+
+  #[entry_point]
+  pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+  ) -> Result<Response, ContractError> {
+    let tract = StablePairContract::default();
+    tract.execute(deps, env, info, msg)
+  }
+
+  #[entry_point]
+  pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let tract = StablePairContract::default();
+    tract.migrate(deps, env, msg)
+  }
+
+  #[entry_point]
+  pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let tract = StablePairContract::default();
+    tract.query(deps, env, msg)
+  }
+}
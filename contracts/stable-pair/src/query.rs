@@ -0,0 +1,287 @@
+use cosmwasm_std::{to_binary, Addr, Binary, Decimal, Deps, Env, QuerierWrapper, QueryRequest, StdError, StdResult, Uint128,
+  WasmQuery};
+
+use wineswap::stable_pair::{AmpConfigResponse, PoolResponse, QueryMsg};
+use wineswap::factory::{FeeDiscountResponse, QueryMsg as FactoryQueryMsg};
+use wineswap::pair::{EffectiveFeeResponse, PairInfoResponse, ReverseSimulationResponse, SimulationDetail, SimulationResponse, SwapStepResponse,
+  TickInfoResponse, TickInfosResponse, WithdrawCalculationResponse};
+use wineswap::asset::{Asset, AssetInfo};
+use wineswap::lp_token::{LiquidityInfoResponse, QueryMsg::LiquidityInfo};
+use wineswap_math::tick::{get_tick_from_price_sqrt, tick_to_tick_index};
+use wineswap_math::stable::{compute_stable_price_sqrt, compute_stable_swap, compute_stable_swap_reverse};
+use wineswap_math::price::price_sqrt_to_price;
+use wineswap_math::fee::discount_fee_rate;
+use crate::state::{StablePairContract, POOL_TICK_INDEX};
+
+impl<'a> StablePairContract<'a> {
+  fn pair_info(&self, deps: Deps, env: Env) -> StdResult<PairInfoResponse> {
+    let config = self.config.load(deps.storage)?;
+    let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+    let pools = self.pools.load(deps.storage)?;
+    // marginal price of the curve
+    let price_sqrt = compute_stable_price_sqrt(amp, pools)?;
+    let price = price_sqrt_to_price(price_sqrt);
+    let current_tick_index = tick_to_tick_index(get_tick_from_price_sqrt(price_sqrt), config.tick_space);
+
+    Ok(PairInfoResponse {
+      liquidity_token: config.liquidity_token.to_string(),
+      asset_infos: config.asset_infos,
+      tick_space: config.tick_space,
+      fee_rate: config.fee_rate,
+      price,
+      current_price_sqrt: price_sqrt,
+      current_tick_index
+    })
+  }
+
+  fn tick_info(&self, deps: Deps, tick_index: i32) -> StdResult<TickInfoResponse> {
+    if tick_index != POOL_TICK_INDEX {
+      return Err(StdError::not_found("wineswap::pair::TickInfo"));
+    }
+
+    Ok(TickInfoResponse {
+      tick_index,
+      tick_info: self.pool_info.load(deps.storage)?,
+    })
+  }
+
+  fn tick_infos(&self, deps: Deps, start_after: Option<i32>, limit: Option<u32>) -> StdResult<TickInfosResponse> {
+    // the whole pool is a single tick
+    let infos = match (start_after, limit) {
+      (_, Some(0)) => vec![],
+      (Some(start_after), _) if start_after >= POOL_TICK_INDEX => vec![],
+      _ => vec![self.tick_info(deps, POOL_TICK_INDEX)?],
+    };
+
+    Ok(TickInfosResponse { infos })
+  }
+
+  fn withdraw_calculation(&self, deps: Deps, token_id: String) -> StdResult<WithdrawCalculationResponse> {
+    let config = self.config.load(deps.storage)?;
+    let liquidity = self.get_liquidity_info(deps.querier, config.liquidity_token.to_string(), token_id)?;
+    let pools = self.pools.load(deps.storage)?;
+    let pool_info = self.pool_info.load(deps.storage)?;
+
+    let asset_infos: [AssetInfo; 2] = config.asset_infos;
+    let assets = [
+      Asset {
+        info: asset_infos[0].clone(),
+        amount: pools[0].multiply_ratio(liquidity.liquidity, pool_info.total_liquidity),
+      },
+      Asset {
+        info: asset_infos[1].clone(),
+        amount: pools[1].multiply_ratio(liquidity.liquidity, pool_info.total_liquidity),
+      },
+    ];
+
+    Ok(WithdrawCalculationResponse { assets })
+  }
+
+  fn swap_simulation(
+    &self,
+    deps: Deps,
+    env: Env,
+    asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  ) -> StdResult<SimulationResponse> {
+    let mut config = self.config.load(deps.storage)?;
+    config.fee_rate = self.trader_fee_rate(deps, config.fee_rate, trader)?;
+    let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+    let pools = self.pools.load(deps.storage)?;
+
+    let (offer_index, ask_index) = self.asset_index(&config.asset_infos, &asset.info)?;
+    let (return_amount, commission_amount) = compute_stable_swap(
+      amp,
+      pools[offer_index],
+      pools[ask_index],
+      asset.amount,
+      config.fee_rate,
+    )?;
+
+    let detail = if detailed.unwrap_or(false) {
+      Some(self.simulation_detail(deps, amp, offer_index, asset.amount, return_amount, commission_amount)?)
+    } else {
+      None
+    };
+
+    Ok(SimulationResponse {
+      return_amount: return_amount.checked_sub(commission_amount)?,
+      commission_amount,
+      detail,
+    })
+  }
+
+  fn swap_simulation_reverse(
+    &self,
+    deps: Deps,
+    env: Env,
+    asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  ) -> StdResult<ReverseSimulationResponse> {
+    let mut config = self.config.load(deps.storage)?;
+    config.fee_rate = self.trader_fee_rate(deps, config.fee_rate, trader)?;
+    let amp = self.amp_config.load(deps.storage)?.amp(env.block.time.seconds());
+    let pools = self.pools.load(deps.storage)?;
+
+    let (ask_index, offer_index) = self.asset_index(&config.asset_infos, &asset.info)?;
+    let (offer_amount, commission_amount) = compute_stable_swap_reverse(
+      amp,
+      pools[offer_index],
+      pools[ask_index],
+      asset.amount,
+      config.fee_rate,
+    )?;
+
+    let detail = if detailed.unwrap_or(false) {
+      let return_amount = asset.amount.checked_add(commission_amount)?;
+      Some(self.simulation_detail(deps, amp, offer_index, offer_amount, return_amount, commission_amount)?)
+    } else {
+      None
+    };
+
+    Ok(ReverseSimulationResponse {
+      offer_amount,
+      commission_amount,
+      detail,
+    })
+  }
+
+  /// fee discount of the trader in the factory
+  pub fn fee_discount(&self, deps: Deps, trader: &Addr) -> StdResult<Decimal> {
+    let config = self.config.load(deps.storage)?;
+    let res: FeeDiscountResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: config.factory.to_string(),
+      msg: to_binary(&FactoryQueryMsg::FeeDiscount { address: trader.to_string() })?,
+    }))?;
+    Ok(res.discount)
+  }
+
+  fn trader_fee_rate(&self, deps: Deps, fee_rate: Decimal, trader: Option<String>) -> StdResult<Decimal> {
+    match trader {
+      Some(trader) => {
+        let trader = deps.api.addr_validate(&trader)?;
+        Ok(discount_fee_rate(fee_rate, self.fee_discount(deps, &trader)?))
+      },
+      None => Ok(fee_rate),
+    }
+  }
+
+  fn effective_fee(&self, deps: Deps, trader: String) -> StdResult<EffectiveFeeResponse> {
+    let config = self.config.load(deps.storage)?;
+    let discount = self.fee_discount(deps, &deps.api.addr_validate(&trader)?)?;
+
+    Ok(EffectiveFeeResponse {
+      fee_rate: config.fee_rate,
+      discount,
+      effective_fee_rate: discount_fee_rate(config.fee_rate, discount),
+    })
+  }
+
+  // the whole pool is a single step, return_amount includes commission
+  fn simulation_detail(
+    &self,
+    deps: Deps,
+    amp: u64,
+    offer_index: usize,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    commission_amount: Uint128,
+  ) -> StdResult<SimulationDetail> {
+    let tick_space = self.config.load(deps.storage)?.tick_space;
+    let pools = self.pools.load(deps.storage)?;
+    let pool_info = self.pool_info.load(deps.storage)?;
+
+    let mut next_pools = pools;
+    next_pools[offer_index] = next_pools[offer_index].checked_add(offer_amount)?;
+    next_pools[1 - offer_index] = next_pools[1 - offer_index].checked_sub(return_amount)?;
+
+    let price_sqrt_before = compute_stable_price_sqrt(amp, pools)?;
+    let price_sqrt_after = compute_stable_price_sqrt(amp, next_pools)?;
+
+    Ok(SimulationDetail {
+      steps: vec![SwapStepResponse {
+        tick_index: POOL_TICK_INDEX,
+        liquidity: pool_info.total_liquidity,
+        offer_amount,
+        return_amount: return_amount.checked_sub(commission_amount)?,
+        commission_amount,
+        price_sqrt_before,
+        price_sqrt_after,
+      }],
+      current_tick_index: tick_to_tick_index(get_tick_from_price_sqrt(price_sqrt_after), tick_space),
+      current_price_sqrt: price_sqrt_after,
+      price: price_sqrt_to_price(price_sqrt_after),
+    })
+  }
+
+  fn pool(&self, deps: Deps) -> StdResult<PoolResponse> {
+    let config = self.config.load(deps.storage)?;
+    let pools = self.pools.load(deps.storage)?;
+    let pool_info = self.pool_info.load(deps.storage)?;
+
+    Ok(PoolResponse {
+      assets: [
+        Asset {
+          info: config.asset_infos[0].clone(),
+          amount: pools[0],
+        },
+        Asset {
+          info: config.asset_infos[1].clone(),
+          amount: pools[1],
+        },
+      ],
+      total_liquidity: pool_info.total_liquidity,
+    })
+  }
+
+  fn amp(&self, deps: Deps, env: Env) -> StdResult<AmpConfigResponse> {
+    let amp_config = self.amp_config.load(deps.storage)?;
+
+    Ok(AmpConfigResponse {
+      amp: amp_config.amp(env.block.time.seconds()),
+      next_amp: amp_config.next_amp,
+      next_amp_time: amp_config.next_amp_time,
+    })
+  }
+
+  // (index of the asset, index of the other asset)
+  fn asset_index(&self, asset_infos: &[AssetInfo; 2], asset_info: &AssetInfo) -> StdResult<(usize, usize)> {
+    if asset_info.equal(&asset_infos[0]) {
+      Ok((0, 1))
+    } else if asset_info.equal(&asset_infos[1]) {
+      Ok((1, 0))
+    } else {
+      Err(StdError::generic_err("Token missmatched"))
+    }
+  }
+
+  pub fn get_liquidity_info(&self, querier: QuerierWrapper, lp_contract: String, token_id: String) -> StdResult<LiquidityInfoResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+      contract_addr: lp_contract,
+      msg: to_binary(&LiquidityInfo{ token_id })?,
+    }))
+  }
+}
+
+impl<'a> StablePairContract<'a> {
+  pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+      QueryMsg::PairInfo {} => to_binary(&self.pair_info(deps, env)?),
+      QueryMsg::TickInfo { tick_index } => to_binary(&self.tick_info(deps, tick_index)?),
+      QueryMsg::TickInfos { start_after, limit } => {
+        to_binary(&self.tick_infos(deps, start_after, limit)?)
+      },
+      QueryMsg::WithdrawCalculation { token_id }
+        => to_binary(&self.withdraw_calculation(deps, token_id)?),
+      QueryMsg::Simulation { offer_asset, detailed, trader }
+        => to_binary(&self.swap_simulation(deps, env, offer_asset, detailed, trader)?),
+      QueryMsg::ReverseSimulation { ask_asset, detailed, trader }
+        => to_binary(&self.swap_simulation_reverse(deps, env, ask_asset, detailed, trader)?),
+      QueryMsg::EffectiveFee { trader } => to_binary(&self.effective_fee(deps, trader)?),
+      QueryMsg::Pool {} => to_binary(&self.pool(deps)?),
+      QueryMsg::AmpConfig {} => to_binary(&self.amp(deps, env)?),
+    }
+  }
+}
@@ -0,0 +1,76 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+
+use wineswap::pair::TickInfo;
+use wineswap::asset::AssetInfo;
+use wineswap_math::stable::compute_amp;
+
+/// every position of the stable pair is this tick index
+pub const POOL_TICK_INDEX: i32 = 0;
+
+pub struct StablePairContract<'a> {
+  pub config: Item<'a, Config>,
+  pub amp_config: Item<'a, AmpConfig>,
+  // [asset0, asset1] in the pool, commission is not included
+  pub pools: Item<'a, [Uint128; 2]>,
+  // fee growth and total liquidity of the whole pool
+  pub pool_info: Item<'a, TickInfo>,
+}
+
+/// same key and format with `pair::Config`, so factory can read it
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Config {
+  pub asset_infos: [AssetInfo; 2],
+  /// only used to report current_tick_index
+  pub tick_space: u16,
+  pub fee_rate: Decimal,
+  pub liquidity_token: Addr,
+  /// factory owner can ramp amp
+  pub factory: Addr,
+  #[serde(default)]
+  pub asset_decimals: Option<[u8; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AmpConfig {
+  pub init_amp: u64,
+  pub init_amp_time: u64,
+  pub next_amp: u64,
+  pub next_amp_time: u64,
+}
+
+impl AmpConfig {
+  pub fn amp(&self, time: u64) -> u64 {
+    compute_amp(self.init_amp, self.init_amp_time, self.next_amp, self.next_amp_time, time)
+  }
+}
+
+impl Default for StablePairContract<'static> {
+  fn default() -> Self {
+    Self::new(
+      "config",
+      "amp_config",
+      "pools",
+      "pool_info",
+    )
+  }
+}
+
+impl<'a> StablePairContract<'a> {
+  fn new(
+    config_key: &'a str,
+    amp_config_key: &'a str,
+    pools_key: &'a str,
+    pool_info_key: &'a str,
+  ) -> Self {
+    Self {
+      config: Item::new(config_key),
+      amp_config: Item::new(amp_config_key),
+      pools: Item::new(pools_key),
+      pool_info: Item::new(pool_info_key),
+    }
+  }
+}
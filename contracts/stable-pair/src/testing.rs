@@ -0,0 +1,804 @@
+use cosmwasm_std::{attr, to_binary, from_binary, Addr, Decimal, CosmosMsg, SubMsg, WasmMsg, Uint128, Uint256, Coin, ReplyOn, StdError};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use wineswap::stable_pair::{InstantiateMsg, ExecuteMsg, QueryMsg, AmpConfigResponse, PoolResponse};
+use wineswap::pair::{EffectiveFeeResponse, PairInfoResponse, Referral, TickIndexes, TickInfosResponse, SimulationResponse, ReverseSimulationResponse};
+use wineswap::lp_token::{InstantiateMsg as TokenInstantiateMsg, ExecuteMsg as TokenExecuteMsg, LiquidityInfoResponse};
+use wineswap::asset::{Asset, AssetInfo};
+
+use cw0::Expiration;
+use cw20::Cw20ExecuteMsg;
+
+use wineswap_math::{
+  stable::{compute_stable_liquidity, compute_stable_swap, MIN_RAMP_TIME},
+  fee::compute_fee_growth,
+};
+use crate::state::StablePairContract;
+use crate::mock_querier::mock_dependencies;
+use crate::error::ContractError;
+
+fn asset_infos() -> [AssetInfo; 2] {
+  [
+    AssetInfo::NativeToken { denom: "uusd".to_string() },
+    AssetInfo::Token { contract_addr: "ust".to_string() },
+  ]
+}
+
+fn instantiate_msg() -> InstantiateMsg {
+  InstantiateMsg {
+    asset_infos: asset_infos(),
+    fee_rate: Decimal::from_ratio(4u128, 10000u128),
+    token_code_id: 123,
+    tick_space: 1,
+    amp: 100,
+    asset_decimals: Some([6, 6]),
+  }
+}
+
+#[test]
+fn instantiate_test() {
+  let pair = StablePairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+
+  let info = mock_info("factory", &[]);
+  let env = mock_env();
+  let res = pair.instantiate(deps.as_mut(), env.clone(), info, instantiate_msg()).unwrap();
+
+  assert_eq!(
+    res.messages,
+    vec![SubMsg {
+      msg: WasmMsg::Instantiate {
+        admin: Some("factory".to_string()),
+        code_id: 123u64,
+        label: "".to_string(),
+        funds: vec![],
+        msg: to_binary(&TokenInstantiateMsg{
+          name: "wineswap stable liquidity token".to_string(),
+          symbol: ("WINELP".to_string()),
+          minter: env.contract.address.to_string(),
+        }).unwrap(),
+      }
+      .into(),
+      gas_limit: None,
+      id: 1,
+      reply_on: ReplyOn::Success,
+    }]
+  );
+
+  let config = pair.config.load(&deps.storage).unwrap();
+  assert_eq!(config.asset_infos, asset_infos());
+  assert_eq!(config.factory, Addr::unchecked("factory"));
+  assert_eq!(config.asset_decimals, Some([6, 6]));
+  // reply doesn't execute so it is still temp addr
+  assert_eq!(config.liquidity_token, "factory".to_string());
+
+  let res: AmpConfigResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::AmpConfig {}).unwrap()).unwrap();
+  assert_eq!(res, AmpConfigResponse {
+    amp: 100,
+    next_amp: 100,
+    next_amp_time: env.block.time.seconds(),
+  });
+
+  // invalid amp
+  let mut msg = instantiate_msg();
+  msg.amp = 0;
+  let res = pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), msg);
+
+  match res {
+    Err(_) => assert!(true),
+    _ => panic!("Must return invalid amp error"),
+  }
+
+  // different decimals
+  let mut msg = instantiate_msg();
+  msg.asset_decimals = Some([6, 8]);
+  let res = pair.instantiate(deps.as_mut(), env, mock_info("factory", &[]), msg);
+
+  match res {
+    Err(StdError::GenericErr { msg }) => assert_eq!(msg, "Asset decimals must be the same"),
+    _ => panic!("Must return generic error"),
+  }
+}
+
+#[test]
+fn provide_withdraw_test() {
+  let pair = StablePairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+  pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), instantiate_msg()).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: None,
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "ust".to_string()},
+        amount: Uint128::from(1000000u128)
+      }
+    ]
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000u128)}]);
+  let res = pair.execute(deps.as_mut(), env.clone(), info, provide_msg).unwrap();
+
+  // balanced first provide is D
+  let liquidity = Uint128::from(2000000u128);
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "ust".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+          owner: "user".to_string(),
+          recipient: env.contract.address.to_string(),
+          amount: Uint128::from(1000000u128),
+        }).unwrap(),
+        funds: vec![],
+      })),
+      // every position is tick 0
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::Mint {
+          owner: "user".to_string(),
+          liquidity,
+          upper_tick_index: 0,
+          lower_tick_index: 0,
+        }).unwrap(),
+        funds: vec![],
+      })),
+    ]
+  );
+
+  deps.querier.with_lp_infos(&[
+    (&"0".to_string(), &LiquidityInfoResponse{
+      approvals: vec![],
+      liquidity,
+      upper_tick_index: 0,
+      lower_tick_index: 0,
+      owner: Addr::unchecked("user")
+    })
+  ]);
+
+  // lp token reads the pool as a single tick
+  let res: TickInfosResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::TickInfos {
+    start_after: Some(-1),
+    limit: Some(1),
+  }).unwrap()).unwrap();
+  assert_eq!(res.infos.len(), 1);
+  assert_eq!(res.infos[0].tick_index, 0);
+  assert_eq!(res.infos[0].tick_info.total_liquidity, liquidity);
+
+  // imbalanced provide with zap in
+  let zap_in_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+      amount: Uint128::from(100000u128)
+    },
+    tick_indexes: TickIndexes {
+      upper_tick_index: 0,
+      lower_tick_index: 0,
+    },
+    min_liquidity: None,
+  };
+
+  let zap_liquidity = compute_stable_liquidity(
+    100,
+    [Uint128::from(1000000u128), Uint128::from(1000000u128)],
+    [Uint128::from(100000u128), Uint128::zero()],
+    liquidity,
+    Decimal::from_ratio(4u128, 10000u128),
+  ).unwrap();
+
+  // less than proportional share
+  assert!(zap_liquidity < Uint128::from(200000u128));
+
+  let info = mock_info("user2", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(100000u128)}]);
+  let res = pair.execute(deps.as_mut(), env.clone(), info, zap_in_msg).unwrap();
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::Mint {
+          owner: "user2".to_string(),
+          liquidity: zap_liquidity,
+          upper_tick_index: 0,
+          lower_tick_index: 0,
+        }).unwrap(),
+        funds: vec![],
+      })),
+    ]
+  );
+
+  let res: PoolResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Pool {}).unwrap()).unwrap();
+  assert_eq!(res.assets[0].amount, Uint128::from(1100000u128));
+  assert_eq!(res.assets[1].amount, Uint128::from(1000000u128));
+  assert_eq!(res.total_liquidity, liquidity + zap_liquidity);
+
+  // partial withdraw
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity {
+    token_id: "0".to_string(),
+    amount: Some(Uint128::from(1000000u128)),
+    receive_asset: None,
+    minimum_receive: None,
+  };
+
+  let total_liquidity = liquidity + zap_liquidity;
+  let amount0 = Uint128::from(1100000u128).multiply_ratio(1000000u128, total_liquidity);
+  let amount1 = Uint128::from(1000000u128).multiply_ratio(1000000u128, total_liquidity);
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), withdraw_msg).unwrap();
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: amount0,
+      }.into_msg(&deps.as_mut().querier, Addr::unchecked("user")).unwrap()),
+      SubMsg::new(Asset {
+        info: AssetInfo::Token {contract_addr: "ust".to_string()},
+        amount: amount1,
+      }.into_msg(&deps.as_mut().querier, Addr::unchecked("user")).unwrap()),
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::ClaimReward {
+          token_id: "0".to_string(),
+        }).unwrap(),
+        funds: vec![],
+      })),
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::UpdateLiquidity {
+          token_id: "0".to_string(),
+          amount: Uint128::from(1000000u128),
+          add: false,
+        }).unwrap(),
+        funds: vec![],
+      })),
+    ]
+  );
+
+  // only owner can withdraw
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity {
+    token_id: "0".to_string(),
+    amount: None,
+    receive_asset: None,
+    minimum_receive: None,
+  };
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user2", &[]), withdraw_msg);
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // min liquidity
+  let zap_in_msg = ExecuteMsg::ZapIn {
+    offer_asset: Asset {
+      info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+      amount: Uint128::from(100000u128)
+    },
+    tick_indexes: TickIndexes {
+      upper_tick_index: 0,
+      lower_tick_index: 0,
+    },
+    min_liquidity: Some(Uint128::from(200000u128)),
+  };
+
+  let info = mock_info("user2", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(100000u128)}]);
+  let res = pair.execute(deps.as_mut(), env, info, zap_in_msg);
+
+  match res {
+    Err(ContractError::MinLiquidityAssertion { .. }) => assert!(true),
+    _ => panic!("Must return min liquidity assertion error"),
+  }
+}
+
+#[test]
+fn zap_out_test() {
+  let pair = StablePairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+  pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), instantiate_msg()).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: None,
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "ust".to_string()},
+        amount: Uint128::from(1000000u128)
+      }
+    ]
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000u128)}]);
+  pair.execute(deps.as_mut(), env.clone(), info, provide_msg).unwrap();
+
+  let liquidity = Uint128::from(2000000u128);
+  deps.querier.with_lp_infos(&[
+    (&"0".to_string(), &LiquidityInfoResponse{
+      approvals: vec![],
+      liquidity,
+      upper_tick_index: 0,
+      lower_tick_index: 0,
+      owner: Addr::unchecked("user")
+    })
+  ]);
+
+  // withdraw half to uusd
+  let withdraw_msg = ExecuteMsg::WithdrawLiquidity {
+    token_id: "0".to_string(),
+    amount: Some(Uint128::from(1000000u128)),
+    receive_asset: Some(AssetInfo::NativeToken {denom: "uusd".to_string()}),
+    minimum_receive: Some(Uint128::from(900000u128)),
+  };
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), withdraw_msg).unwrap();
+
+  let zap_out_msg = ExecuteMsg::ZapOut {
+    owner: "user".to_string(),
+    offer_asset: Asset {
+      info: AssetInfo::Token {contract_addr: "ust".to_string()},
+      amount: Uint128::from(500000u128),
+    },
+    receive_amount: Uint128::from(500000u128),
+    minimum_receive: Some(Uint128::from(900000u128)),
+  };
+
+  // the reward is claimed before the swap
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::ClaimReward {
+          token_id: "0".to_string(),
+        }).unwrap(),
+        funds: vec![],
+      })),
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "liquidity".to_string(),
+        msg: to_binary(&TokenExecuteMsg::UpdateLiquidity {
+          token_id: "0".to_string(),
+          amount: Uint128::from(1000000u128),
+          add: false,
+        }).unwrap(),
+        funds: vec![],
+      })),
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        msg: to_binary(&zap_out_msg).unwrap(),
+        funds: vec![],
+      })),
+    ]
+  );
+
+  // fee growth doesn't move until the swap
+  let pool_info = pair.pool_info.load(&deps.storage).unwrap();
+  assert_eq!(pool_info.last_fee_growth_0, Uint256::zero());
+  assert_eq!(pool_info.last_fee_growth_1, Uint256::zero());
+
+  // only the pair can zap out
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), zap_out_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let (return_amount, commission_amount) = compute_stable_swap(
+    100,
+    Uint128::from(500000u128),
+    Uint128::from(500000u128),
+    Uint128::from(500000u128),
+    Decimal::from_ratio(4u128, 10000u128),
+  ).unwrap();
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), zap_out_msg).unwrap();
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(500000u128) + return_amount - commission_amount,
+      }.into_msg(&deps.as_mut().querier, Addr::unchecked("user")).unwrap()),
+    ]
+  );
+
+  // the commission goes to the remaining liquidity
+  let pool_info = pair.pool_info.load(&deps.storage).unwrap();
+  assert_eq!(pool_info.last_fee_growth_0, compute_fee_growth(commission_amount, Uint128::from(1000000u128)));
+
+  let res: PoolResponse = from_binary(&pair.query(deps.as_ref(), mock_env(), QueryMsg::Pool {}).unwrap()).unwrap();
+  assert_eq!(res.assets[0].amount, Uint128::from(500000u128) - return_amount);
+  assert_eq!(res.assets[1].amount, Uint128::from(1000000u128));
+
+  // minimum receive
+  let res = pair.execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), ExecuteMsg::ZapOut {
+    owner: "user".to_string(),
+    offer_asset: Asset {
+      info: AssetInfo::Token {contract_addr: "ust".to_string()},
+      amount: Uint128::from(500000u128),
+    },
+    receive_amount: Uint128::from(500000u128),
+    minimum_receive: Some(Uint128::from(1000000u128)),
+  });
+
+  match res {
+    Err(ContractError::MinimumReceiveAssertion { .. }) => assert!(true),
+    _ => panic!("Must return minimum receive assertion error"),
+  }
+}
+
+#[test]
+fn swap_test() {
+  let pair = StablePairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+  pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), instantiate_msg()).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  deps.querier.with_tax(
+    Decimal::zero(),
+    &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+  );
+
+  let provide_msg = ExecuteMsg::ProvideLiquidity {
+    token_id: None,
+    tick_indexes: None,
+    assets: [
+      Asset {
+        info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+        amount: Uint128::from(1000000000u128)
+      },
+      Asset {
+        info: AssetInfo::Token {contract_addr: "ust".to_string()},
+        amount: Uint128::from(1000000000u128)
+      }
+    ]
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(1000000000u128)}]);
+  pair.execute(deps.as_mut(), env.clone(), info, provide_msg).unwrap();
+
+  let offer_asset = Asset {
+    info: AssetInfo::NativeToken {denom: "uusd".to_string()},
+    amount: Uint128::from(100000000u128)
+  };
+
+  let simulation: SimulationResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Simulation {
+    offer_asset: offer_asset.clone(),
+    detailed: None,
+    trader: None,
+  }).unwrap()).unwrap();
+
+  let (return_amount, commission_amount) = compute_stable_swap(
+    100,
+    Uint128::from(1000000000u128),
+    Uint128::from(1000000000u128),
+    Uint128::from(100000000u128),
+    Decimal::from_ratio(4u128, 10000u128),
+  ).unwrap();
+
+  assert_eq!(simulation, SimulationResponse {
+    return_amount: return_amount - commission_amount,
+    commission_amount,
+    detail: None,
+  });
+  // much less slippage than constant product
+  assert!(simulation.return_amount > Uint128::from(99000000u128));
+
+  // half of the fee for "discounted" in the factory
+  let discounted: SimulationResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Simulation {
+    offer_asset: offer_asset.clone(),
+    detailed: None,
+    trader: Some("discounted".to_string()),
+  }).unwrap()).unwrap();
+  let (_, discounted_commission_amount) = compute_stable_swap(
+    100,
+    Uint128::from(1000000000u128),
+    Uint128::from(1000000000u128),
+    Uint128::from(100000000u128),
+    Decimal::from_ratio(2u128, 10000u128),
+  ).unwrap();
+  assert_eq!(discounted.commission_amount, discounted_commission_amount);
+
+  let effective_fee: EffectiveFeeResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::EffectiveFee {
+    trader: "discounted".to_string(),
+  }).unwrap()).unwrap();
+  assert_eq!(effective_fee.effective_fee_rate, Decimal::from_ratio(2u128, 10000u128));
+
+  // reverse simulation gives the offer amount back
+  let reverse: ReverseSimulationResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::ReverseSimulation {
+    ask_asset: Asset {
+      info: AssetInfo::Token {contract_addr: "ust".to_string()},
+      amount: simulation.return_amount,
+    },
+    detailed: None,
+    trader: None,
+  }).unwrap()).unwrap();
+  assert!(reverse.offer_amount >= Uint128::from(99999990u128) && reverse.offer_amount <= Uint128::from(100000010u128));
+
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset: offer_asset.clone(),
+    to: None,
+    belief_price: None,
+    max_slippage: None,
+    referral: None,
+  };
+
+  // whole pool is a single step
+  let detail = from_binary::<SimulationResponse>(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Simulation {
+    offer_asset: offer_asset.clone(),
+    detailed: Some(true),
+    trader: None,
+  }).unwrap()).unwrap().detail.unwrap();
+  assert_eq!(detail.steps.len(), 1);
+  assert_eq!(detail.steps[0].return_amount, simulation.return_amount);
+  assert!(detail.steps[0].price_sqrt_after < detail.steps[0].price_sqrt_before);
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(100000000u128)}]);
+  let res = pair.execute(deps.as_mut(), env.clone(), info, swap_msg).unwrap();
+
+  let pair_info: PairInfoResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::PairInfo {}).unwrap()).unwrap();
+  assert_eq!(pair_info.current_price_sqrt, detail.current_price_sqrt);
+  assert_eq!(pair_info.current_tick_index, detail.current_tick_index);
+
+  assert_eq!(
+    res.messages,
+    vec![
+      SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "ust".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+          recipient: "user".to_string(),
+          amount: simulation.return_amount,
+        }).unwrap(),
+        funds: vec![],
+      })),
+    ]
+  );
+
+  // commission is not in the pool, it is shared as fee growth
+  let res: PoolResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Pool {}).unwrap()).unwrap();
+  assert_eq!(res.assets[0].amount, Uint128::from(1100000000u128));
+  assert_eq!(res.assets[1].amount, Uint128::from(1000000000u128) - return_amount);
+
+  let pool_info = pair.pool_info.load(&deps.storage).unwrap();
+  assert_eq!(pool_info.last_fee_growth_0, compute_fee_growth(Uint128::zero(), res.total_liquidity));
+  assert_eq!(pool_info.last_fee_growth_1, compute_fee_growth(commission_amount, res.total_liquidity));
+
+  // slippage
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset: offer_asset.clone(),
+    to: None,
+    belief_price: Some(Decimal::from_ratio(99u128, 100u128)),
+    max_slippage: Some(Decimal::from_ratio(1u128, 100u128)),
+    referral: None,
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(100000000u128)}]);
+  let res = pair.execute(deps.as_mut(), env.clone(), info, swap_msg);
+
+  match res {
+    Err(ContractError::MaxSlippage {}) => assert!(true),
+    _ => panic!("Must return max slippage error"),
+  }
+
+  // referral commission is capped to 100 bps of the factory
+  let simulation: SimulationResponse = from_binary(&pair.query(deps.as_ref(), env.clone(), QueryMsg::Simulation {
+    offer_asset: offer_asset.clone(),
+    detailed: None,
+    trader: None,
+  }).unwrap()).unwrap();
+  let referral_amount = simulation.return_amount.multiply_ratio(100u128, 10000u128);
+
+  let swap_msg = ExecuteMsg::Swap {
+    offer_asset,
+    to: None,
+    belief_price: None,
+    max_slippage: None,
+    referral: Some(Referral { address: "referrer".to_string(), commission_bps: 500 }),
+  };
+
+  let info = mock_info("user", &[Coin{ denom: "uusd".to_string(), amount: Uint128::from(100000000u128)}]);
+  let res = pair.execute(deps.as_mut(), env, info, swap_msg).unwrap();
+  assert_eq!(
+    res.messages[1],
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+      contract_addr: "ust".to_string(),
+      msg: to_binary(&Cw20ExecuteMsg::Transfer {
+        recipient: "referrer".to_string(),
+        amount: referral_amount,
+      }).unwrap(),
+      funds: vec![],
+    }))
+  );
+  assert!(res.attributes.contains(&attr("return_amount", (simulation.return_amount - referral_amount).to_string())));
+  assert!(res.attributes.contains(&attr("referral_amount", referral_amount.to_string())));
+}
+
+#[test]
+fn ramp_amp_test() {
+  let pair = StablePairContract::default();
+
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+  pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), instantiate_msg()).unwrap();
+
+  let now = env.block.time.seconds();
+
+  // only factory owner
+  let ramp_msg = ExecuteMsg::RampAmp {
+    next_amp: 200,
+    next_amp_time: now + MIN_RAMP_TIME,
+  };
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), ramp_msg.clone());
+
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // too fast
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::RampAmp {
+    next_amp: 200,
+    next_amp_time: now + 100,
+  });
+
+  match res {
+    Err(ContractError::InvalidRampTime {}) => assert!(true),
+    _ => panic!("Must return invalid ramp time error"),
+  }
+
+  // too big change
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::RampAmp {
+    next_amp: 1001,
+    next_amp_time: now + MIN_RAMP_TIME,
+  });
+
+  match res {
+    Err(ContractError::InvalidAmp {}) => assert!(true),
+    _ => panic!("Must return invalid amp error"),
+  }
+
+  pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ramp_msg).unwrap();
+
+  // half way
+  let mut half_env = mock_env();
+  half_env.block.time = half_env.block.time.plus_seconds(MIN_RAMP_TIME / 2);
+  let res: AmpConfigResponse = from_binary(&pair.query(deps.as_ref(), half_env.clone(), QueryMsg::AmpConfig {}).unwrap()).unwrap();
+  assert_eq!(res, AmpConfigResponse {
+    amp: 150,
+    next_amp: 200,
+    next_amp_time: now + MIN_RAMP_TIME,
+  });
+
+  // stop at the current amp
+  pair.execute(deps.as_mut(), half_env.clone(), mock_info("owner", &[]), ExecuteMsg::StopRampAmp {}).unwrap();
+
+  let mut end_env = mock_env();
+  end_env.block.time = end_env.block.time.plus_seconds(MIN_RAMP_TIME);
+  let res: AmpConfigResponse = from_binary(&pair.query(deps.as_ref(), end_env.clone(), QueryMsg::AmpConfig {}).unwrap()).unwrap();
+  assert_eq!(res, AmpConfigResponse {
+    amp: 150,
+    next_amp: 150,
+    next_amp_time: half_env.block.time.seconds(),
+  });
+
+  // with the factory timelock, only the factory (queued action) can ramp
+  deps.querier.with_timelock_delay(86400);
+  let ramp_msg = ExecuteMsg::RampAmp {
+    next_amp: 300,
+    next_amp_time: end_env.block.time.seconds() + MIN_RAMP_TIME,
+  };
+  let res = pair.execute(deps.as_mut(), end_env.clone(), mock_info("owner", &[]), ramp_msg.clone());
+
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  pair.execute(deps.as_mut(), end_env.clone(), mock_info("factory", &[]), ramp_msg).unwrap();
+  let res: AmpConfigResponse = from_binary(&pair.query(deps.as_ref(), end_env.clone(), QueryMsg::AmpConfig {}).unwrap()).unwrap();
+  assert_eq!(res.next_amp, 300);
+
+  // stopping is not timelocked
+  pair.execute(deps.as_mut(), end_env, mock_info("owner", &[]), ExecuteMsg::StopRampAmp {}).unwrap();
+}
+
+#[test]
+fn minter_proposal_test() {
+  let pair = StablePairContract::default();
+  let mut deps = mock_dependencies(&[]);
+  let env = mock_env();
+
+  let _res = pair.instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), instantiate_msg()).unwrap();
+
+  let mut config = pair.config.load(&deps.storage).unwrap();
+  config.liquidity_token = Addr::unchecked("liquidity");
+  pair.config.save(deps.as_mut().storage, &config).unwrap();
+
+  let propose_msg = ExecuteMsg::ProposeNewMinter {
+    minter: "new_pair".to_string(),
+    expires: Expiration::AtHeight(env.block.height + 100),
+  };
+
+  // only the factory owner
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), propose_msg.clone());
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  // the pair proposes to its lp token
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg.clone()).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "liquidity".to_string(),
+    msg: to_binary(&TokenExecuteMsg::ProposeNewMinter {
+      minter: "new_pair".to_string(),
+      expires: Expiration::AtHeight(env.block.height + 100),
+    }).unwrap(),
+    funds: vec![],
+  })]);
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("user", &[]), ExecuteMsg::DropMinterProposal {});
+  match res {
+    Err(ContractError::Unauthorized {}) => assert!(true),
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::DropMinterProposal {}).unwrap();
+  assert_eq!(res.messages, vec![SubMsg::new(WasmMsg::Execute {
+    contract_addr: "liquidity".to_string(),
+    msg: to_binary(&TokenExecuteMsg::DropMinterProposal {}).unwrap(),
+    funds: vec![],
+  })]);
+
+  // the factory queues it while the timelock is set
+  deps.querier.with_timelock_delay(86400);
+  let res = pair.execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), propose_msg.clone());
+  match res {
+    Err(ContractError::Timelocked {}) => assert!(true),
+    _ => panic!("Must return timelocked error"),
+  }
+
+  let _res = pair.execute(deps.as_mut(), env, mock_info("factory", &[]), propose_msg).unwrap();
+}
@@ -0,0 +1,95 @@
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128, Uint256};
+use crate::u256::uints::U256;
+use crate::u256::mul_div;
+use crate::tick::DENOMINATOR;
+
+// fee growth is commission per liquidity as Q128.128.
+// like the price it only goes up, so it is allowed to overflow and wrap around.
+// rewards only use the difference of two growths, and wrapping difference is still correct
+// as long as the growth doesn't go around whole U256 between two checkpoints.
+
+/// compute fee growth of given commission, rounding down
+pub fn compute_fee_growth(commission_amount: Uint128, liquidity: Uint128) -> Uint256 {
+  if liquidity.is_zero() {
+    return Uint256::zero()
+  }
+
+  mul_div(U256::from(commission_amount), U256::from(DENOMINATOR), U256::from(liquidity), false).into()
+}
+
+pub fn fee_growth_add(a: Uint256, b: Uint256) -> Uint256 {
+  let (sum, _) = U256::from(a).overflowing_add(U256::from(b));
+  sum.into()
+}
+
+pub fn fee_growth_sub(a: Uint256, b: Uint256) -> Uint256 {
+  let (diff, _) = U256::from(a).overflowing_sub(U256::from(b));
+  diff.into()
+}
+
+/// compute reward of the liquidity from fee growth difference, rounding down
+pub fn compute_reward(fee_growth: Uint256, liquidity: Uint128) -> StdResult<Uint128> {
+  let reward = U256::mul_shr(U256::from(fee_growth), U256::from(liquidity), 128);
+  if reward > U256::from(u128::MAX) {
+    return Err(StdError::generic_err("Overflow"));
+  }
+  Ok(Uint128::from(reward.low_u128()))
+}
+
+/// convert old Decimal fee growth to Q128.128
+pub fn decimal_to_fee_growth(fee_growth: Decimal) -> Uint256 {
+  let decimal_fraction = Uint128::from(1_000_000_000_000_000_000u128);
+  // Decimal * 10^18 is the inner value of the Decimal
+  let atomics = fee_growth * decimal_fraction;
+
+  mul_div(U256::from(atomics), U256::from(DENOMINATOR), U256::from(decimal_fraction), false).into()
+}
+
+#[test]
+fn fee_growth_test() {
+  // 1 commission for 3 liquidity
+  let growth = compute_fee_growth(Uint128::from(1u128), Uint128::from(3u128));
+  assert_eq!(compute_reward(growth, Uint128::from(3u128)).unwrap(), Uint128::zero());
+  assert_eq!(compute_reward(growth * Uint256::from(3u128), Uint128::from(3u128)).unwrap(), Uint128::from(2u128));
+
+  // small commission on huge liquidity doesn't disappear
+  let growth = compute_fee_growth(Uint128::from(1u128), Uint128::from(1_000_000_000_000_000_000_000u128));
+  assert!(!growth.is_zero());
+  assert_eq!(Decimal::from_ratio(1u128, 1_000_000_000_000_000_000_000u128), Decimal::zero());
+  assert_eq!(
+    compute_reward(growth * Uint256::from(10u128), Uint128::from(1_000_000_000_000_000_000_000u128)).unwrap(),
+    Uint128::from(9u128)
+  );
+}
+
+#[test]
+fn fee_growth_wrapping_test() {
+  let max: Uint256 = U256::MAX.into();
+  let before = max - Uint256::from(10u128);
+  let after = fee_growth_add(before, Uint256::from(30u128));
+
+  assert_eq!(after, Uint256::from(19u128));
+  assert_eq!(fee_growth_sub(after, before), Uint256::from(30u128));
+}
+
+#[test]
+fn decimal_to_fee_growth_test() {
+  assert_eq!(decimal_to_fee_growth(Decimal::one()), DENOMINATOR);
+  assert_eq!(decimal_to_fee_growth(Decimal::from_ratio(3u128, 2u128)), DENOMINATOR * Uint256::from(3u128) / Uint256::from(2u128));
+  assert_eq!(decimal_to_fee_growth(Decimal::zero()), Uint256::zero());
+}
+
+/// fee rate after the discount rate, rounding down
+pub fn discount_fee_rate(fee_rate: Decimal, discount: Decimal) -> Decimal {
+  let decimal_fraction = Uint128::from(1_000_000_000_000_000_000u128);
+  let atomics = fee_rate * decimal_fraction;
+  Decimal::from_ratio((Decimal::one() - discount) * atomics, decimal_fraction)
+}
+
+#[test]
+fn discount_fee_rate_test() {
+  let fee_rate = Decimal::from_ratio(3u128, 1000u128);
+  assert_eq!(discount_fee_rate(fee_rate, Decimal::zero()), fee_rate);
+  assert_eq!(discount_fee_rate(fee_rate, Decimal::from_ratio(1u128, 2u128)), Decimal::from_ratio(15u128, 10000u128));
+  assert_eq!(discount_fee_rate(fee_rate, Decimal::one()), Decimal::zero());
+}
@@ -2,4 +2,6 @@ pub mod u256;
 pub mod tick;
 pub mod price;
 pub mod liquidity;
-pub mod swap;
\ No newline at end of file
+pub mod swap;
+pub mod fee;
+pub mod stable;
\ No newline at end of file
@@ -135,25 +135,54 @@ pub fn compute_liquidity(
   lower_tick_index: i32,
   tick_space: u16
 ) -> Uint128 {
+  let (liquidity0, liquidity1) = compute_liquidity_of_tokens(
+    token0_amount,
+    token1_amount,
+    current_price_sqrt,
+    upper_tick_index,
+    lower_tick_index,
+    tick_space
+  );
+
+  // return the smaller one
+  std::cmp::min(liquidity0, liquidity1)
+}
+
+// liquidity that each token amount can make, Uint128::MAX for the token that range doesn't need
+pub fn compute_liquidity_of_tokens(
+  token0_amount: Uint128,
+  token1_amount: Uint128,
+  current_price_sqrt: Uint256,
+  upper_tick_index: i32,
+  lower_tick_index: i32,
+  tick_space: u16
+) -> (Uint128, Uint128) {
   if upper_tick_index < lower_tick_index {
-    return Uint128::zero()
+    return (Uint128::zero(), Uint128::zero())
   }
 
   let tick_space_i32 = i32::from(tick_space);
   let price_low_sqrt: U256 = get_tick_price_sqrt(lower_tick_index * tick_space_i32).into();
   let price_high_sqrt: U256 = get_tick_price_sqrt(upper_tick_index * tick_space_i32 + tick_space_i32).into();
   let current_price_sqrt: U256 = current_price_sqrt.into();
+  let max = U256::from(u128::MAX);
 
   // case1 out of price range (lower, token1 amount = 0), use token0 amount
   if current_price_sqrt < price_low_sqrt {  
-    U256::mul_shr(
-      mul_div(price_high_sqrt, price_low_sqrt, price_high_sqrt - price_low_sqrt, false),
-      U256::from(token0_amount),
-      128
-    ).into()
+    (
+      U256::mul_shr(
+        mul_div(price_high_sqrt, price_low_sqrt, price_high_sqrt - price_low_sqrt, false),
+        U256::from(token0_amount),
+        128
+      ).into(),
+      Uint128::MAX
+    )
   // case2 out of price range (higher, token0 amount = 0), use token1 amount
   } else if current_price_sqrt >= price_high_sqrt{
-    div(U256::from(token1_amount) * U256::from(DENOMINATOR),  price_high_sqrt - price_low_sqrt, false).into()
+    (
+      Uint128::MAX,
+      div(U256::from(token1_amount) * U256::from(DENOMINATOR),  price_high_sqrt - price_low_sqrt, false).into()
+    )
   // case3 price in the price range
   } else {
     let liquidity0 = U256::mul_shr(
@@ -162,14 +191,9 @@ pub fn compute_liquidity(
       128
     );
 
-    let liquidity1 = div(U256::from(token1_amount) * U256::from(DENOMINATOR),  current_price_sqrt - price_low_sqrt, false).into();
+    let liquidity1 = div(U256::from(token1_amount) * U256::from(DENOMINATOR),  current_price_sqrt - price_low_sqrt, false);
 
-    // return the smaller one
-    if liquidity0 > liquidity1 {
-      liquidity1.into()
-    } else {
-      liquidity0.into()
-    }
+    (std::cmp::min(liquidity0, max).into(), std::cmp::min(liquidity1, max).into())
   }
 }
 
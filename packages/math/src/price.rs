@@ -1,9 +1,19 @@
-use cosmwasm_std::{Decimal256, Uint256, Uint128};
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint256, Uint128};
 use wineswap::asset::TokenNumber;
 use crate::u256::uints::{U512, U256};
 use crate::u256::{div};
 use crate::tick::DENOMINATOR;
 
+/// max decimals of an asset, 10^decimals must fit the price conversion
+pub const MAX_DECIMALS: u8 = 18;
+
+fn check_decimals(decimals: [u8; 2]) -> StdResult<()> {
+  if decimals[0] > MAX_DECIMALS || decimals[1] > MAX_DECIMALS {
+    return Err(StdError::generic_err("Invalid decimals"))
+  }
+  Ok(())
+}
+
 pub fn compute_price(
   price_sqrt: Uint256, // Q128.128
   liquidity: Uint128,
@@ -65,4 +75,72 @@ pub fn price_sqrt_to_price(
     U256::from(price),
     DENOMINATOR
   )
-}
\ No newline at end of file
+}
+/// human readable price (asset1 per asset0, with decimals [decimals0, decimals1])
+/// to square root price (Q128.128), rounding down
+pub fn price_to_price_sqrt(price: Decimal256, decimals: [u8; 2]) -> StdResult<Uint256> {
+  check_decimals(decimals)?;
+  let decimal_fraction = U512::exp10(18);
+  let atomics = U512::from(U256::from(price * Uint256::from(1_000_000_000_000_000_000u128)));
+
+  // raw price = price * 10^decimals1 / 10^decimals0
+  let num = atomics * U512::exp10(decimals[1] as usize);
+  let den = decimal_fraction * U512::exp10(decimals[0] as usize);
+  if num.is_zero() {
+    return Ok(Uint256::zero())
+  }
+
+  // sqrt(raw price * 2^256), shift less if it overflows and shift back after sqrt
+  let shift = std::cmp::min(256, (511 - num.bits()) & !1);
+  let price_sqrt = ((num << shift) / den).integer_sqrt() << ((256 - shift) / 2);
+
+  Ok(U256::from(price_sqrt).into())
+}
+
+/// square root price (Q128.128) to human readable price (asset1 per asset0, with decimals [decimals0, decimals1])
+pub fn price_sqrt_to_decimal_price(price_sqrt: Uint256, decimals: [u8; 2]) -> StdResult<Decimal256> {
+  check_decimals(decimals)?;
+  let price_sqrt: U512 = U256::from(price_sqrt).into();
+
+  // price_sqrt^2 / 2^256 * 10^decimals0 / 10^decimals1 in 18 decimal places
+  let atomics = ((price_sqrt * price_sqrt) >> 128) * U512::exp10(18 + decimals[0] as usize)
+    / (U512::from(U256::from(DENOMINATOR)) * U512::exp10(decimals[1] as usize));
+  let atomics = if atomics > U512::from(U256::MAX) {
+    U256::MAX
+  } else {
+    U256::from(atomics)
+  };
+
+  Ok(Decimal256::from_ratio(atomics, Uint256::from(1_000_000_000_000_000_000u128)))
+}
+
+#[test]
+fn price_to_price_sqrt_test() {
+  // price 4 -> price_sqrt 2
+  assert_eq!(
+    price_to_price_sqrt(Decimal256::from_ratio(4u128, 1u128), [0, 0]).unwrap(),
+    DENOMINATOR * Uint256::from(2u128)
+  );
+
+  // 1 asset0 (6 decimals) = 2 asset1 (8 decimals), raw price is 200
+  let price_sqrt = price_to_price_sqrt(Decimal256::from_ratio(2u128, 1u128), [6, 8]).unwrap();
+  assert_eq!(
+    price_sqrt_to_price(price_sqrt) * Uint256::from(1000u128),
+    Uint256::from(199999u128)
+  );
+  assert_eq!(
+    price_sqrt_to_decimal_price(price_sqrt, [6, 8]).unwrap() * Uint256::from(1000u128),
+    Uint256::from(1999u128)
+  );
+
+  assert_eq!(
+    price_sqrt_to_decimal_price(DENOMINATOR * Uint256::from(2u128), [0, 0]).unwrap(),
+    Decimal256::from_ratio(4u128, 1u128)
+  );
+
+  // max decimals converts, more is an error instead of an overflow
+  assert!(price_to_price_sqrt(Decimal256::from_ratio(2u128, 1u128), [MAX_DECIMALS, MAX_DECIMALS]).is_ok());
+  assert!(price_sqrt_to_decimal_price(DENOMINATOR, [MAX_DECIMALS, 0]).is_ok());
+  assert!(price_to_price_sqrt(Decimal256::from_ratio(2u128, 1u128), [0, 255]).is_err());
+  assert!(price_sqrt_to_decimal_price(DENOMINATOR, [255, 0]).is_err());
+}
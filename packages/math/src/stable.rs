@@ -0,0 +1,312 @@
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128, Uint256};
+use crate::u256::uints::{U256, U512};
+use crate::tick::DENOMINATOR;
+
+/// stable pair has always 2 assets
+const N_COINS: u64 = 2;
+const ITERATIONS: usize = 64;
+
+pub const MAX_AMP: u64 = 1_000_000;
+/// amp can be multiplied or divided by at most this in a ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// ramp must take at least a day
+pub const MIN_RAMP_TIME: u64 = 86400;
+
+/// amp at the time, linear between the start and the end of the ramp
+pub fn compute_amp(init_amp: u64, init_amp_time: u64, next_amp: u64, next_amp_time: u64, time: u64) -> u64 {
+  if time >= next_amp_time {
+    return next_amp
+  }
+  if time <= init_amp_time {
+    return init_amp
+  }
+
+  let elapsed = u128::from(time - init_amp_time);
+  let duration = u128::from(next_amp_time - init_amp_time);
+  if next_amp > init_amp {
+    init_amp + (u128::from(next_amp - init_amp) * elapsed / duration) as u64
+  } else {
+    init_amp - (u128::from(init_amp - next_amp) * elapsed / duration) as u64
+  }
+}
+
+/// invariant D of the pools, solves 4A(x + y) + D = 4AD + D^3 / 4xy with newton's method
+pub fn compute_d(amp: u64, pools: [Uint128; 2]) -> StdResult<Uint128> {
+  let sum = U512::from(pools[0].u128()) + U512::from(pools[1].u128());
+  if sum.is_zero() {
+    return Ok(Uint128::zero())
+  }
+  if pools[0].is_zero() || pools[1].is_zero() {
+    return Err(StdError::generic_err("Stable pool must have both assets"))
+  }
+
+  let n = U512::from(N_COINS);
+  let ann = U512::from(amp) * n * n;
+  let ann_sub_one = ann
+    .checked_sub(U512::one())
+    .ok_or_else(|| StdError::generic_err("Amp must be positive"))?;
+  let mut d = sum;
+  for _ in 0..ITERATIONS {
+    let mut d_p = d;
+    for pool in pools.iter() {
+      d_p = d_p * d / (U512::from(pool.u128()) * n);
+    }
+
+    let d_prev = d;
+    d = (ann * sum + d_p * n) * d / (ann_sub_one * d + (n + U512::one()) * d_p);
+
+    if abs_diff(d, d_prev) <= U512::one() {
+      return to_uint128(d)
+    }
+  }
+
+  Err(StdError::generic_err("D does not converge"))
+}
+
+/// the other pool when one pool is x, solves y^2 + (x + D / 4A - D)y = D^3 / 16Ax with newton's method
+pub fn compute_y(amp: u64, x: Uint128, d: Uint128) -> StdResult<Uint128> {
+  if x.is_zero() {
+    return Err(StdError::generic_err("Stable pool must have both assets"))
+  }
+
+  if amp == 0 {
+    return Err(StdError::generic_err("Amp must be positive"))
+  }
+
+  let n = U512::from(N_COINS);
+  let ann = U512::from(amp) * n * n;
+  let x = U512::from(x.u128());
+  let d = U512::from(d.u128());
+
+  let c = d * d / (x * n) * d / (ann * n);
+  let b = x + d / ann;
+
+  let mut y = d;
+  for _ in 0..ITERATIONS {
+    let y_prev = y;
+    let denominator = (y * U512::from(2u8) + b)
+      .checked_sub(d)
+      .filter(|denominator| !denominator.is_zero())
+      .ok_or_else(|| StdError::generic_err("y does not converge"))?;
+    y = (y * y + c) / denominator;
+
+    if abs_diff(y, y_prev) <= U512::one() {
+      return to_uint128(y)
+    }
+  }
+
+  Err(StdError::generic_err("y does not converge"))
+}
+
+/// swap in the stable pool
+/// return (return amount, commission amount), return amount includes commission
+pub fn compute_stable_swap(
+  amp: u64,
+  offer_pool: Uint128,
+  ask_pool: Uint128,
+  offer_amount: Uint128,
+  fee_rate: Decimal,
+) -> StdResult<(Uint128, Uint128)> {
+  let d = compute_d(amp, [offer_pool, ask_pool])?;
+  let new_ask_pool = compute_y(amp, offer_pool.checked_add(offer_amount)?, d)?;
+
+  // rounding error goes to the pool
+  let return_amount = ask_pool.saturating_sub(new_ask_pool).saturating_sub(Uint128::from(1u8));
+  let commission_amount = return_amount * fee_rate;
+
+  Ok((return_amount, commission_amount))
+}
+
+/// offer amount to get the ask amount after commission
+/// return (offer amount, commission amount)
+pub fn compute_stable_swap_reverse(
+  amp: u64,
+  offer_pool: Uint128,
+  ask_pool: Uint128,
+  ask_amount: Uint128,
+  fee_rate: Decimal,
+) -> StdResult<(Uint128, Uint128)> {
+  let one_minus_fee = Decimal::one() - fee_rate;
+  let decimal_fraction = Uint128::from(1_000_000_000_000_000_000u128);
+  // ask_amount / (1 - fee_rate), rounding up
+  let before_commission_amount = Uint128::from(
+    (U256::from(ask_amount) * U256::from(decimal_fraction) + U256::from(one_minus_fee * decimal_fraction) - U256::from(1u8))
+      / U256::from(one_minus_fee * decimal_fraction)
+  );
+
+  if before_commission_amount >= ask_pool {
+    return Err(StdError::generic_err("Not enough pool"))
+  }
+
+  let d = compute_d(amp, [offer_pool, ask_pool])?;
+  let new_offer_pool = compute_y(amp, ask_pool - before_commission_amount, d)?;
+
+  let offer_amount = new_offer_pool.checked_sub(offer_pool)? + Uint128::from(1u8);
+  let commission_amount = before_commission_amount - ask_amount;
+
+  Ok((offer_amount, commission_amount))
+}
+
+/// liquidity to mint for the provided amounts
+/// imbalanced provide pays commission for the part that is not proportional to the pools
+pub fn compute_stable_liquidity(
+  amp: u64,
+  pools: [Uint128; 2],
+  amounts: [Uint128; 2],
+  total_liquidity: Uint128,
+  fee_rate: Decimal,
+) -> StdResult<Uint128> {
+  let mut new_pools = [pools[0].checked_add(amounts[0])?, pools[1].checked_add(amounts[1])?];
+  let d1 = compute_d(amp, new_pools)?;
+
+  // first provide
+  if total_liquidity.is_zero() {
+    return Ok(d1)
+  }
+
+  let d0 = compute_d(amp, pools)?;
+  for i in 0..2 {
+    let ideal_pool = d1.multiply_ratio(pools[i], d0);
+    let difference = if ideal_pool > new_pools[i] {
+      ideal_pool - new_pools[i]
+    } else {
+      new_pools[i] - ideal_pool
+    };
+    // n / 4(n - 1) of the commission
+    new_pools[i] = new_pools[i].checked_sub(difference * fee_rate * Decimal::from_ratio(N_COINS, 4 * (N_COINS - 1)))?;
+  }
+  let d2 = compute_d(amp, new_pools)?;
+
+  Ok(total_liquidity.multiply_ratio(d2.saturating_sub(d0), d0))
+}
+
+/// square root of asset0 price as asset1 at the pools (Q128.128)
+/// price = (16Ax^2y^2 + D^3y) / (16Ax^2y^2 + D^3x), price 1 for the empty pool
+pub fn compute_stable_price_sqrt(amp: u64, pools: [Uint128; 2]) -> StdResult<Uint256> {
+  if pools[0].is_zero() || pools[1].is_zero() {
+    return Ok(DENOMINATOR)
+  }
+
+  let d = U512::from(compute_d(amp, pools)?.u128());
+  let x = U512::from(pools[0].u128());
+  let y = U512::from(pools[1].u128());
+
+  // price is same when x, y, D are scaled together, keep them under 100 bits not to overflow
+  let shift = d.bits().saturating_sub(100);
+  let (d, x, y) = (d >> shift, x >> shift, y >> shift);
+
+  let a = U512::from(amp) * U512::from(16u8) * x * x * y * y;
+  let d3 = d * d * d;
+  let numerator = a + d3 * y;
+  let denominator = a + d3 * x;
+
+  // scale under 250 bits, then price in Q256.256
+  let shift = numerator.bits().max(denominator.bits()).saturating_sub(250);
+  let (numerator, denominator) = (numerator >> shift, denominator >> shift);
+  let price = (numerator << 256) / denominator;
+  let price_sqrt: U256 = price.integer_sqrt().into();
+
+  Ok(price_sqrt.into())
+}
+
+fn abs_diff(a: U512, b: U512) -> U512 {
+  if a > b {
+    a - b
+  } else {
+    b - a
+  }
+}
+
+fn to_uint128(value: U512) -> StdResult<Uint128> {
+  if value > U512::from(u128::MAX) {
+    return Err(StdError::generic_err("Overflow"))
+  }
+  Ok(Uint128::from(value.low_u128()))
+}
+
+#[test]
+fn compute_amp_test() {
+  // ramp up and down
+  assert_eq!(compute_amp(100, 1000, 200, 2000, 500), 100);
+  assert_eq!(compute_amp(100, 1000, 200, 2000, 1500), 150);
+  assert_eq!(compute_amp(100, 1000, 200, 2000, 3000), 200);
+  assert_eq!(compute_amp(200, 1000, 100, 2000, 1250), 175);
+}
+
+#[test]
+fn compute_d_test() {
+  // balanced pool, D is the sum
+  let d = compute_d(100, [Uint128::from(1000000000u128), Uint128::from(1000000000u128)]).unwrap();
+  assert_eq!(d, Uint128::from(2000000000u128));
+
+  // imbalanced pool, between constant sum and constant product
+  let d = compute_d(100, [Uint128::from(1500000000u128), Uint128::from(500000000u128)]).unwrap();
+  assert!(d < Uint128::from(2000000000u128));
+  assert!(d > Uint128::from(2u128 * 866025403u128));
+
+  // y keeps D
+  let y = compute_y(100, Uint128::from(1500000000u128), d).unwrap();
+  assert!(y >= Uint128::from(499999999u128) && y <= Uint128::from(500000001u128));
+
+  assert_eq!(compute_d(100, [Uint128::zero(), Uint128::zero()]).unwrap(), Uint128::zero());
+  assert!(compute_d(100, [Uint128::zero(), Uint128::from(1u128)]).is_err());
+
+  // zero amp fails instead of panicking
+  assert!(compute_d(0, [Uint128::from(1000000000u128), Uint128::from(1000000000u128)]).is_err());
+  assert!(compute_y(0, Uint128::from(1500000000u128), d).is_err());
+}
+
+#[test]
+fn compute_stable_swap_test() {
+  let pools = [Uint128::from(1000000000000u128), Uint128::from(1000000000000u128)];
+
+  // high amp, almost 1:1
+  let (return_amount, commission_amount)
+    = compute_stable_swap(1000, pools[0], pools[1], Uint128::from(1000000000u128), Decimal::permille(3)).unwrap();
+  assert!(return_amount > Uint128::from(999990000u128) && return_amount < Uint128::from(1000000000u128));
+  assert_eq!(commission_amount, return_amount * Decimal::permille(3));
+
+  // low amp, more slippage
+  let (low_amp_return, _)
+    = compute_stable_swap(1, pools[0], pools[1], Uint128::from(1000000000u128), Decimal::permille(3)).unwrap();
+  assert!(low_amp_return < return_amount);
+
+  // reverse gives the same amount back
+  let ask_amount = return_amount - commission_amount;
+  let (offer_amount, reverse_commission)
+    = compute_stable_swap_reverse(1000, pools[0], pools[1], ask_amount, Decimal::permille(3)).unwrap();
+  assert!(offer_amount >= Uint128::from(1000000000u128) && offer_amount <= Uint128::from(1000000003u128));
+  assert!(reverse_commission >= commission_amount && reverse_commission <= commission_amount + Uint128::from(1u128));
+}
+
+#[test]
+fn compute_stable_liquidity_test() {
+  let pools = [Uint128::from(1000000000u128), Uint128::from(1000000000u128)];
+
+  // first provide
+  let liquidity = compute_stable_liquidity(100, [Uint128::zero(), Uint128::zero()], pools, Uint128::zero(), Decimal::permille(3)).unwrap();
+  assert_eq!(liquidity, Uint128::from(2000000000u128));
+
+  // proportional provide doesn't pay commission
+  let balanced = compute_stable_liquidity(100, pools, [Uint128::from(1000000u128), Uint128::from(1000000u128)], liquidity, Decimal::permille(3)).unwrap();
+  assert_eq!(balanced, Uint128::from(2000000u128));
+
+  // single asset provide gets less
+  let imbalanced = compute_stable_liquidity(100, pools, [Uint128::from(2000000u128), Uint128::zero()], liquidity, Decimal::permille(3)).unwrap();
+  assert!(imbalanced < balanced);
+  assert!(imbalanced > Uint128::from(1990000u128));
+}
+
+#[test]
+fn compute_stable_price_sqrt_test() {
+  // balanced pool is price 1
+  let price_sqrt = compute_stable_price_sqrt(100, [Uint128::from(1000000u128), Uint128::from(1000000u128)]).unwrap();
+  let diff = if price_sqrt > DENOMINATOR { price_sqrt - DENOMINATOR } else { DENOMINATOR - price_sqrt };
+  assert!(diff <= Uint256::from(1u8));
+
+  // more asset0 is cheaper asset0, but close to 1 with high amp
+  let low_amp = compute_stable_price_sqrt(1, [Uint128::from(1500000u128), Uint128::from(500000u128)]).unwrap();
+  let high_amp = compute_stable_price_sqrt(1000, [Uint128::from(1500000u128), Uint128::from(500000u128)]).unwrap();
+  assert!(low_amp < high_amp);
+  assert!(high_amp < DENOMINATOR);
+}
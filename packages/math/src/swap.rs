@@ -105,6 +105,77 @@ pub fn compute_swap_tick(
   }
 }
 
+/// compute swap in one tick until the price reaches the target price (or the tick's boundary)
+/// liquidity must not be 0
+/// return (offer amount, return amount, commission amount, sqrt of next price, next tick)
+pub fn compute_swap_tick_to_price(
+  tick_index: i32,
+  tick_space: u16,
+  current_price_sqrt: Uint256,
+  target_price_sqrt: Uint256,
+  liquidity: Uint128,
+  offer_token: &TokenNumber,
+  fee_rate: Decimal
+) -> (Uint128, Uint128, Uint128, Uint256, i32) {
+  let tick_space_i32 = i32::from(tick_space);
+  let price_low_sqrt = get_tick_price_sqrt(tick_index * tick_space_i32);
+  let price_high_sqrt = get_tick_price_sqrt((tick_index + 1) * tick_space_i32);
+
+  let (token0_amount, token1_amount) = get_token_amount_from_liquidity(tick_index, tick_index, tick_space, current_price_sqrt, liquidity);
+
+  let offer_amount = match offer_token {
+    // sell
+    TokenNumber::Token0 => {
+      // target is out of the tick, swap to the floor price
+      if target_price_sqrt <= price_low_sqrt {
+        Uint128::MAX
+      } else {
+        let (token0_amount_target, _) = get_token_amount_from_liquidity(tick_index, tick_index, tick_space, target_price_sqrt, liquidity);
+        token0_amount_target.saturating_sub(token0_amount)
+      }
+    },
+    // buy
+    TokenNumber::Token1 => {
+      // target is out of the tick, swap to the ceiling price
+      if target_price_sqrt >= price_high_sqrt {
+        Uint128::MAX
+      } else {
+        let (_, token1_amount_target) = get_token_amount_from_liquidity(tick_index, tick_index, tick_space, target_price_sqrt, liquidity);
+        token1_amount_target.saturating_sub(token1_amount)
+      }
+    },
+  };
+
+  compute_swap_tick(tick_index, tick_space, current_price_sqrt, liquidity, offer_token, offer_amount, fee_rate)
+}
+
+#[test]
+fn swap_to_price_test() {
+  let liquidity = Uint128::from(10000000000u128);
+  let price_sqrt: Uint256 = (U256::from(DENOMINATOR) * U256::from(11u8) / U256::from(10u8)).into();
+
+  // buy, price 1 -> 1.21, offer = L * (1.1 - 1)
+  let (offer_amount, return_amount, commission_amount, next_price_sqrt, next_tick_index) = compute_swap_tick_to_price(
+    0, 20000u16, DENOMINATOR, price_sqrt, liquidity, &TokenNumber::Token1, Decimal::percent(1)
+  );
+  assert_eq!(offer_amount, Uint128::from(1000000000u128));
+  assert_eq!(next_tick_index, 0);
+  assert!(next_price_sqrt <= price_sqrt && next_price_sqrt + Uint256::from(1u8) >= price_sqrt);
+  // L * (1 - 1 / 1.1)
+  assert!(return_amount >= Uint128::from(909090908u128) && return_amount <= Uint128::from(909090909u128));
+  assert_eq!(commission_amount, return_amount * Decimal::percent(1));
+
+  // sell to the floor price of the tick
+  let (offer_amount, _, _, next_price_sqrt, next_tick_index) = compute_swap_tick_to_price(
+    0, 20000u16, price_sqrt, Uint256::zero(), liquidity, &TokenNumber::Token0, Decimal::zero()
+  );
+  let (token0_amount, _) = get_token_amount_from_liquidity(0, 0, 20000u16, price_sqrt, liquidity);
+  let (token0_amount_max, _) = get_token_amount_from_liquidity(0, 0, 20000u16, DENOMINATOR, liquidity);
+  assert_eq!(offer_amount, token0_amount_max - token0_amount);
+  assert_eq!(next_price_sqrt, DENOMINATOR);
+  assert_eq!(next_tick_index, -1);
+}
+
 // compute return amount, have to rounding down,
 fn compute_swap(
   offer_token: &TokenNumber,
@@ -176,6 +247,42 @@ fn swap_test() {
 }
 
 
+/// return amount per unit of offer amount at the price, after commission (Q128.128)
+/// used to compare pools of the same assets. higher is better for the swapper
+pub fn marginal_return(price_sqrt: Uint256, offer_token: &TokenNumber, fee_rate: Decimal) -> Uint256 {
+  let decimal_denominator = Uint128::from(1_000_000_000_000_000_000u128);
+  let one_minus_fee: U256 = ((Decimal::one() - fee_rate) * decimal_denominator).into();
+  let denominator = U256::from(DENOMINATOR);
+  let price_sqrt = U256::from(price_sqrt);
+
+  // sqrt of offer token price as return token
+  let price_sqrt = match offer_token {
+    TokenNumber::Token0 => price_sqrt,
+    TokenNumber::Token1 => mul_div(denominator, denominator, price_sqrt, false),
+  };
+  let price = mul_div(price_sqrt, price_sqrt, denominator, false);
+
+  mul_div(price, one_minus_fee, decimal_denominator.into(), false).into()
+}
+
+#[test]
+fn marginal_return_test() {
+  // price 1
+  assert_eq!(marginal_return(DENOMINATOR, &TokenNumber::Token0, Decimal::zero()), DENOMINATOR);
+  assert_eq!(marginal_return(DENOMINATOR, &TokenNumber::Token1, Decimal::zero()), DENOMINATOR);
+
+  // price 4, 1% commission
+  let price_sqrt: Uint256 = (U256::from(DENOMINATOR) * U256::from(2u8)).into();
+  assert_eq!(
+    marginal_return(price_sqrt, &TokenNumber::Token0, Decimal::percent(1)),
+    (U256::from(DENOMINATOR) * U256::from(396u32) / U256::from(100u32)).into()
+  );
+  assert_eq!(
+    marginal_return(price_sqrt, &TokenNumber::Token1, Decimal::percent(1)),
+    (U256::from(DENOMINATOR) * U256::from(99u32) / U256::from(400u32)).into()
+  );
+}
+
 /// compute swap in one tick
 /// return (offer amount, return amount, commission amount, sqrt of next price, next tick)
 pub fn compute_swap_tick_reverse(
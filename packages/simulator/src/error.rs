@@ -0,0 +1,42 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+// same messages as the pair contract
+#[derive(Error, Debug, PartialEq)]
+pub enum SimulatorError {
+  #[error("{0}")]
+  Std(#[from] StdError),
+
+  #[error("{0}")]
+  OverflowError(#[from] OverflowError),
+
+  #[error("Invalid tick space")]
+  InvalidTickSpace {},
+
+  #[error("Invalid fee rate")]
+  InvalidFeeRate {},
+
+  #[error("Invalid price")]
+  InvalidPrice {},
+
+  #[error("Upper tick must be greater than or equal to lower tick")]
+  InvalidTickRange {},
+
+  #[error("Zero liquidity error")]
+  ZeroLiquidity {},
+
+  #[error("Can't swap")]
+  CanNotSwap {},
+
+  #[error("Max ticks reached, max_ticks: {max_ticks}, filled offer: {offer_amount}, return: {return_amount}")]
+  MaxTicksReached { max_ticks: u32, offer_amount: Uint128, return_amount: Uint128 },
+
+  #[error("Tick range must be smaller or equal to 500")]
+  TickRangeLimit {},
+
+  #[error("You must put position_id or tick_indexes")]
+  ProvideOptionError {},
+
+  #[error("Position not found")]
+  PositionNotFound {},
+}
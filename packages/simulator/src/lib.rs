@@ -0,0 +1,10 @@
+pub mod error;
+pub mod pool;
+pub mod simulate;
+
+pub use crate::error::SimulatorError;
+pub use crate::pool::{Pool, Position, SwapResult};
+pub use crate::simulate::{simulate, Action, PositionReport, Report};
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint256};
+use wineswap::asset::TokenNumber;
+use wineswap::pair::{TickIndexes, TickInfo};
+use wineswap_math::{
+  tick::{get_tick_from_price_sqrt, get_tick_price_sqrt, tick_to_tick_index, tick_index_to_tick, DENOMINATOR, MAX_TICK, MIN_TICK},
+  liquidity::{compute_liquidity, get_token_amount_from_liquidity},
+  swap::compute_swap_tick,
+  fee::{compute_fee_growth, compute_reward, fee_growth_add, fee_growth_sub},
+  price::price_sqrt_to_price,
+};
+
+use crate::error::SimulatorError;
+
+// same as the pair contract
+const TICK_RANGE_LIMIT: i32 = 500;
+pub const DEFAULT_MAX_SWAP_TICKS: u32 = 100;
+static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+
+/// in memory pair. every state change follows the pair and the lp token contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Pool {
+  pub tick_space: u16,
+  pub fee_rate: Decimal,
+  pub current_tick_index: i32,
+  // Q128.128
+  pub current_price_sqrt: Uint256,
+  pub ticks: BTreeMap<i32, TickInfo>,
+  pub positions: BTreeMap<u64, Position>,
+  pub position_count: u64,
+  /// max ticks that a swap walks, `max_swap_ticks` of the pair
+  #[serde(default = "default_max_swap_ticks")]
+  pub max_swap_ticks: u32,
+}
+
+fn default_max_swap_ticks() -> u32 {
+  DEFAULT_MAX_SWAP_TICKS
+}
+
+/// liquidity position, the lp token of the contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Position {
+  pub upper_tick_index: i32,
+  pub lower_tick_index: i32,
+  pub liquidity: Uint128,
+  /// [token0, token1] fee growth of each tick at the last claim, from lower tick
+  pub last_fee_growths: Vec<[Uint256; 2]>,
+  /// total provided amount
+  pub deposited: [Uint128; 2],
+  /// total withdrawn amount, not include fees
+  pub withdrawn: [Uint128; 2],
+  /// total claimed fees
+  pub claimed_fees: [Uint128; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SwapResult {
+  /// return amount after commission
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+}
+
+impl Pool {
+  pub fn new(initial_price: Decimal, tick_space: u16, fee_rate: Decimal) -> Result<Self, SimulatorError> {
+    if tick_space == 0u16 {
+      return Err(SimulatorError::InvalidTickSpace {});
+    }
+
+    if fee_rate >= Decimal::one() {
+      return Err(SimulatorError::InvalidFeeRate {});
+    }
+
+    // to Q128.128
+    let price_sqrt: Uint256 = Decimal256::from_ratio(
+      initial_price.sqrt() * DECIMAL_FRACTION,
+      DECIMAL_FRACTION
+    ) * DENOMINATOR;
+
+    let max_price_sqrt = get_tick_price_sqrt(MAX_TICK + 1i32);
+    let min_price_sqrt = get_tick_price_sqrt(MIN_TICK);
+
+    if price_sqrt <= min_price_sqrt || price_sqrt >= max_price_sqrt {
+      return Err(SimulatorError::InvalidPrice {});
+    }
+
+    let tick = get_tick_from_price_sqrt(price_sqrt);
+
+    Ok(Pool {
+      tick_space,
+      fee_rate,
+      current_tick_index: tick_to_tick_index(tick, tick_space),
+      current_price_sqrt: price_sqrt,
+      ticks: BTreeMap::new(),
+      positions: BTreeMap::new(),
+      position_count: 0,
+      max_swap_ticks: DEFAULT_MAX_SWAP_TICKS,
+    })
+  }
+
+  pub fn price(&self) -> Decimal256 {
+    price_sqrt_to_price(self.current_price_sqrt)
+  }
+
+  pub fn position(&self, position_id: u64) -> Result<&Position, SimulatorError> {
+    self.positions.get(&position_id).ok_or(SimulatorError::PositionNotFound {})
+  }
+
+  /// provide to new position with tick_indexes or to exist position with position_id.
+  /// returns position id, added liquidity and provided amounts. the rest of the amounts is refunded
+  pub fn provide(
+    &mut self,
+    position_id: Option<u64>,
+    tick_indexes: Option<TickIndexes>,
+    amounts: [Uint128; 2],
+  ) -> Result<(u64, Uint128, [Uint128; 2]), SimulatorError> {
+    let (lower_tick_index, upper_tick_index) = if let Some(position_id) = position_id {
+      let position = self.position(position_id)?;
+      (position.lower_tick_index, position.upper_tick_index)
+    } else if let Some(tick_indexes) = tick_indexes {
+      let (lower_tick_index, upper_tick_index) = (tick_indexes.lower_tick_index, tick_indexes.upper_tick_index);
+      if lower_tick_index > upper_tick_index {
+        return Err(SimulatorError::InvalidTickRange {})
+      }
+
+      if tick_index_to_tick(upper_tick_index, self.tick_space) > MAX_TICK
+      || tick_index_to_tick(lower_tick_index, self.tick_space) < MIN_TICK {
+        return Err(SimulatorError::InvalidTickRange {})
+      }
+
+      if upper_tick_index - lower_tick_index > TICK_RANGE_LIMIT {
+        return Err(SimulatorError::TickRangeLimit {})
+      }
+      (lower_tick_index, upper_tick_index)
+    } else {
+      return Err(SimulatorError::ProvideOptionError {})
+    };
+
+    let liquidity = compute_liquidity(
+      amounts[0],
+      amounts[1],
+      self.current_price_sqrt,
+      upper_tick_index,
+      lower_tick_index,
+      self.tick_space,
+    );
+
+    if liquidity.is_zero() {
+      return Err(SimulatorError::ZeroLiquidity {})
+    }
+
+    let (amount0, amount1) = get_token_amount_from_liquidity(
+      upper_tick_index,
+      lower_tick_index,
+      self.tick_space,
+      self.current_price_sqrt,
+      liquidity
+    );
+
+    for i in lower_tick_index..(upper_tick_index + 1) {
+      let tick = self.ticks.entry(i).or_default();
+      tick.total_liquidity = tick.total_liquidity.checked_add(liquidity)?;
+    }
+
+    let position_id = match position_id {
+      Some(position_id) => {
+        // claim first like the contract, then add liquidity
+        self.claim(position_id)?;
+        let position = self.positions.get_mut(&position_id).ok_or(SimulatorError::PositionNotFound {})?;
+        position.liquidity += liquidity;
+        position_id
+      }
+      None => {
+        let position_id = self.position_count;
+        self.position_count += 1;
+        let position = Position {
+          upper_tick_index,
+          lower_tick_index,
+          liquidity,
+          last_fee_growths: self.fee_growths(lower_tick_index, upper_tick_index),
+          deposited: [Uint128::zero(), Uint128::zero()],
+          withdrawn: [Uint128::zero(), Uint128::zero()],
+          claimed_fees: [Uint128::zero(), Uint128::zero()],
+        };
+        self.positions.insert(position_id, position);
+        position_id
+      }
+    };
+
+    let position = self.positions.get_mut(&position_id).ok_or(SimulatorError::PositionNotFound {})?;
+    position.deposited = [position.deposited[0] + amount0, position.deposited[1] + amount1];
+
+    Ok((position_id, liquidity, [amount0, amount1]))
+  }
+
+  /// withdraw all of the liquidity if `liquidity` is None. fees are claimed together.
+  /// returns withdrawn amounts, not include fees
+  pub fn withdraw(&mut self, position_id: u64, liquidity: Option<Uint128>) -> Result<[Uint128; 2], SimulatorError> {
+    let position = self.position(position_id)?;
+    let (lower_tick_index, upper_tick_index) = (position.lower_tick_index, position.upper_tick_index);
+    let withdraw_amount = liquidity.unwrap_or(position.liquidity);
+    let remain = position.liquidity.checked_sub(withdraw_amount)?;
+
+    let (amount0, amount1) = get_token_amount_from_liquidity(
+      upper_tick_index,
+      lower_tick_index,
+      self.tick_space,
+      self.current_price_sqrt,
+      withdraw_amount
+    );
+
+    for i in lower_tick_index..(upper_tick_index + 1) {
+      let tick = self.ticks.entry(i).or_default();
+      tick.total_liquidity = tick.total_liquidity.checked_sub(withdraw_amount)?;
+    }
+
+    self.claim(position_id)?;
+
+    let position = self.positions.get_mut(&position_id).ok_or(SimulatorError::PositionNotFound {})?;
+    position.liquidity = remain;
+    position.withdrawn = [position.withdrawn[0] + amount0, position.withdrawn[1] + amount1];
+
+    Ok([amount0, amount1])
+  }
+
+  /// unclaimed fees of the position
+  pub fn reward(&self, position_id: u64) -> Result<[Uint128; 2], SimulatorError> {
+    let position = self.position(position_id)?;
+    let new_growths = self.fee_growths(position.lower_tick_index, position.upper_tick_index);
+
+    let mut reward_per_liquidity = [Uint256::zero(), Uint256::zero()];
+    for (new_growth, last_growth) in new_growths.iter().zip(position.last_fee_growths.iter()) {
+      for i in 0..2 {
+        reward_per_liquidity[i] = fee_growth_add(reward_per_liquidity[i], fee_growth_sub(new_growth[i], last_growth[i]));
+      }
+    }
+
+    Ok([
+      compute_reward(reward_per_liquidity[0], position.liquidity)?,
+      compute_reward(reward_per_liquidity[1], position.liquidity)?,
+    ])
+  }
+
+  pub fn claim(&mut self, position_id: u64) -> Result<[Uint128; 2], SimulatorError> {
+    let reward = self.reward(position_id)?;
+    let position = self.position(position_id)?;
+    let new_growths = self.fee_growths(position.lower_tick_index, position.upper_tick_index);
+
+    let position = self.positions.get_mut(&position_id).ok_or(SimulatorError::PositionNotFound {})?;
+    position.last_fee_growths = new_growths;
+    position.claimed_fees = [position.claimed_fees[0] + reward[0], position.claimed_fees[1] + reward[1]];
+
+    Ok(reward)
+  }
+
+  /// swap like the contract. the pool doesn't change when it fails
+  pub fn swap(&mut self, offer_token: TokenNumber, offer_amount: Uint128) -> Result<SwapResult, SimulatorError> {
+    let mut remain = offer_amount;
+    let mut tick_index = self.current_tick_index;
+    let mut price_sqrt = self.current_price_sqrt;
+    let mut total_return_amount = Uint128::zero();
+    let mut total_commission_amount = Uint128::zero();
+    // apply after the swap succeeds
+    let mut fee_growth_updates: Vec<(i32, Uint256)> = vec![];
+
+    while remain > Uint128::zero() {
+      if fee_growth_updates.len() as u32 >= self.max_swap_ticks {
+        return Err(SimulatorError::MaxTicksReached {
+          max_ticks: self.max_swap_ticks,
+          offer_amount: offer_amount - remain,
+          return_amount: total_return_amount - total_commission_amount,
+        })
+      }
+
+      let liquidity = match self.ticks.get(&tick_index) {
+        Some(tick) if !tick.total_liquidity.is_zero() => tick.total_liquidity,
+        _ => return Err(SimulatorError::CanNotSwap {})
+      };
+
+      let (offer_amount, return_amount, commission_amount, next_price_sqrt, next_tick_index)
+        = compute_swap_tick(tick_index, self.tick_space, price_sqrt, liquidity, &offer_token, remain, self.fee_rate);
+
+      fee_growth_updates.push((tick_index, compute_fee_growth(commission_amount, liquidity)));
+
+      remain = remain.checked_sub(offer_amount)?;
+      total_return_amount += return_amount;
+      total_commission_amount += commission_amount;
+      tick_index = next_tick_index;
+      price_sqrt = next_price_sqrt;
+    }
+
+    let return_amount = total_return_amount.checked_sub(total_commission_amount)?;
+
+    // commission is paid in return asset
+    for (tick_index, fee_growth) in fee_growth_updates {
+      let tick = self.ticks.entry(tick_index).or_default();
+      match offer_token {
+        TokenNumber::Token0 => tick.last_fee_growth_1 = fee_growth_add(tick.last_fee_growth_1, fee_growth),
+        TokenNumber::Token1 => tick.last_fee_growth_0 = fee_growth_add(tick.last_fee_growth_0, fee_growth),
+      }
+    }
+
+    self.current_tick_index = tick_index;
+    self.current_price_sqrt = price_sqrt;
+
+    Ok(SwapResult {
+      return_amount,
+      commission_amount: total_commission_amount,
+    })
+  }
+
+  /// token amounts of the position's liquidity at the current price
+  pub fn position_amounts(&self, position_id: u64) -> Result<[Uint128; 2], SimulatorError> {
+    let position = self.position(position_id)?;
+    let (amount0, amount1) = get_token_amount_from_liquidity(
+      position.upper_tick_index,
+      position.lower_tick_index,
+      self.tick_space,
+      self.current_price_sqrt,
+      position.liquidity
+    );
+
+    Ok([amount0, amount1])
+  }
+
+  fn fee_growths(&self, lower_tick_index: i32, upper_tick_index: i32) -> Vec<[Uint256; 2]> {
+    (lower_tick_index..(upper_tick_index + 1))
+      .map(|i| match self.ticks.get(&i) {
+        Some(tick) => [tick.last_fee_growth_0, tick.last_fee_growth_1],
+        None => [Uint256::zero(), Uint256::zero()],
+      })
+      .collect()
+  }
+}
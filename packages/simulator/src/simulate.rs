@@ -0,0 +1,143 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal256, Uint128, Uint256};
+use wineswap::asset::TokenNumber;
+use wineswap::pair::TickIndexes;
+use wineswap_math::tick::DENOMINATOR;
+use wineswap_math::u256::mul_div;
+use wineswap_math::u256::uints::U256;
+
+use crate::error::SimulatorError;
+use crate::pool::Pool;
+
+/// an entry of the trade tape
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+  Swap {
+    offer_token: TokenNumber,
+    amount: Uint128,
+  },
+  Provide {
+    // when provide to exist position put position_id
+    position_id: Option<u64>,
+    // when make new position put tick_indexes
+    tick_indexes: Option<TickIndexes>,
+    amounts: [Uint128; 2],
+  },
+  Withdraw {
+    position_id: u64,
+    liquidity: Option<Uint128>,
+  },
+  Claim {
+    position_id: u64,
+  },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Report {
+  pub price: Decimal256,
+  pub tick_index: i32,
+  /// index of the actions that failed. the contract reverts them, so they don't change the pool
+  pub failed_actions: Vec<usize>,
+  pub positions: Vec<PositionReport>,
+}
+
+/// values are token1 amount at the final price
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PositionReport {
+  pub position_id: u64,
+  pub upper_tick_index: i32,
+  pub lower_tick_index: i32,
+  pub liquidity: Uint128,
+  pub deposited: [Uint128; 2],
+  pub withdrawn: [Uint128; 2],
+  /// amounts of the remaining liquidity at the final price
+  pub amounts: [Uint128; 2],
+  /// claimed + unclaimed fees
+  pub fees: [Uint128; 2],
+  /// value of the deposited amounts when they were held
+  pub hold_value: Uint256,
+  /// value of the remaining and the withdrawn amounts, not include fees
+  pub lp_value: Uint256,
+  pub fee_value: Uint256,
+  /// (hold_value - lp_value) / hold_value, zero if lp_value is bigger
+  pub impermanent_loss: Decimal256,
+}
+
+/// run the actions in order and report every position
+pub fn simulate<I>(pool: &mut Pool, actions: I) -> Result<Report, SimulatorError>
+where
+  I: IntoIterator<Item = Action>,
+{
+  let mut failed_actions: Vec<usize> = vec![];
+
+  for (i, action) in actions.into_iter().enumerate() {
+    let res = match action {
+      Action::Swap { offer_token, amount } => pool.swap(offer_token, amount).map(|_| ()),
+      Action::Provide { position_id, tick_indexes, amounts } => pool.provide(position_id, tick_indexes, amounts).map(|_| ()),
+      Action::Withdraw { position_id, liquidity } => pool.withdraw(position_id, liquidity).map(|_| ()),
+      Action::Claim { position_id } => pool.claim(position_id).map(|_| ()),
+    };
+
+    if res.is_err() {
+      failed_actions.push(i);
+    }
+  }
+
+  let mut positions: Vec<PositionReport> = vec![];
+  for (position_id, position) in pool.positions.iter() {
+    let amounts = pool.position_amounts(*position_id)?;
+    let reward = pool.reward(*position_id)?;
+    let fees = [position.claimed_fees[0] + reward[0], position.claimed_fees[1] + reward[1]];
+
+    let hold_value = value(position.deposited, pool.current_price_sqrt);
+    let lp_value = value(
+      [amounts[0] + position.withdrawn[0], amounts[1] + position.withdrawn[1]],
+      pool.current_price_sqrt
+    );
+
+    let impermanent_loss = if hold_value > lp_value {
+      Decimal256::from_ratio(hold_value - lp_value, hold_value)
+    } else {
+      Decimal256::zero()
+    };
+
+    positions.push(PositionReport {
+      position_id: *position_id,
+      upper_tick_index: position.upper_tick_index,
+      lower_tick_index: position.lower_tick_index,
+      liquidity: position.liquidity,
+      deposited: position.deposited,
+      withdrawn: position.withdrawn,
+      amounts,
+      fees,
+      hold_value,
+      lp_value,
+      fee_value: value(fees, pool.current_price_sqrt),
+      impermanent_loss,
+    });
+  }
+
+  Ok(Report {
+    price: pool.price(),
+    tick_index: pool.current_tick_index,
+    failed_actions,
+    positions,
+  })
+}
+
+// token1 amount of the assets. amount0 * price + amount1, rounding down
+fn value(amounts: [Uint128; 2], price_sqrt: Uint256) -> Uint256 {
+  let price_sqrt = U256::from(price_sqrt);
+  let denominator = U256::from(DENOMINATOR);
+  let amount0_value = mul_div(
+    mul_div(U256::from(amounts[0]), price_sqrt, denominator, false),
+    price_sqrt,
+    denominator,
+    false
+  );
+
+  Uint256::from(amount0_value) + Uint256::from(amounts[1])
+}
@@ -0,0 +1,165 @@
+use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint256};
+use wineswap::asset::TokenNumber;
+use wineswap::pair::TickIndexes;
+use wineswap_math::{
+  liquidity::{compute_liquidity, get_token_amount_from_liquidity},
+  swap::compute_swap_tick,
+  tick::DENOMINATOR,
+};
+
+use crate::{simulate, Action, Pool, SimulatorError};
+
+fn tick_indexes(lower_tick_index: i32, upper_tick_index: i32) -> Option<TickIndexes> {
+  Some(TickIndexes { upper_tick_index, lower_tick_index })
+}
+
+#[test]
+fn provide_swap_test() {
+  let mut pool = Pool::new(Decimal::one(), 100, Decimal::from_ratio(1u128, 100u128)).unwrap();
+
+  // invalid range
+  let res = pool.provide(None, tick_indexes(10, -10), [Uint128::from(1000000u128), Uint128::from(1000000u128)]);
+  assert_eq!(res, Err(SimulatorError::InvalidTickRange {}));
+
+  let (position_id, liquidity, provided) = pool.provide(
+    None,
+    tick_indexes(-10, 10),
+    [Uint128::from(1000000u128), Uint128::from(1000000u128)]
+  ).unwrap();
+
+  assert_eq!(position_id, 0);
+  assert_eq!(liquidity, compute_liquidity(Uint128::from(1000000u128), Uint128::from(1000000u128), DENOMINATOR, 10, -10, 100));
+  let (amount0, amount1) = get_token_amount_from_liquidity(10, -10, 100, DENOMINATOR, liquidity);
+  assert_eq!(provided, [amount0, amount1]);
+  assert_eq!(pool.ticks.len(), 21);
+
+  // swap in a single tick
+  let res = pool.swap(TokenNumber::Token1, Uint128::from(50000u128)).unwrap();
+
+  let (_, return_amount, commission_amount, next_price_sqrt, next_tick_index) = compute_swap_tick(
+    0, 100, DENOMINATOR, liquidity, &TokenNumber::Token1, Uint128::from(50000u128), Decimal::from_ratio(1u128, 100u128)
+  );
+  assert_eq!(res.return_amount, return_amount - commission_amount);
+  assert_eq!(res.commission_amount, commission_amount);
+  assert_eq!(pool.current_price_sqrt, next_price_sqrt);
+  assert_eq!(pool.current_tick_index, next_tick_index);
+
+  // the only position gets all of the commission (rounding down)
+  let reward = pool.reward(position_id).unwrap();
+  assert_eq!(reward[1], Uint128::zero());
+  assert!(commission_amount - reward[0] <= Uint128::from(1u128));
+
+  // claimed reward is not claimable again
+  assert_eq!(pool.claim(position_id).unwrap(), reward);
+  assert_eq!(pool.reward(position_id).unwrap(), [Uint128::zero(), Uint128::zero()]);
+
+  // swap that needs more liquidity than the pool has fails without change
+  let before = pool.clone();
+  let res = pool.swap(TokenNumber::Token0, Uint128::from(100000000u128));
+  assert_eq!(res, Err(SimulatorError::CanNotSwap {}));
+  assert_eq!(pool, before);
+
+  // swap over max swap ticks fails like the pair
+  pool.max_swap_ticks = 1;
+  let res = pool.swap(TokenNumber::Token0, Uint128::from(200000u128));
+  match res {
+    Err(SimulatorError::MaxTicksReached { max_ticks, .. }) => assert_eq!(max_ticks, 1),
+    _ => panic!("Must return max ticks reached error"),
+  }
+  assert_eq!(pool, Pool { max_swap_ticks: 1, ..before });
+
+  pool.max_swap_ticks = 100;
+  pool.swap(TokenNumber::Token0, Uint128::from(200000u128)).unwrap();
+}
+
+#[test]
+fn fee_share_test() {
+  let mut pool = Pool::new(Decimal::one(), 100, Decimal::from_ratio(3u128, 1000u128)).unwrap();
+
+  // same range, position 1 has twice liquidity
+  let (_, liquidity0, _) = pool.provide(
+    None,
+    tick_indexes(-10, 10),
+    [Uint128::from(1000000u128), Uint128::from(1000000u128)]
+  ).unwrap();
+  let (_, liquidity1, _) = pool.provide(
+    None,
+    tick_indexes(-10, 10),
+    [Uint128::from(2000000u128), Uint128::from(2000000u128)]
+  ).unwrap();
+  assert!(liquidity1 >= liquidity0 + liquidity0 - Uint128::from(1u128));
+
+  let res = pool.swap(TokenNumber::Token0, Uint128::from(300000u128)).unwrap();
+
+  let reward0 = pool.reward(0).unwrap();
+  let reward1 = pool.reward(1).unwrap();
+  assert_eq!(reward0[0], Uint128::zero());
+  assert!(reward1[1] >= reward0[1] + reward0[1] - Uint128::from(1u128));
+  assert!(res.commission_amount - reward0[1] - reward1[1] <= Uint128::from(2u128));
+
+  // additional provide claims first
+  let (position_id, _, _) = pool.provide(
+    Some(0),
+    None,
+    [Uint128::from(1000000u128), Uint128::from(1000000u128)]
+  ).unwrap();
+  assert_eq!(position_id, 0);
+  assert_eq!(pool.position(0).unwrap().claimed_fees, reward0);
+  assert_eq!(pool.reward(0).unwrap(), [Uint128::zero(), Uint128::zero()]);
+
+  // withdraw more than the position has
+  let res = pool.withdraw(1, Some(liquidity1 + Uint128::from(1u128)));
+  match res {
+    Err(SimulatorError::OverflowError(_)) => assert!(true),
+    _ => panic!("Must return overflow error"),
+  }
+}
+
+#[test]
+fn simulate_test() {
+  let mut pool = Pool::new(Decimal::one(), 100, Decimal::from_ratio(3u128, 1000u128)).unwrap();
+
+  let actions = vec![
+    Action::Provide {
+      position_id: None,
+      tick_indexes: tick_indexes(-10, 10),
+      amounts: [Uint128::from(1000000u128), Uint128::from(1000000u128)],
+    },
+    Action::Provide {
+      position_id: None,
+      tick_indexes: tick_indexes(-3, 3),
+      amounts: [Uint128::from(1000000u128), Uint128::from(1000000u128)],
+    },
+    Action::Swap { offer_token: TokenNumber::Token1, amount: Uint128::from(200000u128) },
+    // position not found
+    Action::Claim { position_id: 5 },
+    Action::Swap { offer_token: TokenNumber::Token0, amount: Uint128::from(100000u128) },
+    Action::Withdraw { position_id: 1, liquidity: None },
+  ];
+
+  let report = simulate(&mut pool, actions).unwrap();
+
+  assert_eq!(report.failed_actions, vec![3]);
+  assert_eq!(report.price, pool.price());
+  assert_eq!(report.tick_index, pool.current_tick_index);
+  assert_eq!(report.positions.len(), 2);
+
+  // fully withdrawn position
+  let position = &report.positions[1];
+  assert_eq!(position.liquidity, Uint128::zero());
+  assert_eq!(position.amounts, [Uint128::zero(), Uint128::zero()]);
+  assert_ne!(position.withdrawn, [Uint128::zero(), Uint128::zero()]);
+  assert_eq!(position.fees, pool.position(1).unwrap().claimed_fees);
+
+  // price moved up, lp sold token0 for token1 and lost some value
+  assert!(report.price > Decimal256::one());
+  for position in report.positions.iter() {
+    assert!(position.hold_value > position.lp_value);
+    assert!(position.impermanent_loss > Decimal256::zero());
+    assert!(position.fee_value > Uint256::zero());
+    assert_eq!(
+      position.impermanent_loss,
+      Decimal256::from_ratio(position.hold_value - position.lp_value, position.hold_value)
+    );
+  }
+}
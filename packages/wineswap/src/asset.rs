@@ -1,12 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper, StdError, StdResult,
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, MessageInfo, QuerierWrapper, StdError, StdResult,
   Uint128, WasmMsg};
-use cw20::{Cw20ExecuteMsg};
-use terra_cosmwasm::TerraQuerier;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
-static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+use crate::tax::{ChainTax, TaxPolicy};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -57,6 +56,19 @@ impl AssetInfo {
       }
     }
   }
+
+  pub fn query_balance(&self, querier: &QuerierWrapper, address: &Addr) -> StdResult<Uint128> {
+    match self {
+      AssetInfo::NativeToken { denom } => Ok(querier.query_balance(address, denom)?.amount),
+      AssetInfo::Token { contract_addr } => {
+        let res: BalanceResponse = querier.query_wasm_smart(
+          contract_addr,
+          &Cw20QueryMsg::Balance { address: address.to_string() }
+        )?;
+        Ok(res.balance)
+      }
+    }
+  }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -101,6 +113,18 @@ impl Asset{
   }
 
   pub fn into_msg(self, querier: &QuerierWrapper, recipient: Addr) -> StdResult<CosmosMsg> {
+    self.into_msg_with::<ChainTax>(querier, recipient)
+  }
+
+  pub fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Coin> {
+    self.deduct_tax_with::<ChainTax>(querier)
+  }
+
+  pub fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+    self.compute_tax_with::<ChainTax>(querier)
+  }
+
+  pub fn into_msg_with<T: TaxPolicy>(self, querier: &QuerierWrapper, recipient: Addr) -> StdResult<CosmosMsg> {
     let amount = self.amount;
 
     match &self.info {
@@ -114,46 +138,34 @@ impl Asset{
       })),
       AssetInfo::NativeToken { .. } => Ok(CosmosMsg::Bank(BankMsg::Send {
         to_address: recipient.to_string(),
-        amount: vec![self.deduct_tax(querier)?],
+        amount: vec![self.deduct_tax_with::<T>(querier)?],
       })),
     }
   }
 
-  pub fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Coin> {
+  pub fn deduct_tax_with<T: TaxPolicy>(&self, querier: &QuerierWrapper) -> StdResult<Coin> {
     let amount = self.amount;
     if let AssetInfo::NativeToken { denom } = &self.info {
       Ok(Coin {
         denom: denom.to_string(),
-        amount: amount.checked_sub(self.compute_tax(querier)?)?,
+        amount: amount.checked_sub(self.compute_tax_with::<T>(querier)?)?,
       })
     } else {
       Err(StdError::generic_err("cannot deduct tax from token asset"))
     }
   }
 
-  pub fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
-    let amount = self.amount;
+  pub fn compute_tax_with<T: TaxPolicy>(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
     if let AssetInfo::NativeToken { denom } = &self.info {
-      if denom == "uluna" {
-        Ok(Uint128::zero())
-      } else {
-        let terra_querier = TerraQuerier::new(querier);
-        let tax_rate: Decimal = (terra_querier.query_tax_rate()?).rate;
-        let tax_cap: Uint128 = (terra_querier.query_tax_cap(denom.to_string())?).cap;
-        Ok(std::cmp::min(
-          amount.checked_sub(amount.multiply_ratio(
-            DECIMAL_FRACTION,
-            DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
-          ))?,
-          tax_cap,
-        ))
-      }
+      T::compute_tax(querier, denom, self.amount)
     } else {
       Ok(Uint128::zero())
     }
   }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum TokenNumber {
   Token0,
   Token1
@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal256, Event, Uint128, Uint256};
+
+// custom events are emitted with `wasm-` prefix. (ex. wasm-tick_swap)
+pub const TICK_SWAP_EVENT: &str = "tick_swap";
+pub const TICK_LIQUIDITY_EVENT: &str = "tick_liquidity";
+
+/// swap in a single tick. emitted for every tick the swap passes through
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TickSwapEvent {
+  pub tick_index: i32,
+  /// total liquidity of the tick
+  pub liquidity: Uint128,
+  pub offer_amount: Uint128,
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+  /// fee growth added to the tick, [token0, token1] (Q128.128)
+  pub fee_growth_delta: [Uint256; 2],
+  /// Q128.128
+  pub price_sqrt_before: Uint256,
+  pub price_sqrt_after: Uint256,
+  pub price_before: Decimal256,
+  pub price_after: Decimal256,
+}
+
+impl From<TickSwapEvent> for Event {
+  fn from(event: TickSwapEvent) -> Self {
+    Event::new(TICK_SWAP_EVENT)
+      .add_attribute("tick_index", event.tick_index.to_string())
+      .add_attribute("liquidity", event.liquidity)
+      .add_attribute("offer_amount", event.offer_amount)
+      .add_attribute("return_amount", event.return_amount)
+      .add_attribute("commission_amount", event.commission_amount)
+      .add_attribute("fee_growth_delta_0", event.fee_growth_delta[0].to_string())
+      .add_attribute("fee_growth_delta_1", event.fee_growth_delta[1].to_string())
+      .add_attribute("price_sqrt_before", event.price_sqrt_before.to_string())
+      .add_attribute("price_sqrt_after", event.price_sqrt_after.to_string())
+      .add_attribute("price_before", event.price_before.to_string())
+      .add_attribute("price_after", event.price_after.to_string())
+  }
+}
+
+/// liquidity change of a single tick. emitted for every tick in the range of provide/withdraw
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TickLiquidityEvent {
+  pub tick_index: i32,
+  /// changed amount of liquidity
+  pub liquidity_delta: Uint128,
+  /// true for provide, false for withdraw
+  pub add: bool,
+  /// total liquidity of the tick after the change
+  pub liquidity: Uint128,
+}
+
+impl From<TickLiquidityEvent> for Event {
+  fn from(event: TickLiquidityEvent) -> Self {
+    Event::new(TICK_LIQUIDITY_EVENT)
+      .add_attribute("tick_index", event.tick_index.to_string())
+      .add_attribute("liquidity_delta", event.liquidity_delta)
+      .add_attribute("add", event.add.to_string())
+      .add_attribute("liquidity", event.liquidity)
+  }
+}
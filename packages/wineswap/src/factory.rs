@@ -1,15 +1,32 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Decimal};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cw0::Expiration;
+use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
+use crate::ownership::OwnershipProposal;
+use crate::pair::{HistoryConfig, Referral};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
   pub owner: String,
   pub pair_code_id: u64,
   pub token_code_id: u64,
+  pub quote_assets: Vec<AssetInfo>,
+  /// code id of the stable pair, used by pair types with amp
+  pub stable_pair_code_id: Option<u64>,
+  /// max number of ticks a swap can walk on new pairs, pair default if not set
+  pub max_swap_ticks: Option<u32>,
+  /// max referral commission of swaps on the pairs. default: 0
+  pub max_referral_commission_bps: Option<u16>,
+  /// delay (seconds) of the timelocked actions. default: 0, executed without queue
+  pub timelock_delay: Option<u64>,
+  /// fee to create a pair, free if not set
+  pub pair_creation_fee: Option<Asset>,
+  /// receiver of the pair creation fee, required with the fee
+  pub fee_collector: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -17,25 +34,198 @@ pub struct Config {
   pub owner: Addr,
   pub pair_code_id: u64,
   pub token_code_id: u64,
+  /// assets used to quote the price of other assets (ex. uusd)
+  #[serde(default)]
+  pub quote_assets: Vec<AssetInfo>,
+  #[serde(default)]
+  pub stable_pair_code_id: Option<u64>,
+  #[serde(default)]
+  pub max_swap_ticks: Option<u32>,
+  #[serde(default)]
+  pub max_referral_commission_bps: u16,
+  /// new owner that can claim the ownership
+  #[serde(default)]
+  pub ownership_proposal: Option<OwnershipProposal>,
+  /// timelocked actions must be queued and wait this (seconds) if not 0
+  #[serde(default)]
+  pub timelock_delay: u64,
+  /// paid by the creator of a pair to fee_collector
+  #[serde(default)]
+  pub pair_creation_fee: Option<Asset>,
+  #[serde(default)]
+  pub fee_collector: Option<Addr>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
   UpdateConfig {
-    owner: Option<String>,
     token_code_id: Option<u64>,
     pair_code_id: Option<u64>,
+    stable_pair_code_id: Option<u64>,
+    max_swap_ticks: Option<u32>,
+    max_referral_commission_bps: Option<u16>,
+    timelock_delay: Option<u64>,
+    /// zero amount removes the fee
+    pair_creation_fee: Option<Asset>,
+    fee_collector: Option<String>,
+  },
+  /// propose the new owner, the owner is changed when the new owner claims it before `expires`
+  ProposeNewOwner {
+    owner: String,
+    expires: Expiration,
   },
+  /// new owner of the proposal takes the ownership
+  ClaimOwnership {},
+  DropOwnershipProposal {},
+  /// update config of the pair (not stable pair)
+  UpdatePairConfig {
+    pair: String,
+    max_swap_ticks: Option<u32>,
+    history_config: Option<HistoryConfig>,
+  },
+  /// native pair creation fee is sent with it, cw20 fee is paid with `Cw20HookMsg::CreatePair`
   CreatePair {
     asset_infos: [AssetInfo; 2],
     pair_type: String,
     initial_price: Decimal,
   },
+  /// remove the pair from the factory, the pair contract is not changed
+  DeregisterPair {
+    asset_infos: [AssetInfo; 2],
+    pair_type: String,
+  },
+  AddPairType {
+    type_name: String,
+    tick_space: u16,
+    fee_rate: Decimal,
+    /// stable pair type with this amplification factor
+    amp: Option<u64>,
+  },
+  AddQuoteAsset {
+    asset_info: AssetInfo,
+  },
+  RemoveQuoteAsset {
+    asset_info: AssetInfo,
+  },
+  /// register decimals of the native token, pairs of the native token can be created after it
+  AddNativeTokenDecimals {
+    denom: String,
+    decimals: u8,
+  },
+  /// discount rate of the swap fee of the address on every pair, zero discount removes it
+  SetFeeDiscount {
+    address: String,
+    discount: Decimal,
+  },
+  /// migrate the pair or the lp token created by the factory, the factory is their admin
+  MigrateContract {
+    contract: String,
+    code_id: u64,
+    msg: Binary,
+  },
+  /// queue the timelocked action, executable after `timelock_delay`
+  QueueAction {
+    action: TimelockAction,
+  },
+  /// execute the queued action after its delay
+  ExecuteAction {
+    id: u64,
+  },
+  CancelAction {
+    id: u64,
+  },
+  Receive(Cw20ReceiveMsg),
+  /// swap native token, split over every pair type of the assets
+  SplitSwap {
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    /// referral of every split swap
+    referral: Option<Referral>,
+  },
+  /// fails if the receiver got less than minimum_receive after prev_balance
+  AssertMinimumReceive {
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    minimum_receive: Uint128,
+    receiver: String,
+  },
+}
+
+/// owner actions that must be queued while `timelock_delay` is set, same as the execute msgs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelockAction {
+  UpdateConfig {
+    token_code_id: Option<u64>,
+    pair_code_id: Option<u64>,
+    stable_pair_code_id: Option<u64>,
+    max_swap_ticks: Option<u32>,
+    max_referral_commission_bps: Option<u16>,
+    timelock_delay: Option<u64>,
+    /// zero amount removes the fee
+    pair_creation_fee: Option<Asset>,
+    fee_collector: Option<String>,
+  },
+  UpdatePairConfig {
+    pair: String,
+    max_swap_ticks: Option<u32>,
+    history_config: Option<HistoryConfig>,
+  },
   AddPairType {
     type_name: String,
     tick_space: u16,
     fee_rate: Decimal,
+    amp: Option<u64>,
+  },
+  SetFeeDiscount {
+    address: String,
+    discount: Decimal,
+  },
+  DeregisterPair {
+    asset_infos: [AssetInfo; 2],
+    pair_type: String,
+  },
+  /// `RampAmp` of the stable pair, sent by the factory
+  RampAmp {
+    pair: String,
+    next_amp: u64,
+    next_amp_time: u64,
+  },
+  MigrateContract {
+    contract: String,
+    code_id: u64,
+    msg: Binary,
+  },
+  /// `ProposeNewMinter` of the pair (or the stable pair), sent by the factory
+  ProposeNewMinter {
+    pair: String,
+    minter: String,
+    expires: Expiration,
+  },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+  /// create pair with the sent cw20 token as the pair creation fee
+  CreatePair {
+    asset_infos: [AssetInfo; 2],
+    pair_type: String,
+    initial_price: Decimal,
+  },
+  /// swap cw20 token, split over every pair type of the assets
+  SplitSwap {
+    ask_asset_info: AssetInfo,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    /// referral of every split swap
+    referral: Option<Referral>,
   },
 }
 
@@ -57,7 +247,35 @@ pub enum QueryMsg {
     PairTypes {
       start_after: Option<String>,
       limit: Option<u32>,
-    }
+    },
+    PairsByAsset {
+      asset_info: AssetInfo,
+      start_after: Option<AssetInfosWithType>,
+      limit: Option<u32>,
+    },
+    QuotePairs {
+      asset_info: AssetInfo,
+    },
+    BestQuote {
+      offer_asset: Asset,
+      ask_asset_info: AssetInfo,
+    },
+    /// zero discount if the address is not registered
+    FeeDiscount {
+      address: String,
+    },
+    FeeDiscounts {
+      start_after: Option<String>,
+      limit: Option<u32>,
+    },
+    NativeTokenDecimals {
+      denom: String,
+    },
+    /// queued timelocked actions in ascending order of the id
+    PendingActions {
+      start_after: Option<u64>,
+      limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -65,6 +283,7 @@ pub struct PairTypeResponse {
   pub type_name: String,
   pub tick_space: u16,
   pub fee_rate: Decimal,
+  pub amp: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -72,6 +291,52 @@ pub struct PairsResponse {
   pub pairs: Vec<PairInfoWithType>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuotePairsResponse {
+  /// only quote assets that have pair with the asset
+  pub quote_pairs: Vec<QuotePairInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuotePairInfo {
+  pub quote_asset: AssetInfo,
+  pub pair_types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BestQuoteResponse {
+  pub pair: PairInfoWithType,
+  /// return amount after commission and tax
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+  /// tax of the offer and the return asset
+  pub tax_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeDiscountResponse {
+  pub address: String,
+  /// discount rate of the fee rate of the pairs
+  pub discount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NativeTokenDecimalsResponse {
+  pub denom: String,
+  pub decimals: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAction {
+  pub id: u64,
+  pub action: TimelockAction,
+  /// block time (seconds) the action can be executed from
+  pub executable_at: u64,
+  /// block time (seconds) the action is queued, a longer delay set after it applies too
+  #[serde(default)]
+  pub queued_at: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AssetInfosWithType {
   pub asset_infos: [AssetInfo; 2],
@@ -84,6 +349,12 @@ pub struct PairInfoWithType {
   pub contract_addr: Addr,
   pub liquidity_token: Addr,
   pub pair_type: String,
+  /// decimals of asset_infos, not stored on pairs created before it
+  #[serde(default)]
+  pub asset_decimals: Option<[u8; 2]>,
+  /// sender of `CreatePair`, not stored on pairs created before it
+  #[serde(default)]
+  pub creator: Option<Addr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -92,6 +363,12 @@ pub struct PairInfo {
   pub contract_addr: Addr,
   pub liquidity_token: Addr,
   pub pair_type: PairType,
+  /// decimals of asset_infos, not stored on pairs created before it
+  #[serde(default)]
+  pub asset_decimals: Option<[u8; 2]>,
+  /// sender of `CreatePair`, not stored on pairs created before it
+  #[serde(default)]
+  pub creator: Option<Addr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -99,6 +376,9 @@ pub struct PairType {
   pub type_name: String,
   pub tick_space: u16,
   pub fee_rate: Decimal,
+  /// stable pair type if set, initial amp of the pairs
+  #[serde(default)]
+  pub amp: Option<u64>,
 }
 
 pub fn pair_key(asset_infos: &[AssetInfo; 2], pair_type: String) -> Vec<u8> {
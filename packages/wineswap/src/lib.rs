@@ -1,5 +1,10 @@
 pub mod lp_token;
 pub mod pair;
+pub mod stable_pair;
 pub mod new_int_key;
 pub mod factory;
-pub mod asset;
\ No newline at end of file
+pub mod migrator;
+pub mod asset;
+pub mod tax;
+pub mod event;
+pub mod ownership;
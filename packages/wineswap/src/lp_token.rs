@@ -5,6 +5,7 @@ use cw0::Expiration;
 use cosmwasm_std::{to_binary, BlockInfo, WasmMsg, CosmosMsg, StdResult, Addr, Binary, Uint128};
 
 use crate::asset::Asset;
+use crate::ownership::OwnershipProposal;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct InstantiateMsg {
@@ -13,6 +14,9 @@ pub struct InstantiateMsg {
   pub minter: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -45,6 +49,26 @@ pub enum ExecuteMsg {
     amount: Uint128,
     add: bool
   },
+
+  /// move `liquidity` of the token to a new token with the same range
+  Split {
+    token_id: String,
+    liquidity: Uint128,
+  },
+
+  /// combine tokens with the same range and owner into the first token
+  Merge { token_ids: Vec<String> },
+
+  /// propose the new minter, the minter is changed when the new minter claims it before `expires`
+  ProposeNewMinter {
+    minter: String,
+    expires: Expiration,
+  },
+
+  /// new minter of the proposal takes the minter
+  ClaimMinter {},
+
+  DropMinterProposal {},
 }
 
 
@@ -75,6 +99,9 @@ pub enum QueryMsg {
 
   Minter {},
 
+  /// number of minted tokens, burned ones included. the next token id
+  NumTokens {},
+
   Reward {
     token_id: String
   },
@@ -93,6 +120,9 @@ pub struct ConfigResponse {
   pub name: String,
   pub symbol: String,
   pub minter: Addr,
+  /// new minter that can claim the minter
+  #[serde(default)]
+  pub minter_proposal: Option<OwnershipProposal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -114,6 +144,11 @@ pub struct MinterResponse {
   pub minter: String
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NumTokensResponse {
+  pub count: u64
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct RewardResponse {
   pub rewards: [Asset; 2]
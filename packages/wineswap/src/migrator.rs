@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw20::Cw20ReceiveMsg;
+
+use crate::asset::AssetInfo;
+use crate::pair::TickIndexes;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+  /// wineswap factory, new pair is found from here
+  pub factory: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Config {
+  pub factory: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+  Receive(Cw20ReceiveMsg),
+  /// internal, provide the withdrawn assets to the pair
+  ProvideLiquidity {},
+  /// internal, send the position and unused assets to the receiver
+  Refund {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+  /// sent cw20 is the lp token of old_pair
+  Migrate {
+    old_pair: String,
+    pair_type: String,
+    tick_indexes: TickIndexes,
+    receiver: Option<String>,
+  },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+  Config {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+/// msgs of the constant product pair (terraswap) to migrate from
+pub mod classic {
+  use super::*;
+
+  #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+  #[serde(rename_all = "snake_case")]
+  pub enum QueryMsg {
+    Pair {},
+  }
+
+  #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+  pub struct PairInfo {
+    pub asset_infos: [AssetInfo; 2],
+    pub contract_addr: String,
+    pub liquidity_token: String,
+  }
+
+  #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+  #[serde(rename_all = "snake_case")]
+  pub enum Cw20HookMsg {
+    WithdrawLiquidity {},
+  }
+}
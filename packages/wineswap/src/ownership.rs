@@ -0,0 +1,12 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw0::Expiration;
+
+/// pending transfer of the ownership, the proposed owner has to claim it before it expires
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OwnershipProposal {
+  pub owner: Addr,
+  pub expires: Expiration,
+}
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128, Uint256};
+use cw0::Expiration;
 use cw20::Cw20ReceiveMsg;
 
 use crate::asset::{Asset, AssetInfo};
@@ -12,13 +13,17 @@ pub struct Config {
   pub tick_space: u16,
   pub fee_rate: Decimal,
   pub liquidity_token: Addr,
+  /// decimals of asset_infos, not stored on pairs instantiated before it or without the factory
+  #[serde(default)]
+  pub asset_decimals: Option<[u8; 2]>,
 }
 
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct TickInfo {
-  pub last_fee_growth_0: Decimal,
-  pub last_fee_growth_1: Decimal,
+  /// accumulate commission per liquidity, Q128.128
+  pub last_fee_growth_0: Uint256,
+  pub last_fee_growth_1: Uint256,
   pub total_liquidity: Uint128,
 }
 
@@ -31,6 +36,92 @@ pub struct InstantiateMsg {
   // 0 < tick_space
   pub tick_space: u16,
   pub fee_rate: Decimal,
+  /// number of history buckets to keep. default: 168 hourly, 365 daily
+  pub history_config: Option<HistoryConfig>,
+  /// max number of ticks a swap can walk. default: 100
+  pub max_swap_ticks: Option<u32>,
+  /// decimals of asset_infos, set by the factory
+  pub asset_decimals: Option<[u8; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MigrateMsg {
+  /// factory of the pairs instantiated before the pair stored it, so the factory can update the config.
+  /// not used by the stable pair
+  pub factory: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryConfig {
+  pub hourly_size: u32,
+  pub daily_size: u32,
+}
+
+impl Default for HistoryConfig {
+  fn default() -> Self {
+    HistoryConfig {
+      hourly_size: 168,
+      daily_size: 365,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryPeriod {
+  Hourly,
+  Daily,
+}
+
+/// which tick index boundary the price snaps to
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+  /// tick index that contains the price
+  Down,
+  /// next tick index unless the price is exactly on the boundary
+  Up,
+  /// tick index of the closer boundary
+  Nearest,
+}
+
+impl HistoryPeriod {
+  pub fn seconds(&self) -> u64 {
+    match self {
+      HistoryPeriod::Hourly => 3600,
+      HistoryPeriod::Daily => 86400,
+    }
+  }
+}
+
+/// swap summary of one period
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryInfo {
+  /// start time of the period (unix seconds)
+  pub start_time: u64,
+  /// [asset0, asset1]
+  pub volume: [Uint128; 2],
+  /// [asset0, asset1]
+  pub commission: [Uint128; 2],
+  /// tick index before the first swap of the period
+  pub open_tick_index: i32,
+  /// tick index after the last swap of the period
+  pub close_tick_index: i32,
+  pub swap_count: u64,
+}
+
+/// commission of the swap for the front-end, paid in the return asset
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Referral {
+  pub address: String,
+  pub commission_bps: u16,
+}
+
+impl Referral {
+  /// commission of the return amount, commission_bps is capped by max_commission_bps
+  pub fn commission_amount(&self, return_amount: Uint128, max_commission_bps: u16) -> Uint128 {
+    return_amount.multiply_ratio(self.commission_bps.min(max_commission_bps), 10000u128)
+  }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -55,7 +146,11 @@ pub enum ExecuteMsg {
 
   WithdrawLiquidity  {
     token_id: String,
-    amount: Option<Uint128>
+    amount: Option<Uint128>,
+    /// swap the other asset to this through the pair after withdraw
+    receive_asset: Option<AssetInfo>,
+    /// used with receive_asset
+    minimum_receive: Option<Uint128>,
   },
 
   Swap { 
@@ -63,12 +158,52 @@ pub enum ExecuteMsg {
     to: Option<String>,
     belief_price: Option<Decimal>,
     max_slippage: Option<Decimal>,
+    /// lower the max number of ticks of the pair for this swap
+    max_ticks: Option<u32>,
+    /// when the max ticks are reached, swap the filled part and refund the rest instead of fail
+    partial_fill: Option<bool>,
+    referral: Option<Referral>,
   },
 
   ClaimReward {
     token_id: String,
     rewards: [Asset; 2],
   },
+
+  /// swap part of the native offer asset and provide to a new position
+  ZapIn {
+    offer_asset: Asset,
+    tick_indexes: TickIndexes,
+    min_liquidity: Option<Uint128>,
+  },
+
+  /// swap of `WithdrawLiquidity` with `receive_asset`, only the pair itself can execute.
+  /// it runs after the reward of the position is claimed, so the position doesn't earn its own commission
+  ZapOut {
+    owner: String,
+    /// withdrawn amount of the other asset, swapped to the receive asset
+    offer_asset: Asset,
+    /// withdrawn amount of the receive asset
+    receive_amount: Uint128,
+    minimum_receive: Option<Uint128>,
+  },
+
+  /// only the factory can execute
+  UpdateConfig {
+    max_swap_ticks: Option<u32>,
+    /// history buckets over the new size are pruned on the next swap
+    history_config: Option<HistoryConfig>,
+  },
+
+  /// propose the new minter of the lp token, the new minter takes it with `ClaimMinter` of the lp token.
+  /// factory owner only (the factory itself while the timelock is set)
+  ProposeNewMinter {
+    minter: String,
+    expires: Expiration,
+  },
+
+  /// drop the minter proposal of the lp token, factory owner only
+  DropMinterProposal {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -78,6 +213,24 @@ pub enum Cw20HookMsg {
     to: Option<String>,
     belief_price: Option<Decimal>,
     max_slippage: Option<Decimal>,
+    max_ticks: Option<u32>,
+    partial_fill: Option<bool>,
+    referral: Option<Referral>,
+  },
+
+  ProvideLiquidity {
+    // the other side of the pair, sent cw20 is the first asset
+    other_asset: Asset,
+    // when provide to exist position put token_id
+    token_id: Option<String>,
+    // when make new position put tick_indexes
+    tick_indexes: Option<TickIndexes>
+  },
+
+  /// swap part of the sent cw20 and provide to a new position
+  ZapIn {
+    tick_indexes: TickIndexes,
+    min_liquidity: Option<Uint128>,
   },
 }
 
@@ -92,6 +245,8 @@ pub enum LpHookMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
   PairInfo {},
+
+  Config {},
   
   TickInfo {
     tick_index: i32
@@ -112,16 +267,72 @@ pub enum QueryMsg {
     token_id: String
   },
 
-  Simulation { offer_asset: Asset },
+  /// `detailed` to get swap steps of every tick and the price after the swap,
+  /// `trader` to apply the fee discount of the trader
+  Simulation {
+    offer_asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  },
 
-  ReverseSimulation { ask_asset: Asset },
+  ReverseSimulation {
+    ask_asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  },
 
   CumulativeVolume {},
+
+  History {
+    period: HistoryPeriod,
+    // start_time of the period
+    start_after: Option<u64>,
+    limit: Option<u32>,
+  },
+
+  /// price is asset1 per asset0, decimals are [asset0, asset1] (raw amount price if not given)
+  PriceToTickIndex {
+    price: Decimal256,
+    rounding: Rounding,
+    decimals: Option<[u8; 2]>,
+  },
+
+  /// price range of the tick index, `ProvideLiquidity` covers lower price of the lower_tick_index
+  /// to upper price of the upper_tick_index
+  TickIndexToPriceRange {
+    tick_index: i32,
+    decimals: Option<[u8; 2]>,
+  },
+
+  /// offer amount that moves the current price to the target price, `detailed` to get swap steps of every tick
+  AmountToPrice {
+    target_price: Decimal256,
+    decimals: Option<[u8; 2]>,
+    detailed: Option<bool>,
+  },
+
+  /// fee rate of the swaps of the trader, with the fee discount in the factory
+  EffectiveFee {
+    trader: String,
+  },
 }
 
 
 
 /// QueryResponses
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+  pub asset_infos: [AssetInfo; 2],
+  pub tick_space: u16,
+  pub fee_rate: Decimal,
+  pub liquidity_token: String,
+  /// not stored on pairs instantiated before the max swap ticks
+  pub factory: Option<String>,
+  pub max_swap_ticks: u32,
+  pub asset_decimals: Option<[u8; 2]>,
+  pub history_config: HistoryConfig,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct PairInfoResponse {
   pub liquidity_token: String,
@@ -129,9 +340,28 @@ pub struct PairInfoResponse {
   pub tick_space: u16,
   pub fee_rate: Decimal,
   pub price: Decimal256,
+  /// square root of the price, Q128.128
+  pub current_price_sqrt: Uint256,
   pub current_tick_index: i32
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PriceToTickIndexResponse {
+  pub tick_index: i32,
+  /// exact lower bound price of the tick index
+  pub price: Decimal256,
+  pub price_sqrt: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TickIndexToPriceRangeResponse {
+  pub tick_index: i32,
+  pub lower_price: Decimal256,
+  pub upper_price: Decimal256,
+  pub lower_price_sqrt: Uint256,
+  pub upper_price_sqrt: Uint256,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ProvideCalculationResponse {
   pub asset: Asset,
@@ -153,14 +383,66 @@ pub struct TickInfosResponse {
   pub infos: Vec<TickInfoResponse>
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryResponse {
+  pub infos: Vec<HistoryInfo>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EffectiveFeeResponse {
+  pub fee_rate: Decimal,
+  pub discount: Decimal,
+  /// fee_rate * (1 - discount)
+  pub effective_fee_rate: Decimal,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SimulationResponse {
   pub return_amount: Uint128,
   pub commission_amount: Uint128,
+  /// only for `detailed` simulation
+  #[serde(default)]
+  pub detail: Option<SimulationDetail>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ReverseSimulationResponse {
   pub offer_amount: Uint128,
   pub commission_amount: Uint128,
-}
\ No newline at end of file
+  /// only for `detailed` simulation
+  #[serde(default)]
+  pub detail: Option<SimulationDetail>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulationDetail {
+  pub steps: Vec<SwapStepResponse>,
+  /// tick index and price that the pool would reach
+  pub current_tick_index: i32,
+  pub current_price_sqrt: Uint256,
+  pub price: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmountToPriceResponse {
+  pub offer_asset: Asset,
+  /// commission is deducted
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+  /// price after the swap, can be slightly different from the target by rounding
+  pub price_sqrt: Uint256,
+  pub steps: Option<Vec<SwapStepResponse>>,
+}
+
+/// swap in a tick
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapStepResponse {
+  pub tick_index: i32,
+  pub liquidity: Uint128,
+  pub offer_amount: Uint128,
+  /// commission is deducted
+  pub return_amount: Uint128,
+  pub commission_amount: Uint128,
+  pub price_sqrt_before: Uint256,
+  pub price_sqrt_after: Uint256,
+}
@@ -0,0 +1,150 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal, Uint128};
+use cw0::Expiration;
+use cw20::Cw20ReceiveMsg;
+
+use crate::asset::{Asset, AssetInfo};
+use crate::pair::{Referral, TickIndexes};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InstantiateMsg {
+  pub asset_infos: [AssetInfo; 2],
+  pub token_code_id: u64,
+  pub tick_space: u16,
+  pub fee_rate: Decimal,
+  /// amplification factor, 1 ~ 1000000
+  pub amp: u64,
+  /// decimals of asset_infos, set by the factory
+  pub asset_decimals: Option<[u8; 2]>,
+}
+
+/// same with `pair::ExecuteMsg` except amp messages, positions are always the whole pool
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+  /// `pair::Cw20HookMsg`
+  Receive(Cw20ReceiveMsg),
+
+  ProvideLiquidity {
+    assets: [Asset; 2],
+    // when provide to exist position put token_id
+    token_id: Option<String>,
+    // not used
+    tick_indexes: Option<TickIndexes>
+  },
+
+  WithdrawLiquidity  {
+    token_id: String,
+    amount: Option<Uint128>,
+    /// swap the other asset to this through the pair after withdraw
+    receive_asset: Option<AssetInfo>,
+    /// used with receive_asset
+    minimum_receive: Option<Uint128>,
+  },
+
+  Swap {
+    offer_asset: Asset,
+    to: Option<String>,
+    belief_price: Option<Decimal>,
+    max_slippage: Option<Decimal>,
+    referral: Option<Referral>,
+  },
+
+  ClaimReward {
+    token_id: String,
+    rewards: [Asset; 2],
+  },
+
+  /// provide only one of the assets
+  ZapIn {
+    offer_asset: Asset,
+    // not used
+    tick_indexes: TickIndexes,
+    min_liquidity: Option<Uint128>,
+  },
+
+  /// swap of `WithdrawLiquidity` with `receive_asset`, only the pair itself can execute.
+  /// it runs after the reward of the position is claimed, so the position doesn't earn its own commission
+  ZapOut {
+    owner: String,
+    /// withdrawn amount of the other asset, swapped to the receive asset
+    offer_asset: Asset,
+    /// withdrawn amount of the receive asset
+    receive_amount: Uint128,
+    minimum_receive: Option<Uint128>,
+  },
+
+  /// change amp linearly until next_amp_time, factory owner only (the factory itself while the timelock is set)
+  RampAmp {
+    next_amp: u64,
+    next_amp_time: u64,
+  },
+
+  /// stop ramping at the current amp, factory owner only
+  StopRampAmp {},
+
+  /// same with the pair's `ProposeNewMinter`
+  ProposeNewMinter {
+    minter: String,
+    expires: Expiration,
+  },
+
+  /// same with the pair's `DropMinterProposal`
+  DropMinterProposal {},
+}
+
+/// same with `pair::QueryMsg` for the supported queries
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+  PairInfo {},
+
+  TickInfo {
+    tick_index: i32
+  },
+
+  TickInfos {
+    start_after: Option<i32>,
+    limit: Option<u32>,
+  },
+
+  WithdrawCalculation {
+    token_id: String
+  },
+
+  Simulation {
+    offer_asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  },
+
+  ReverseSimulation {
+    ask_asset: Asset,
+    detailed: Option<bool>,
+    trader: Option<String>,
+  },
+
+  EffectiveFee {
+    trader: String,
+  },
+
+  Pool {},
+
+  AmpConfig {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PoolResponse {
+  pub assets: [Asset; 2],
+  pub total_liquidity: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AmpConfigResponse {
+  /// amp at the current block time
+  pub amp: u64,
+  pub next_amp: u64,
+  pub next_amp_time: u64,
+}
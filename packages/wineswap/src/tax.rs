@@ -0,0 +1,145 @@
+use cosmwasm_std::{QuerierWrapper, StdResult, Uint128};
+#[cfg(feature = "terra")]
+use cosmwasm_std::Decimal;
+#[cfg(feature = "terra")]
+use terra_cosmwasm::TerraQuerier;
+
+#[cfg(test)]
+use cosmwasm_std::testing::MockQuerier;
+#[cfg(test)]
+use crate::asset::{Asset, AssetInfo};
+#[cfg(all(test, feature = "terra"))]
+use cosmwasm_std::{to_binary, ContractResult, SystemResult};
+#[cfg(all(test, feature = "terra"))]
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper};
+
+#[cfg(feature = "terra")]
+static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+
+/// tax charged by the chain when native token is sent
+pub trait TaxPolicy {
+  /// tax included in the `amount` of `denom`
+  fn compute_tax(querier: &QuerierWrapper, denom: &str, amount: Uint128) -> StdResult<Uint128>;
+}
+
+/// chains without tax
+pub struct ZeroTax;
+
+impl TaxPolicy for ZeroTax {
+  fn compute_tax(_querier: &QuerierWrapper, _denom: &str, _amount: Uint128) -> StdResult<Uint128> {
+    Ok(Uint128::zero())
+  }
+}
+
+/// terra treasury tax, luna is tax free
+#[cfg(feature = "terra")]
+pub struct TerraTax;
+
+#[cfg(feature = "terra")]
+impl TaxPolicy for TerraTax {
+  fn compute_tax(querier: &QuerierWrapper, denom: &str, amount: Uint128) -> StdResult<Uint128> {
+    if denom == "uluna" {
+      return Ok(Uint128::zero())
+    }
+
+    let terra_querier = TerraQuerier::new(querier);
+    let tax_rate: Decimal = (terra_querier.query_tax_rate()?).rate;
+    let tax_cap: Uint128 = (terra_querier.query_tax_cap(denom.to_string())?).cap;
+    Ok(std::cmp::min(
+      amount.checked_sub(amount.multiply_ratio(
+        DECIMAL_FRACTION,
+        DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
+      ))?,
+      tax_cap,
+    ))
+  }
+}
+
+/// tax policy of the chain the contracts are built for
+#[cfg(feature = "terra")]
+pub type ChainTax = TerraTax;
+
+#[cfg(not(feature = "terra"))]
+pub type ChainTax = ZeroTax;
+
+#[cfg(test)]
+fn native_asset(denom: &str, amount: u128) -> Asset {
+  Asset {
+    info: AssetInfo::NativeToken { denom: denom.to_string() },
+    amount: Uint128::from(amount),
+  }
+}
+
+#[test]
+fn zero_tax_test() {
+  let querier: MockQuerier = MockQuerier::new(&[]);
+  let querier = QuerierWrapper::new(&querier);
+
+  for amount in [0u128, 1_000_000u128, 1_000_000_000_000u128] {
+    let asset = native_asset("uusd", amount);
+    assert_eq!(ZeroTax::compute_tax(&querier, "uusd", asset.amount).unwrap(), Uint128::zero());
+    assert_eq!(asset.compute_tax_with::<ZeroTax>(&querier).unwrap(), Uint128::zero());
+    assert_eq!(asset.deduct_tax_with::<ZeroTax>(&querier).unwrap().amount, asset.amount);
+  }
+}
+
+// treasury querier with the tax rate and the same tax cap for every denom
+#[cfg(all(test, feature = "terra"))]
+fn mock_terra_querier(rate: Decimal, cap: u128) -> MockQuerier<TerraQueryWrapper> {
+  MockQuerier::<TerraQueryWrapper>::new(&[]).with_custom_handler(move |query| match &query.query_data {
+    TerraQuery::TaxRate {} => SystemResult::Ok(ContractResult::from(to_binary(&TaxRateResponse { rate }))),
+    TerraQuery::TaxCap { .. } => {
+      SystemResult::Ok(ContractResult::from(to_binary(&TaxCapResponse { cap: Uint128::from(cap) })))
+    }
+    _ => panic!("DO NOT ENTER HERE"),
+  })
+}
+
+#[cfg(feature = "terra")]
+#[test]
+fn terra_tax_rate_test() {
+  let querier = mock_terra_querier(Decimal::percent(1), 1_000_000);
+  let querier = QuerierWrapper::new(&querier);
+
+  // tax is included in the amount, 1_000_000 = 990_099 + 1% of it
+  let asset = native_asset("uusd", 1_000_000);
+  assert_eq!(TerraTax::compute_tax(&querier, "uusd", asset.amount).unwrap(), Uint128::from(9_901u128));
+  assert_eq!(asset.compute_tax_with::<TerraTax>(&querier).unwrap(), Uint128::from(9_901u128));
+  assert_eq!(asset.deduct_tax_with::<TerraTax>(&querier).unwrap().amount, Uint128::from(990_099u128));
+}
+
+#[cfg(feature = "terra")]
+#[test]
+fn terra_tax_cap_test() {
+  let querier = mock_terra_querier(Decimal::percent(1), 100);
+  let querier = QuerierWrapper::new(&querier);
+
+  let asset = native_asset("uusd", 1_000_000);
+  assert_eq!(TerraTax::compute_tax(&querier, "uusd", asset.amount).unwrap(), Uint128::from(100u128));
+  assert_eq!(asset.deduct_tax_with::<TerraTax>(&querier).unwrap().amount, Uint128::from(999_900u128));
+}
+
+#[cfg(feature = "terra")]
+#[test]
+fn terra_tax_zero_test() {
+  let querier = mock_terra_querier(Decimal::percent(1), 1_000_000);
+  let querier = QuerierWrapper::new(&querier);
+
+  // luna is tax free
+  let asset = native_asset("uluna", 1_000_000);
+  assert_eq!(TerraTax::compute_tax(&querier, "uluna", asset.amount).unwrap(), Uint128::zero());
+  assert_eq!(asset.deduct_tax_with::<TerraTax>(&querier).unwrap().amount, asset.amount);
+
+  // zero amount and zero rate
+  assert_eq!(TerraTax::compute_tax(&querier, "uusd", Uint128::zero()).unwrap(), Uint128::zero());
+  let querier = mock_terra_querier(Decimal::zero(), 1_000_000);
+  let querier = QuerierWrapper::new(&querier);
+  assert_eq!(TerraTax::compute_tax(&querier, "uusd", Uint128::from(1_000_000u128)).unwrap(), Uint128::zero());
+
+  // token asset is never taxed
+  let token = Asset {
+    info: AssetInfo::Token { contract_addr: "token".to_string() },
+    amount: Uint128::from(1_000_000u128),
+  };
+  assert_eq!(token.compute_tax_with::<TerraTax>(&querier).unwrap(), Uint128::zero());
+}